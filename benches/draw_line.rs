@@ -0,0 +1,79 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+//! Strokes glyph outlines, which draws each of their straight edges with
+//! `draw_line`. Run with `cargo bench`
+#![feature(allocator_api, test)]
+
+extern crate test;
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/testing.rs"]
+mod testing;
+
+use glfont::{
+    render::{
+        FormattedSlice,
+        RenderMode,
+    },
+    FontCollection,
+    Renderer,
+};
+
+/// Font whose 'O' is a polygon of `sides` straight edges, covering every angle
+/// the lines are drawn at
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn polygon_font(sides: u16) -> Vec<u8> {
+    let points: Vec<_> = (0..sides)
+        .map(|idx| {
+            let angle = f32::from(idx) * core::f32::consts::TAU / f32::from(sides);
+            let (sin, cos) = angle.sin_cos();
+            (
+                400.0f32.mul_add(cos, 500.0) as i16,
+                400.0f32.mul_add(sin, 500.0) as i16,
+                true,
+            )
+        })
+        .collect();
+    testing::font(
+        &[(Vec::new(), 500), (testing::simple_glyph(&[&points]), 1000)],
+        &[('O', 1)],
+    )
+}
+
+/// Strokes the outline of `text` at `size`
+fn bench_outline(
+    bencher: &mut test::Bencher,
+    sides: u16,
+    size: u16,
+    text: &str,
+) {
+    let mut fonts = FontCollection::new(std::alloc::Global);
+    let id = fonts
+        .add_loaded(glfont::parse_font_bytes(&polygon_font(sides)).expect("Font should parse"));
+    let slices = [FormattedSlice {
+        id,
+        size,
+        text,
+        ruby: None,
+    }];
+
+    let width = usize::from(size) * text.len() + usize::from(size);
+    let mut buffer = vec![rgb::Gray::new(0u8); width * usize::from(size) * 2];
+    bencher.iter(|| {
+        Renderer::new(&fonts)
+            .mode(RenderMode::Outline)
+            .draw(&slices, &mut buffer, width)
+            .expect("Render should succeed")
+    });
+}
+
+#[bench]
+fn short_lines(bencher: &mut test::Bencher) {
+    bench_outline(bencher, 256, 24, "OOOOOOOOOOOOOOOO");
+}
+
+#[bench]
+fn long_lines(bencher: &mut test::Bencher) {
+    bench_outline(bencher, 8, 256, "OO");
+}
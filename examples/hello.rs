@@ -10,9 +10,9 @@ use std::{
 
 use glfont::{
     render::SubPixelAlignment,
-    render_to_buf,
     FontCollection,
     FontTrait,
+    Renderer,
 };
 
 // Would use WM supplied info to calc this in actual use
@@ -36,17 +36,14 @@ fn main() -> Result<(), Box<dyn Error>> {
         id:   font,
         size: 48,
         text: "fuck off",
+        ruby: None,
     }];
 
     let mut display_buf = [rgb::Gray::<u8>::new(u8::MAX); 512 * 342];
-    render_to_buf(
-        &fonts,
-        &formatted,
-        &mut display_buf,
-        512,
-        DPI,
-        SubPixelAlignment::None,
-    )?;
+    Renderer::new(&fonts)
+        .dpi(DPI)
+        .subpixel(SubPixelAlignment::None)
+        .draw(&formatted, &mut display_buf, 512)?;
 
     let path = std::path::Path::new("./line.png");
     let file = std::fs::File::create(path)?;
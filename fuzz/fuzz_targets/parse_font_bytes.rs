@@ -0,0 +1,9 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = glfont::parse_font_bytes(data);
+});
@@ -1,17 +1,45 @@
 // Copyright (C) 2024 GLStudios
 // SPDX-License-Identifier: LGPL-2.1-only
 
+use itertools::{
+    EitherOrBoth,
+    Itertools,
+};
+
 use crate::{
     tables::{
-        glyf::Glyph,
+        cbdt::ColorBitmap,
+        cmap::Type as Cmap,
+        glyf::{
+            Glyph,
+            PathCmd,
+            MAX_COMPONENT_DEPTH,
+        },
+        head::Type as Head,
+        hhea::{
+            CaretSlope,
+            Type as Hhea,
+        },
+        maxp::Type as Maxp,
         name::RecordType,
+        os2::{
+            Type as Os2,
+            UnicodeBlock,
+            UNICODE_BLOCKS,
+        },
         parse_table,
+        pclt::Type as Pclt,
         Table,
     },
     types::{
         ChecksumReader,
+        CoreBox,
         CoreRead,
+        CoreReadError,
         CoreVec,
+        LimitReader,
+        SecondaryMap,
+        SliceReader,
         Slotmap,
         SlotmapKey,
         ValidType,
@@ -19,12 +47,130 @@ use crate::{
     ParseError,
 };
 
-// A: core::alloc::Allocator + core::fmt::Debug + 'static
-pub type Font<A = alloc::alloc::Global> = CoreVec<Table<A>, A>;
+#[derive(Debug)]
+pub struct Font<A: core::alloc::Allocator + core::fmt::Debug + 'static = alloc::alloc::Global> {
+    tables:  CoreVec<Table<A>, A>,
+    layout:  CoreVec<TableLayout, A>,
+    #[cfg(feature = "std")]
+    timings: CoreVec<([u8; 4], std::time::Duration), A>,
+
+    outline_format: OutlineFormat,
+}
+
+/// Kind of glyph data a font carries, by precedence of the tables present
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutlineFormat {
+    /// `glyf` quadratic outlines
+    TrueType,
+    /// `CFF ` or `CFF2` cubic outlines
+    Cff,
+    /// Only `sbix`, `CBDT` or `EBDT` bitmaps
+    Bitmap,
+    None,
+}
+
+impl OutlineFormat {
+    const fn from_tag(tag: [u8; 4]) -> Self {
+        match &tag {
+            b"glyf" => Self::TrueType,
+            b"CFF " | b"CFF2" => Self::Cff,
+            b"sbix" | b"CBDT" | b"EBDT" => Self::Bitmap,
+            _ => Self::None,
+        }
+    }
+
+    fn from_directory(directory: &[TableLayout]) -> Self {
+        // Variants are declared in order of precedence
+        directory
+            .iter()
+            .fold(Self::None, |format, t| format.min(Self::from_tag(t.tag)))
+    }
+}
+
+/// Location of a table within the original font file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableLayout {
+    pub tag:      [u8; 4],
+    pub offset:   u32,
+    pub length:   u32,
+    pub checksum: u32,
+}
+
+/// Vertical metrics of a font at a size, in pixels above the baseline
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PpemMetrics {
+    pub ascent:     f32,
+    /// Negative below the baseline
+    pub descent:    f32,
+    pub line_gap:   f32,
+    /// `None` without `OS/2` version 2+
+    pub cap_height: Option<f32>,
+    /// `None` without `OS/2` version 2+
+    pub x_height:   Option<f32>,
+}
+
+/// What a font covers and is called, for indexing fonts in one call
+#[derive(Debug, Clone)]
+pub struct FontSummary<'a, A: core::alloc::Allocator> {
+    /// Typographic family (name ID 16), else the family (name ID 1)
+    pub family:  Option<&'a str>,
+    /// Typographic subfamily (name ID 17), else the subfamily (name ID 2), e.g.
+    /// "Bold Italic"
+    pub style:   Option<&'a str>,
+    /// Blocks with a character mapped by `cmap` or their `OS/2`
+    /// `ulUnicodeRange` bit set, in code point order
+    pub blocks:  CoreVec<&'static UnicodeBlock, A>,
+    /// OpenType tags of the scripts written with `blocks`, sorted
+    pub scripts: CoreVec<[u8; 4], A>,
+}
+
+/// Horizontal metrics and bounding box of a glyph, in font units
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphMetrics {
+    pub advance:           u16,
+    pub left_side_bearing: i16,
+    /// (`x_min`, `y_min`, `x_max`, `y_max`)
+    pub bbox:              (i16, i16, i16, i16),
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Keep the table directory, exposed through [`Font::layout`]
+    pub record_layout:     bool,
+    /// Keep each glyph's hinting instructions, exposed through
+    /// [`Glyph::instructions`]
+    pub keep_instructions: bool,
+    /// Time how long each table takes to parse, exposed through
+    /// [`Font::timings`]
+    #[cfg(feature = "std")]
+    pub collect_timing:    bool,
+    /// `cmap` (platform id, encoding id) subtable to map characters with, in
+    /// place of the best Unicode one. Ignored when the font lacks it or it's
+    /// in an unsupported format
+    pub preferred_cmap:    Option<(u16, u16)>,
+
+    /// Limits on the work done parsing untrusted fonts, exceeding one fails
+    /// with [`ParseError::BudgetExceeded`]
+    pub max_tables:       Option<usize>,
+    pub max_glyphs:       Option<usize>,
+    /// Summed over every simple glyph
+    pub max_total_points: Option<usize>,
+}
+
+/// Change to a glyph between two fonts, by glyph id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphDiff {
+    Added(u32),
+    Removed(u32),
+    Modified(u32),
+}
 
 pub struct Collection<A: core::alloc::Allocator + core::fmt::Debug + 'static = alloc::alloc::Global>
 {
-    loaded: Slotmap<Font<A>, A>,
+    loaded:   Slotmap<Font<A>, A>,
+    fallback: Option<SlotmapKey>,
+    // Source fonts of each merged face, created on the first merge
+    merged:   Option<SecondaryMap<CoreVec<SlotmapKey, A>, A>>,
 }
 
 pub trait Trait<A: core::alloc::Allocator> {
@@ -33,94 +179,401 @@ pub trait Trait<A: core::alloc::Allocator> {
         record_type: RecordType,
     ) -> Option<&str>;
     fn id(&self) -> Option<&str>;
+    /// Name ID 6 exactly as stored, which may break the PostScript naming
+    /// rules in badly made fonts
+    fn postscript_name(&self) -> Option<&str>;
+    /// [`Trait::postscript_name`] reduced to the characters PostScript allows,
+    /// printable ASCII without `[](){}<>/%`, and at most 63 of them. `None`
+    /// if nothing is left
+    fn postscript_name_sanitized(&self) -> Option<CoreBox<str, A>>
+    where
+        A: Clone;
+    /// TrueType outline of `glyph_id`, `None` when out of range or when the
+    /// font has no `glyf` outlines, see [`Trait::has_outlines`]
     fn glyph(
         &self,
         glyph_id: u32,
     ) -> Option<&Glyph<A>>;
-    fn units_per_em(&self) -> u16;
+    /// Whether `glyf` outlines were parsed. False for CFF and bitmap-only
+    /// fonts, whose glyphs need their own tables
+    fn has_outlines(&self) -> bool;
+    /// Adjustment to the advance of `left` when followed by `right` from the
+    /// legacy `kern` table, in font units. 0 without one
+    fn kern_pair(
+        &self,
+        left: u32,
+        right: u32,
+    ) -> i16;
+    /// PostScript name of `glyph_id` from `post`, `None` for fonts without
+    /// glyph names
+    fn glyph_name(
+        &self,
+        glyph_id: u32,
+    ) -> Option<&str>;
+    /// Number of glyphs declared by `maxp`
+    fn num_glyphs(&self) -> u32;
+    /// Every glyph in `glyf`, in glyph id order. Glyphs without outlines are
+    /// yielded empty so indices match glyph ids
+    fn glyphs(&self) -> core::slice::Iter<'_, Glyph<A>>;
+    /// Glyph `c` maps to through the font's best Unicode `cmap` subtable, or
+    /// the one pinned by [`Options::preferred_cmap`]. `None` when unmapped
+    fn glyph_id(
+        &self,
+        c: char,
+    ) -> Option<u32>;
+    /// `None` when the font has no `head`
+    fn units_per_em(&self) -> Option<u16>;
+    /// `(x_min, y_min, x_max, y_max)` of every glyph from `head`, in font
+    /// units. All 0 when the font has no `head`
+    fn bbox(&self) -> (i16, i16, i16, i16);
+    fn outline_format(&self) -> OutlineFormat;
+    fn pclt(&self) -> Option<&Pclt<A>>;
+    fn os2(&self) -> Option<&Os2<A>>;
+    fn maxp(&self) -> Option<&Maxp<A>>;
+    /// `OS/2` weight class, 400 (regular) when the font has no `OS/2`
+    fn weight_class(&self) -> u16;
+    /// `OS/2` width class, 5 (normal) when the font has no `OS/2`
+    fn width_class(&self) -> u16;
+    /// In font units, from `OS/2` version 2+
+    fn cap_height(&self) -> Option<i16>;
+    /// In font units, from `OS/2` version 2+
+    fn x_height(&self) -> Option<i16>;
+    /// `hhea` ascender in font units, 0 when the font has no `hhea`
+    fn ascent(&self) -> i16;
+    /// `hhea` descender in font units, negative below the baseline
+    fn descent(&self) -> i16;
+    /// `hhea` gap between the descent of one line and the ascent of the next
+    fn line_gap(&self) -> i16;
+    /// `hhea` caret slope as (rise, run), (1, 0) being vertical. Vertical when
+    /// the font has no `hhea`
+    fn caret_slope(&self) -> (i16, i16);
+    /// `hhea` shift applied to a slanted caret, in font units
+    fn caret_offset(&self) -> i16;
+    /// Coordinate of `script`'s default horizontal baseline from `BASE`, in
+    /// font units
+    fn baseline_offset(
+        &self,
+        script: [u8; 4],
+    ) -> Option<i16>;
+    /// PNG image of `glyph_id` from the `CBDT` strike best matching `ppem`,
+    /// the smallest at least as large, else the largest. The image is for the
+    /// strike's size, which [`ColorBitmap::decode`] scales it from
+    fn color_bitmap(
+        &self,
+        glyph_id: u32,
+        ppem: u16,
+    ) -> Option<ColorBitmap<'_>>;
+    fn glyph_to_cubic_path(
+        &self,
+        glyph_id: u32,
+    ) -> Option<CoreVec<PathCmd, A>>
+    where
+        A: Clone;
+    fn advance_width(
+        &self,
+        glyph_id: u32,
+    ) -> Option<u16>;
+    /// Resolves the metrics of every glyph in `glyph_ids` with a single table
+    /// lookup. Metrics missing from `hmtx` or `glyf` are zeroed
+    fn glyph_metrics_batch(
+        &self,
+        glyph_ids: &[u32],
+    ) -> CoreVec<GlyphMetrics, A>
+    where
+        A: Clone;
+    /// Advance of `glyph_id` in pixels at `ppem`, 0 if the glyph has no metrics
+    /// or the font no `head`
+    fn advance_px(
+        &self,
+        glyph_id: u32,
+        ppem: f32,
+    ) -> f32;
+    /// Vertical metrics scaled to pixels at `ppem`, `None` if the font has no
+    /// `head`
+    fn ppem_metrics(
+        &self,
+        ppem: f32,
+    ) -> Option<PpemMetrics>;
+    /// Names, Unicode blocks and scripts of the font. Only the blocks of
+    /// [`UNICODE_BLOCKS`] are reported
+    fn font_summary(&self) -> FontSummary<'_, A>
+    where
+        A: Clone;
+}
+
+/// `(searchRange, entrySelector, rangeShift)` of a table directory with
+/// `num_tables` records, in u32 as `num_tables * 16` overflows u16 past 4095
+/// tables
+fn search_hints(num_tables: u16) -> (u32, u32, u32) {
+    let entry_selector = num_tables.checked_ilog2().unwrap_or(0);
+    let search_range = 2_u32.pow(entry_selector) * 16;
+    let range_shift = (u32::from(num_tables) * 16).saturating_sub(search_range);
+    (search_range, entry_selector, range_shift)
 }
 
-fn verify_header<R: CoreRead>(input: &mut R) -> Result<u16, ParseError<R::IoError>> {
-    let mut version = [0; 4];
-    input.read(&mut version)?;
-    if version != [0x00, 0x01, 0x00, 0x00] {
+/// Returns the sfntVersion, `0x00010000` for TrueType outlines or `OTTO`
+/// for CFF, and the number of tables
+fn verify_header<R: CoreRead>(input: &mut R) -> Result<([u8; 4], u16), ParseError<R::IoError>> {
+    let version: [u8; 4] = input.read_array()?;
+    if version != [0x00, 0x01, 0x00, 0x00] && version != *b"OTTO" {
         return Err(ParseError::InvalidSfntVersion(version));
     }
 
     let num_tables: u16 = input.read_int()?;
     tracing::trace!("NumTables: {num_tables}");
-
-    let search_range: u16 = input.read_int()?;
-    if search_range != 2_u16.pow(num_tables.ilog2()) * 16 {
+    if num_tables == 0 {
         return Err(ParseError::Parsing {
-            variable: "SearchRange",
-            expected: ValidType::U16(2_u16.pow(num_tables.ilog2()) * 16),
-            parsed:   ValidType::U16(search_range),
+            variable: "numTables",
+            expected: ValidType::U16(1),
+            parsed:   ValidType::U16(num_tables),
         });
     }
 
+    let (search_range_act, entry_selector_act, range_shift_act) = search_hints(num_tables);
+
+    // Only hints for a binary search, which plenty of fonts get wrong
+    let search_range: u16 = input.read_int()?;
     let entry_selector: u16 = input.read_int()?;
-    if entry_selector != u16::try_from(num_tables.ilog2()).expect("ilog2 downcast failed") {
-        return Err(ParseError::Parsing {
-            variable: "EntrySelector",
-            expected: ValidType::U32(num_tables.ilog2()),
-            parsed:   ValidType::U16(entry_selector),
+    let range_shift: u16 = input.read_int()?;
+    if (
+        u32::from(search_range),
+        u32::from(entry_selector),
+        u32::from(range_shift),
+    ) != (search_range_act, entry_selector_act, range_shift_act)
+    {
+        tracing::warn!(
+            "Ignoring searchRange {search_range}, entrySelector {entry_selector} and \
+             rangeShift {range_shift}, expected {search_range_act}, {entry_selector_act} and \
+             {range_shift_act}"
+        );
+    }
+
+    Ok((version, num_tables))
+}
+
+/// Reads the header and table directory of a single font
+fn read_directory<A: core::alloc::Allocator + Copy, R: CoreRead>(
+    allocator: A,
+    reader: &mut R,
+    options: &Options,
+) -> Result<CoreVec<TableLayout, A>, ParseError<R::IoError>> {
+    let (version, num_tables) = verify_header(reader)?;
+    if let Some(limit) = options
+        .max_tables
+        .filter(|limit| usize::from(num_tables) > *limit)
+    {
+        return Err(ParseError::BudgetExceeded {
+            budget: "max_tables",
+            limit,
         });
     }
 
-    let range_shift: u16 = input.read_int()?;
-    if range_shift != num_tables * 16 - search_range {
-        return Err(ParseError::Parsing {
-            variable: "RangeShift",
-            expected: ValidType::U16(num_tables * 16 - search_range),
-            parsed:   ValidType::U16(range_shift),
+    let mut directory = CoreVec::with_capacity_in(num_tables as usize, allocator);
+    for _ in 0..num_tables {
+        directory.push(TableLayout {
+            tag:      reader.read_array()?,
+            checksum: reader.read_int()?,
+            offset:   reader.read_int()?,
+            length:   reader.read_int()?,
         });
     }
 
-    Ok(num_tables)
+    if version == *b"OTTO" && OutlineFormat::from_directory(&directory) != OutlineFormat::Cff {
+        tracing::event!(tracing::Level::WARN, "OTTO font without CFF outlines");
+    }
+
+    Ok(directory)
 }
 
-/// # Panics
-/// - If Slice of size `N` is unable to cast to array of type `[u8; N]`
-/// - If Downcast fails
-#[tracing::instrument(level = "trace", skip_all)]
+/// # Errors
+/// - If `input` is not a valid font
 pub fn open_font<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static, R: CoreRead>(
     allocator: A,
     input: &mut R,
 ) -> Result<Font<A>, ParseError<R::IoError>> {
-    let mut reader = ChecksumReader::new(input);
+    open_font_with_options(allocator, input, &Options::default())
+}
 
-    let num_tables = verify_header(&mut reader)?;
-    let mut tables = CoreVec::with_capacity_in(num_tables as usize, allocator);
+/// Opens a font embedded `base_offset` bytes into `input`, e.g. after a
+/// container's header. Table offsets are taken relative to `base_offset`
+/// # Errors
+/// - If `input` ends before `base_offset`
+/// - If the data at `base_offset` is not a valid font
+pub fn open_font_at<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static, R: CoreRead>(
+    allocator: A,
+    input: &mut R,
+    base_offset: usize,
+) -> Result<Font<A>, ParseError<R::IoError>> {
+    let skipped = input.skip(base_offset)?;
+    if skipped != base_offset {
+        return Err(ParseError::UnexpectedEop {
+            location: "base_offset",
+            needed:   base_offset - skipped,
+        });
+    }
 
-    for _ in 0..num_tables {
-        let mut tag = [0u8; 4];
-        let read = reader.read(&mut tag)?;
-        if read != tag.len() {
-            return Err(ParseError::UnexpectedEop {
-                location: "TableRecord",
-                needed:   tag.len() - read,
-            });
-        }
+    open_font(allocator, input)
+}
+
+/// # Errors
+/// - If `input` is not a valid font
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn open_font_with_options<
+    A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static,
+    R: CoreRead,
+>(
+    allocator: A,
+    input: &mut R,
+    options: &Options,
+) -> Result<Font<A>, ParseError<R::IoError>> {
+    parse_font(allocator, input, options, None)
+}
+
+/// Parses as much of `input` as possible, collecting every error in tables
+/// that could be skipped over instead of stopping at the first
+///
+/// The font is `None` when parsing couldn't continue, with the error that
+/// stopped it last
+#[allow(clippy::type_complexity)]
+pub fn open_font_collect_errors<
+    A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static,
+    R: CoreRead,
+>(
+    allocator: A,
+    input: &mut R,
+) -> (Option<Font<A>>, CoreVec<ParseError<R::IoError>, A>) {
+    let mut errors = CoreVec::new_in(allocator);
+    match parse_font(allocator, input, &Options::default(), Some(&mut errors)) {
+        Ok(font) => (Some(font), errors),
+        Err(error) => {
+            errors.push(error);
+            (None, errors)
+        },
+    }
+}
+
+/// Whether parsing can carry on past `error` from a single table, as the
+/// reader is still positioned by the table directory
+const fn is_recoverable<E: core::fmt::Debug>(error: &ParseError<E>) -> bool {
+    !matches!(
+        error,
+        ParseError::Io(CoreReadError::Io(_)) | ParseError::BudgetExceeded { .. }
+    )
+}
+
+/// Notes a `head` direction hint contradicted by the characters `cmap` maps,
+/// which legacy renderers may still trust
+fn check_direction_hint<A: core::alloc::Allocator + core::fmt::Debug + 'static>(
+    tables: &[Table<A>]
+) {
+    // Checking means looking up thousands of characters
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return;
+    }
 
-        let checksum: u32 = reader.read_int()?;
-        let offset: u32 = reader.read_int()?;
-        let length: u32 = reader.read_int()?;
+    let head = tables.iter().find_map(|t| match t {
+        Table::Head(head) => Some(head),
+        _ => None,
+    });
+    let cmap = tables.iter().find_map(|t| match t {
+        Table::Cmap(cmap) => Some(cmap),
+        _ => None,
+    });
+    let (Some(head), Some(cmap)) = (head, cmap) else {
+        return;
+    };
+
+    if direction_hint_contradicted(head, cmap) {
+        tracing::event!(
+            tracing::Level::DEBUG,
+            "head::fontDirectionHint {} contradicts the characters in cmap",
+            head.font_direction_hint
+        );
+    }
+}
+
+/// Whether `head` claims strongly left-to-right glyphs while `cmap` maps
+/// right-to-left characters, or the reverse
+fn direction_hint_contradicted<A: core::alloc::Allocator>(
+    head: &Head<A>,
+    cmap: &Cmap<A>,
+) -> bool {
+    let maps_any = |ranges: &[core::ops::RangeInclusive<char>]| {
+        ranges
+            .iter()
+            .any(|range| range.clone().any(|c| cmap.glyph_id(c).is_some()))
+    };
+    match head.font_direction_hint {
+        // Hebrew through Arabic Extended-A, and their presentation forms
+        1 | 2 => maps_any(&[
+            '\u{0590}'..='\u{08ff}',
+            '\u{fb1d}'..='\u{fdff}',
+            '\u{fe70}'..='\u{feff}',
+        ]),
+        -2 | -1 => maps_any(&['A'..='Z', 'a'..='z']),
+        _ => false,
+    }
+}
 
-        tables.push((tag, checksum, offset as usize, length as usize));
+/// Pushes `error` to `errors` if given and it [`is_recoverable`], otherwise
+/// returns it
+fn collect_error<A: core::alloc::Allocator, E: core::fmt::Debug>(
+    errors: Option<&mut CoreVec<ParseError<E>, A>>,
+    error: ParseError<E>,
+) -> Result<(), ParseError<E>> {
+    match errors {
+        Some(errors) if is_recoverable(&error) => {
+            errors.push(error);
+            Ok(())
+        },
+        _ => Err(error),
     }
+}
+
+#[allow(clippy::too_many_lines)]
+fn parse_font<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static, R: CoreRead>(
+    allocator: A,
+    input: &mut R,
+    options: &Options,
+    mut errors: Option<&mut CoreVec<ParseError<R::IoError>, A>>,
+) -> Result<Font<A>, ParseError<R::IoError>> {
+    let mut reader = ChecksumReader::new(input);
+
+    let mut directory = read_directory(allocator, &mut reader, options)?;
+    let layout = if options.record_layout {
+        directory.clone()
+    } else {
+        CoreVec::new_in(allocator)
+    };
+    let outline_format = OutlineFormat::from_directory(&directory);
 
     tracing::event!(
         name: "Header",
         tracing::Level::TRACE,
         "Bytes read: {}", reader.total_read()
     );
-    let mut parsed_tables = Vec::new_in(allocator);
+    let mut parsed_tables = CoreVec::new_in(allocator);
+    #[cfg(feature = "std")]
+    let mut timings = CoreVec::new_in(allocator);
 
     let mut checksum_adj = 0;
 
-    tables.sort_by(|(_, _, a, _), (_, _, b, _)| a.cmp(b));
-    for (tag, checksum, offset, length) in tables {
+    directory.sort_by_key(|t| t.offset);
+    for TableLayout {
+        tag,
+        offset,
+        length,
+        checksum,
+    } in directory
+    {
+        let (offset, length) = (offset as usize, length as usize);
+        if offset < reader.total_read() {
+            return Err(ParseError::Parsing {
+                variable: "TableRecord::offset",
+                expected: ValidType::USize(reader.total_read()),
+                parsed:   ValidType::USize(offset),
+            });
+        }
         if offset != reader.total_read() {
             tracing::event!(
                 tracing::Level::WARN,
@@ -139,28 +592,34 @@ pub fn open_font<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static,
             ValidType::Tag(tag)
         );
 
-        let parsed = parse_table(allocator, &parsed_tables, tag, &mut tag_reader);
+        #[cfg(feature = "std")]
+        let start = options.collect_timing.then(std::time::Instant::now);
+        let parsed = parse_table(
+            allocator,
+            options,
+            &parsed_tables,
+            tag,
+            &mut LimitReader::new(&mut tag_reader, length),
+        );
+        #[cfg(feature = "std")]
+        if let Some(start) = start.filter(|_| parsed.is_ok()) {
+            timings.push((tag, start.elapsed()));
+        }
 
-        tag_reader.skip(length - tag_reader.total_read())?;
+        tag_reader.skip(length.saturating_sub(tag_reader.total_read()))?;
         let mut checksum_act = tag_reader.finish()?;
 
-        if tag == *b"head" {
-            let Table::Head(head) = parsed? else {
-                panic!("head not parsed as head");
-            };
+        match parsed {
+            Ok(Table::Head(head)) => {
+                // this works cuz it's on a 4-byte boundary
+                (checksum_act, _) = checksum_act.overflowing_sub(head.checksum_adjustment);
+                checksum_adj = head.checksum_adjustment;
 
-            // this works cuz it's on a 4-byte boundary
-            (checksum_act, _) = checksum_act.overflowing_sub(head.checksum_adjustment);
-            checksum_adj = head.checksum_adjustment;
-
-            parsed_tables.push(Table::Head(head));
-        } else if parsed.is_ok() {
-            parsed_tables.push(parsed?);
-        } else {
-            let error = parsed.expect_err("is_not_ok");
-            if !matches!(error, ParseError::InvalidTag(_)) {
-                return Err(error);
-            };
+                parsed_tables.push(Table::Head(head));
+            },
+            Ok(table) => parsed_tables.push(table),
+            Err(ParseError::InvalidTag(_)) => {},
+            Err(error) => collect_error(errors.as_deref_mut(), error)?,
         }
 
         if checksum_act != checksum {
@@ -178,6 +637,8 @@ pub fn open_font<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static,
         }
     }
 
+    check_direction_hint(&parsed_tables);
+
     let mut checksum = reader.finish()?;
 
     if let Some(Table::Head(head)) = parsed_tables.iter().find(|t| matches!(t, Table::Head(_))) {
@@ -187,14 +648,551 @@ pub fn open_font<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static,
     // ChecksumAdjustment may be set to 0 for version 'OTTO'
     (checksum, _) = 0xb1b0_afba_u32.overflowing_sub(checksum);
     if checksum != checksum_adj {
-        return Err(ParseError::Parsing {
+        collect_error(errors, ParseError::Parsing {
             variable: "ChecksumAdjustment",
             expected: ValidType::U32(checksum_adj),
             parsed:   ValidType::U32(checksum),
+        })?;
+    }
+
+    Ok(Font {
+        tables: parsed_tables,
+        layout,
+        #[cfg(feature = "std")]
+        timings,
+        outline_format,
+    })
+}
+
+/// Parses every font of a TrueType Collection (`ttcf`), version 1.0 or 2.0
+///
+/// Tables shared between fonts are parsed once, for the first font using them,
+/// then copied into the others. A table is only shared when those it's parsed
+/// against are too. As the collection's tables aren't laid out per font,
+/// `ChecksumAdjustment` isn't verified
+/// # Errors
+/// - If `bytes` is not a valid collection, or any of its fonts is invalid
+pub fn open_collection<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static>(
+    allocator: A,
+    bytes: &[u8],
+) -> Result<CoreVec<Font<A>, A>, ParseError<core::convert::Infallible>> {
+    let mut reader = SliceReader::new(bytes);
+
+    let tag: [u8; 4] = reader.read_array()?;
+    if tag != *b"ttcf" {
+        return Err(ParseError::InvalidSfntVersion(tag));
+    }
+
+    let major_version: u16 = reader.read_int()?;
+    let minor_version: u16 = reader.read_int()?;
+    if !(1..=2).contains(&major_version) {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"ttcf",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0001_0000, 0x0002_0000],
+        });
+    }
+
+    // Version 2.0's DSIG fields follow the offsets, and aren't needed
+    let num_fonts: u32 = reader.read_int()?;
+    let mut fonts = CoreVec::new_in(allocator);
+    let mut shared = CoreVec::new_in(allocator);
+    for _ in 0..num_fonts {
+        let offset = reader.read_int::<u32>()? as usize;
+        let font = parse_font_slice(
+            allocator,
+            bytes,
+            offset,
+            &Options::default(),
+            Some((&fonts, &mut shared)),
+        )?;
+        fonts.push(font);
+    }
+
+    Ok(fonts)
+}
+
+/// Table parsed for an earlier font of a collection
+struct SharedTable {
+    /// Record of the table, then of each of its
+    /// [`dependencies`](crate::tables::dependencies) the font has
+    key:   [Option<TableLayout>; 4],
+    font:  usize,
+    table: usize,
+}
+
+impl SharedTable {
+    /// Key of `record` within a font of `directory`
+    fn key(
+        record: &TableLayout,
+        directory: &[TableLayout],
+    ) -> [Option<TableLayout>; 4] {
+        let mut key = [Some(*record), None, None, None];
+        for (slot, tag) in key[1..]
+            .iter_mut()
+            .zip(crate::tables::dependencies(record.tag))
+        {
+            *slot = directory.iter().find(|r| r.tag == *tag).copied();
+        }
+        key
+    }
+}
+
+/// Fonts of a collection parsed so far, and the tables they've parsed
+type Shared<'a, A> = (&'a [Font<A>], &'a mut CoreVec<SharedTable, A>);
+
+/// Parses the font whose table directory is at `offset` within `bytes`,
+/// reading each table from its offset in `bytes` rather than in file order
+///
+/// With `shared`, tables matching one of an earlier font's are copied from it
+/// instead, and those newly parsed are added
+fn parse_font_slice<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static>(
+    allocator: A,
+    bytes: &[u8],
+    offset: usize,
+    options: &Options,
+    mut shared: Option<Shared<'_, A>>,
+) -> Result<Font<A>, ParseError<core::convert::Infallible>> {
+    let mut reader = SliceReader::new(bytes.get(offset..).ok_or(ParseError::Parsing {
+        variable: "TTCHeader::tableDirectoryOffsets",
+        expected: ValidType::USize(bytes.len()),
+        parsed:   ValidType::USize(offset),
+    })?);
+    let mut directory = read_directory(allocator, &mut reader, options)?;
+    let layout = if options.record_layout {
+        directory.clone()
+    } else {
+        CoreVec::new_in(allocator)
+    };
+    let outline_format = OutlineFormat::from_directory(&directory);
+
+    let mut parsed_tables = CoreVec::new_in(allocator);
+    #[cfg(feature = "std")]
+    let mut timings = CoreVec::new_in(allocator);
+    // Tables may overlap or be out of order, but parsing by offset keeps
+    // the order tables see each other in the same as `open_font`
+    directory.sort_by_key(|t| t.offset);
+    for record in &directory {
+        let key = SharedTable::key(record, &directory);
+        if let Some((fonts, shared)) = &shared {
+            let earlier = shared
+                .iter()
+                .find(|table| table.key == key)
+                .and_then(|table| fonts.get(table.font)?.tables.get(table.table));
+            if let Some(table) = earlier {
+                parsed_tables.push(table.clone());
+                continue;
+            }
+        }
+
+        let (offset, length) = (record.offset as usize, record.length as usize);
+        let table = bytes
+            .get(offset..)
+            .and_then(|table| table.get(..length))
+            .ok_or(ParseError::Parsing {
+                variable: "TableRecord::offset",
+                expected: ValidType::USize(bytes.len()),
+                parsed:   ValidType::USize(offset),
+            })?;
+
+        #[cfg(feature = "std")]
+        let start = options.collect_timing.then(std::time::Instant::now);
+        let parsed = parse_table(
+            allocator,
+            options,
+            &parsed_tables,
+            record.tag,
+            &mut SliceReader::new(table),
+        );
+        #[cfg(feature = "std")]
+        if let Some(start) = start.filter(|_| parsed.is_ok()) {
+            timings.push((record.tag, start.elapsed()));
+        }
+
+        match parsed {
+            Ok(table) => {
+                if let Some((fonts, shared)) = &mut shared {
+                    shared.push(SharedTable {
+                        key,
+                        font: fonts.len(),
+                        table: parsed_tables.len(),
+                    });
+                }
+                parsed_tables.push(table);
+            },
+            Err(ParseError::InvalidTag(_)) => {},
+            Err(error) => return Err(error),
+        }
+    }
+
+    check_direction_hint(&parsed_tables);
+
+    Ok(Font {
+        tables: parsed_tables,
+        layout,
+        #[cfg(feature = "std")]
+        timings,
+        outline_format,
+    })
+}
+
+/// Sum of `bytes` as big-endian `u32`s, zero-padded to a multiple of 4
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.chunks(4).fold(0, |sum: u32, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Opens an in-memory font, e.g. a memory-mapped file, without copying or
+/// streaming through it. Each table is read straight from its offset, so
+/// gaps between tables cost nothing
+/// # Errors
+/// - If `bytes` is not a valid font
+pub fn open_font_from_bytes<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static>(
+    allocator: A,
+    bytes: &[u8],
+) -> Result<Font<A>, ParseError<core::convert::Infallible>> {
+    let font = parse_font_slice(allocator, bytes, 0, &Options::default(), None)?;
+
+    let checksum_adj = font
+        .tables
+        .iter()
+        .find_map(|t| match t {
+            Table::Head(head) => Some(head.checksum_adjustment),
+            _ => None,
+        })
+        .unwrap_or(0);
+
+    // ChecksumAdjustment is summed as 0, as when it was calculated
+    let sum = checksum(bytes).wrapping_sub(checksum_adj);
+    let expected = 0xb1b0_afba_u32.wrapping_sub(sum);
+    if expected != checksum_adj {
+        return Err(ParseError::Parsing {
+            variable: "ChecksumAdjustment",
+            expected: ValidType::U32(checksum_adj),
+            parsed:   ValidType::U32(expected),
+        });
+    }
+
+    Ok(font)
+}
+
+/// Most a zlib stream can inflate by, each 258 byte match taking 2 bits at
+/// best. Tables claiming more are lying about their length
+const MAX_WOFF_EXPANSION: usize = 1032;
+
+/// Table directory entry of a WOFF file
+struct WoffTable {
+    tag:           [u8; 4],
+    /// Of the stored data, within the WOFF file
+    offset:        usize,
+    comp_length:   usize,
+    orig_length:   usize,
+    orig_checksum: u32,
+    /// Of the inflated table, within the rebuilt sfnt
+    sfnt_offset:   usize,
+}
+
+/// Inflates `table` from the WOFF file `bytes` into `out`, which holds its
+/// original length
+fn inflate_woff_table(
+    bytes: &[u8],
+    table: &WoffTable,
+    out: &mut [u8],
+) -> Result<(), ParseError<core::convert::Infallible>> {
+    let data = bytes
+        .get(table.offset..)
+        .and_then(|data| data.get(..table.comp_length))
+        .ok_or(ParseError::Parsing {
+            variable: "WOFF TableDirectoryEntry::offset",
+            expected: ValidType::USize(bytes.len()),
+            parsed:   ValidType::USize(table.offset),
+        })?;
+
+    // Tables that didn't shrink are stored as is
+    match table.comp_length.cmp(&table.orig_length) {
+        core::cmp::Ordering::Equal => out.copy_from_slice(data),
+        core::cmp::Ordering::Less => {
+            let inflated = miniz_oxide::inflate::decompress_slice_iter_to_slice(
+                out,
+                core::iter::once(data),
+                true,
+                false,
+            )
+            .map_err(|status| ParseError::Parsing {
+                variable: "WOFF table zlib stream",
+                expected: ValidType::I8(miniz_oxide::inflate::TINFLStatus::Done as i8),
+                parsed:   ValidType::I8(status as i8),
+            })?;
+            if inflated != table.orig_length {
+                return Err(ParseError::Parsing {
+                    variable: "WOFF TableDirectoryEntry::origLength",
+                    expected: ValidType::USize(table.orig_length),
+                    parsed:   ValidType::USize(inflated),
+                });
+            }
+        },
+        core::cmp::Ordering::Greater => {
+            return Err(ParseError::Parsing {
+                variable: "WOFF TableDirectoryEntry::compLength",
+                expected: ValidType::USize(table.orig_length),
+                parsed:   ValidType::USize(table.comp_length),
+            })
+        },
+    }
+
+    // `head`'s checksum is taken with ChecksumAdjustment as 0
+    let mut sum = checksum(out);
+    if let (b"head", Some(adjustment)) = (&table.tag, out.get(8..12)) {
+        sum = sum.wrapping_sub(u32::from_be_bytes([
+            adjustment[0],
+            adjustment[1],
+            adjustment[2],
+            adjustment[3],
+        ]));
+    }
+    if sum != table.orig_checksum {
+        tracing::event!(
+            tracing::Level::WARN,
+            "{} checksum {sum:#010x} doesn't match the original's {:#010x}",
+            ValidType::Tag(table.tag),
+            table.orig_checksum
+        );
+    }
+
+    Ok(())
+}
+
+/// Opens a WOFF (`wOFF`) web font, inflating its tables back into an sfnt
+/// which is then parsed as by [`open_font_from_bytes`]
+///
+/// Tables are laid out in the order their data is stored, which encoders keep
+/// from the original font. As their offsets may still differ from the
+/// original's, `ChecksumAdjustment` isn't verified, only each table's checksum
+/// # Errors
+/// - If `bytes` is not a valid WOFF file, or the font within is invalid
+/// - If a table claims to inflate to more than zlib can produce from its
+///   compressed length, or the tables don't add up to `totalSfntSize`
+/// - If the rebuilt sfnt can't be allocated
+pub fn open_woff<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static>(
+    allocator: A,
+    bytes: &[u8],
+) -> Result<Font<A>, ParseError<core::convert::Infallible>> {
+    let mut reader = SliceReader::new(bytes);
+
+    let signature: [u8; 4] = reader.read_array()?;
+    if signature != *b"wOFF" {
+        return Err(ParseError::InvalidSfntVersion(signature));
+    }
+    let flavor: [u8; 4] = reader.read_array()?;
+    let length: u32 = reader.read_int()?;
+    let num_tables: u16 = reader.read_int()?;
+    let _reserved: u16 = reader.read_int()?;
+    let total_sfnt_size: u32 = reader.read_int()?;
+    // Font version, then the extended metadata and private data blocks
+    let _metadata: [u8; 24] = reader.read_array()?;
+
+    if length as usize != bytes.len() {
+        tracing::event!(
+            tracing::Level::WARN,
+            "WOFF length {length} doesn't match the {} bytes given",
+            bytes.len()
+        );
+    }
+
+    if num_tables == 0 {
+        return Err(ParseError::Parsing {
+            variable: "WOFF::numTables",
+            expected: ValidType::U16(1),
+            parsed:   ValidType::U16(num_tables),
+        });
+    }
+
+    let mut tables = CoreVec::with_capacity_in(usize::from(num_tables), allocator);
+    for _ in 0..num_tables {
+        let table = WoffTable {
+            tag:           reader.read_array()?,
+            offset:        reader.read_int::<u32>()? as usize,
+            comp_length:   reader.read_int::<u32>()? as usize,
+            orig_length:   reader.read_int::<u32>()? as usize,
+            orig_checksum: reader.read_int()?,
+            sfnt_offset:   0,
+        };
+
+        // Bounds the rebuilt font by the size of the file, before anything is
+        // allocated for it
+        if table.offset.saturating_add(table.comp_length) > bytes.len() {
+            return Err(ParseError::Parsing {
+                variable: "WOFF TableDirectoryEntry::compLength",
+                expected: ValidType::USize(bytes.len().saturating_sub(table.offset)),
+                parsed:   ValidType::USize(table.comp_length),
+            });
+        }
+        let max_length = table.comp_length.saturating_mul(MAX_WOFF_EXPANSION);
+        if table.orig_length > max_length {
+            return Err(ParseError::Parsing {
+                variable: "WOFF TableDirectoryEntry::origLength",
+                expected: ValidType::USize(max_length),
+                parsed:   ValidType::USize(table.orig_length),
+            });
+        }
+        tables.push(table);
+    }
+
+    // Tables are 4-byte aligned after the header and table records
+    tables.sort_by_key(|t| t.offset);
+    let mut total = 12 + 16 * usize::from(num_tables);
+    for table in &mut tables {
+        table.sfnt_offset = total;
+        total = total
+            .checked_add(table.orig_length.next_multiple_of(4))
+            .filter(|total| u32::try_from(*total).is_ok())
+            .ok_or(ParseError::Parsing {
+                variable: "WOFF::totalSfntSize",
+                expected: ValidType::U32(total_sfnt_size),
+                parsed:   ValidType::USize(total),
+            })?;
+    }
+    if total != total_sfnt_size as usize {
+        return Err(ParseError::Parsing {
+            variable: "WOFF::totalSfntSize",
+            expected: ValidType::USize(total),
+            parsed:   ValidType::U32(total_sfnt_size),
         });
     }
 
-    Ok(parsed_tables)
+    let mut sfnt = CoreVec::new_in(allocator);
+    sfnt.try_reserve_exact(total)
+        .map_err(|_| ParseError::Allocation {
+            location:  "WOFF::totalSfntSize",
+            expected:  total,
+            allocated: 0,
+        })?;
+    sfnt.resize(total, 0);
+
+    for table in &tables {
+        let out = &mut sfnt[table.sfnt_offset..table.sfnt_offset + table.orig_length];
+        inflate_woff_table(bytes, table, out)?;
+    }
+
+    // Header and table records, as `read_directory` expects them
+    let mut header = CoreVec::with_capacity_in(12 + 16 * tables.len(), allocator);
+    header.extend_from_slice(&flavor);
+    header.extend_from_slice(&num_tables.to_be_bytes());
+    for field in <[u32; 3]>::from(search_hints(num_tables)) {
+        header.extend_from_slice(&u16::try_from(field).unwrap_or(u16::MAX).to_be_bytes());
+    }
+    tables.sort_by_key(|t| t.tag);
+    for table in &tables {
+        header.extend_from_slice(&table.tag);
+        header.extend_from_slice(&table.orig_checksum.to_be_bytes());
+        for field in [table.sfnt_offset, table.orig_length] {
+            header.extend_from_slice(&u32::try_from(field).unwrap_or(u32::MAX).to_be_bytes());
+        }
+    }
+    sfnt[..header.len()].copy_from_slice(&header);
+
+    parse_font_slice(allocator, &sfnt, 0, &Options::default(), None)
+}
+
+/// Panic-free entry point over an in-memory font, intended for fuzzing
+/// # Errors
+/// - If `bytes` is not a valid font
+pub fn parse_font_bytes(bytes: &[u8]) -> Result<Font, ParseError<core::convert::Infallible>> {
+    open_font_from_bytes(alloc::alloc::Global, bytes)
+}
+
+impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Font<A> {
+    /// Font without any tables, standing in for a merged face
+    fn empty(allocator: A) -> Self
+    where
+        A: Clone,
+    {
+        Self {
+            tables:                          CoreVec::new_in(allocator.clone()),
+            #[cfg(feature = "std")]
+            timings:                         CoreVec::new_in(allocator.clone()),
+            layout:                          CoreVec::new_in(allocator),
+            outline_format:                  OutlineFormat::None,
+        }
+    }
+
+    pub fn tables(&self) -> &[Table<A>] {
+        &self.tables
+    }
+
+    fn glyf(&self) -> &[Glyph<A>] {
+        self.tables
+            .iter()
+            .find_map(|t| match t {
+                Table::Glyf(glyf) => Some(glyf.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Glyph whose `hmtx` entry `glyph_id` uses, following the composite
+    /// components flagged with `USE_MY_METRICS`
+    pub(crate) fn metrics_glyph_id(
+        &self,
+        glyph_id: u32,
+    ) -> u32 {
+        // Bounded, as a broken font's components may refer back to themselves
+        let mut metrics_id = glyph_id;
+        for _ in 0..MAX_COMPONENT_DEPTH {
+            match self
+                .glyf()
+                .get(metrics_id as usize)
+                .and_then(Glyph::metrics_component)
+            {
+                Some(component) => metrics_id = u32::from(component),
+                None => break,
+            }
+        }
+        metrics_id
+    }
+
+    fn hhea(&self) -> Option<&Hhea<A>> {
+        self.tables.iter().find_map(|t| match t {
+            Table::Hhea(hhea) => Some(hhea),
+            _ => None,
+        })
+    }
+
+    /// Table directory of the original font, empty unless loaded with
+    /// [`Options::record_layout`]
+    pub fn layout(&self) -> &[TableLayout] {
+        &self.layout
+    }
+
+    /// Time each table took to parse, in parsing order. Empty unless loaded
+    /// with [`Options::collect_timing`]
+    #[cfg(feature = "std")]
+    pub fn timings(&self) -> &[([u8; 4], std::time::Duration)] {
+        &self.timings
+    }
+}
+
+/// Compares the outlines of every glyph in `a` against `b`. Glyph ids only
+/// present in `b` are [`GlyphDiff::Added`], only in `a` [`GlyphDiff::Removed`]
+pub fn diff_glyphs<A: core::alloc::Allocator + Clone + core::fmt::Debug + 'static>(
+    a: &Font<A>,
+    b: &Font<A>,
+) -> CoreVec<GlyphDiff, A> {
+    let mut diff = CoreVec::new_in(a.tables.allocator().clone());
+
+    for (glyph_id, glyphs) in (0u32..).zip(a.glyf().iter().zip_longest(b.glyf())) {
+        match glyphs {
+            EitherOrBoth::Both(a, b) if a != b => diff.push(GlyphDiff::Modified(glyph_id)),
+            EitherOrBoth::Both(..) => {},
+            EitherOrBoth::Left(_) => diff.push(GlyphDiff::Removed(glyph_id)),
+            EitherOrBoth::Right(_) => diff.push(GlyphDiff::Added(glyph_id)),
+        }
+    }
+
+    diff
 }
 
 impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Trait<A> for Font<A> {
@@ -202,7 +1200,8 @@ impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Trait<A> for Font<A
         &self,
         record_type: RecordType,
     ) -> Option<&str> {
-        let Some(Table::Name(name_table)) = self.iter().find(|t| matches!(t, Table::Name(_)))
+        let Some(Table::Name(name_table)) =
+            self.tables.iter().find(|t| matches!(t, Table::Name(_)))
         else {
             return None;
         };
@@ -218,11 +1217,38 @@ impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Trait<A> for Font<A
         self.name_record(RecordType::UniqueIdentifier)
     }
 
+    fn postscript_name(&self) -> Option<&str> {
+        self.name_record(RecordType::PostScript)
+    }
+
+    fn postscript_name_sanitized(&self) -> Option<CoreBox<str, A>>
+    where
+        A: Clone,
+    {
+        const MAX_LEN: usize = 63;
+
+        let mut name = CoreVec::with_capacity_in(MAX_LEN, self.tables.allocator().clone());
+        name.extend(
+            self.postscript_name()?
+                .bytes()
+                .filter(|b| (33..=126).contains(b) && !b"[](){}<>/%".contains(b))
+                .take(MAX_LEN),
+        );
+        if name.is_empty() {
+            return None;
+        }
+
+        // Only ASCII was kept
+        let (ptr, allocator) = CoreBox::into_raw_with_allocator(name.into_boxed_slice());
+        Some(unsafe { CoreBox::from_raw_in(ptr as *mut str, allocator) })
+    }
+
     fn glyph(
         &self,
         glyph_id: u32,
     ) -> Option<&Glyph<A>> {
-        let Some(Table::Glyf(glyf_table)) = self.iter().find(|t| matches!(t, Table::Glyf(_)))
+        let Some(Table::Glyf(glyf_table)) =
+            self.tables.iter().find(|t| matches!(t, Table::Glyf(_)))
         else {
             return None;
         };
@@ -230,21 +1256,313 @@ impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Trait<A> for Font<A
         glyf_table.get(glyph_id as usize)
     }
 
-    fn units_per_em(&self) -> u16 {
-        let Some(Table::Head(head_table)) = self.iter().find(|t| matches!(t, Table::Head(_)))
-        else {
-            panic!("No Head");
+    fn has_outlines(&self) -> bool {
+        self.tables.iter().any(|t| matches!(t, Table::Glyf(_)))
+    }
+
+    fn kern_pair(
+        &self,
+        left: u32,
+        right: u32,
+    ) -> i16 {
+        let (Ok(left), Ok(right)) = (u16::try_from(left), u16::try_from(right)) else {
+            return 0;
         };
 
-        head_table.units_per_em
+        self.tables
+            .iter()
+            .find_map(|t| match t {
+                Table::Kern(kern) => Some(kern.kern_pair(left, right)),
+                _ => None,
+            })
+            .unwrap_or(0)
     }
-}
 
-impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Collection<A> {
-    pub fn new(allocator: A) -> Self {
-        Self {
-            loaded: Slotmap::new(allocator),
-        }
+    fn glyph_name(
+        &self,
+        glyph_id: u32,
+    ) -> Option<&str> {
+        self.tables
+            .iter()
+            .find_map(|t| match t {
+                Table::Post(post) => Some(post),
+                _ => None,
+            })?
+            .glyph_name(glyph_id)
+    }
+
+    fn num_glyphs(&self) -> u32 {
+        self.maxp().map_or(0, |maxp| u32::from(maxp.num_glyphs()))
+    }
+
+    fn glyphs(&self) -> core::slice::Iter<'_, Glyph<A>> {
+        self.glyf().iter()
+    }
+
+    fn glyph_id(
+        &self,
+        c: char,
+    ) -> Option<u32> {
+        self.tables
+            .iter()
+            .find_map(|t| match t {
+                Table::Cmap(cmap) => Some(cmap),
+                _ => None,
+            })?
+            .glyph_id(c)
+    }
+
+    fn units_per_em(&self) -> Option<u16> {
+        self.tables.iter().find_map(|t| match t {
+            Table::Head(head) => Some(head.units_per_em),
+            _ => None,
+        })
+    }
+
+    fn bbox(&self) -> (i16, i16, i16, i16) {
+        self.tables
+            .iter()
+            .find_map(|t| match t {
+                Table::Head(head) => Some((head.x_min, head.y_min, head.x_max, head.y_max)),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn outline_format(&self) -> OutlineFormat {
+        self.outline_format
+    }
+
+    fn pclt(&self) -> Option<&Pclt<A>> {
+        self.tables.iter().find_map(|t| match t {
+            Table::Pclt(pclt) => Some(pclt),
+            _ => None,
+        })
+    }
+
+    fn os2(&self) -> Option<&Os2<A>> {
+        self.tables.iter().find_map(|t| match t {
+            Table::Os2(os2) => Some(os2),
+            _ => None,
+        })
+    }
+
+    fn maxp(&self) -> Option<&Maxp<A>> {
+        self.tables.iter().find_map(|t| match t {
+            Table::Maxp(maxp) => Some(maxp),
+            _ => None,
+        })
+    }
+
+    fn weight_class(&self) -> u16 {
+        self.os2().map_or(400, |os2| os2.weight_class)
+    }
+
+    fn width_class(&self) -> u16 {
+        self.os2().map_or(5, |os2| os2.width_class)
+    }
+
+    fn cap_height(&self) -> Option<i16> {
+        self.os2()?.cap_height
+    }
+
+    fn x_height(&self) -> Option<i16> {
+        self.os2()?.x_height
+    }
+
+    fn ascent(&self) -> i16 {
+        self.hhea().map_or(0, |hhea| hhea.ascender)
+    }
+
+    fn descent(&self) -> i16 {
+        self.hhea().map_or(0, |hhea| hhea.descender)
+    }
+
+    fn line_gap(&self) -> i16 {
+        self.hhea().map_or(0, |hhea| hhea.line_gap)
+    }
+
+    fn caret_slope(&self) -> (i16, i16) {
+        self.hhea().map_or((1, 0), |hhea| match hhea.carat_slope {
+            CaretSlope::Vertical => (1, 0),
+            CaretSlope::Horizontal => (0, 1),
+            CaretSlope::Specific { rise, run } => (rise, run),
+        })
+    }
+
+    fn caret_offset(&self) -> i16 {
+        self.hhea().map_or(0, |hhea| hhea.carat_offset)
+    }
+
+    fn baseline_offset(
+        &self,
+        script: [u8; 4],
+    ) -> Option<i16> {
+        self.tables
+            .iter()
+            .find_map(|t| match t {
+                Table::Base(base) => Some(base),
+                _ => None,
+            })?
+            .baseline_offset(script)
+    }
+
+    fn color_bitmap(
+        &self,
+        glyph_id: u32,
+        ppem: u16,
+    ) -> Option<ColorBitmap<'_>> {
+        let cblc = self.tables.iter().find_map(|t| match t {
+            Table::Cblc(cblc) => Some(cblc),
+            _ => None,
+        })?;
+        let cbdt = self.tables.iter().find_map(|t| match t {
+            Table::Cbdt(cbdt) => Some(cbdt),
+            _ => None,
+        })?;
+
+        let strike = cblc.best_strike(ppem)?;
+        let location = strike.get(u16::try_from(glyph_id).ok()?)?;
+        Some(ColorBitmap {
+            strike_ppem: u16::from(strike.ppem_y),
+            ppem,
+            png: cbdt.png(location)?,
+        })
+    }
+
+    fn advance_width(
+        &self,
+        glyph_id: u32,
+    ) -> Option<u16> {
+        let Some(Table::Hmtx(hmtx_table)) =
+            self.tables.iter().find(|t| matches!(t, Table::Hmtx(_)))
+        else {
+            return None;
+        };
+
+        hmtx_table
+            .get(self.metrics_glyph_id(glyph_id) as usize)
+            .map(|m| m.advance)
+    }
+
+    fn glyph_to_cubic_path(
+        &self,
+        glyph_id: u32,
+    ) -> Option<CoreVec<PathCmd, A>>
+    where
+        A: Clone,
+    {
+        self.glyph(glyph_id)
+            .map(|glyph| glyph.to_cubic_path(self.tables.allocator().clone()))
+    }
+
+    fn glyph_metrics_batch(
+        &self,
+        glyph_ids: &[u32],
+    ) -> CoreVec<GlyphMetrics, A>
+    where
+        A: Clone,
+    {
+        let hmtx_table = self.tables.iter().find_map(|t| match t {
+            Table::Hmtx(hmtx) => Some(hmtx),
+            _ => None,
+        });
+        let glyf_table = self.tables.iter().find_map(|t| match t {
+            Table::Glyf(glyf) => Some(glyf),
+            _ => None,
+        });
+
+        let mut metrics =
+            CoreVec::with_capacity_in(glyph_ids.len(), self.tables.allocator().clone());
+        metrics.extend(glyph_ids.iter().map(|glyph_id| {
+            let hmtx = hmtx_table.and_then(|t| t.get(self.metrics_glyph_id(*glyph_id) as usize));
+            let glyph = glyf_table.and_then(|t| t.get(*glyph_id as usize));
+
+            GlyphMetrics {
+                advance:           hmtx.map_or(0, |m| m.advance),
+                left_side_bearing: hmtx.map_or(0, |m| m.left_side_bearing),
+                bbox:              glyph.map_or((0, 0, 0, 0), |g| {
+                    (
+                        g.x_bounds.start,
+                        g.y_bounds.start,
+                        g.x_bounds.last,
+                        g.y_bounds.last,
+                    )
+                }),
+            }
+        }));
+
+        metrics
+    }
+
+    fn advance_px(
+        &self,
+        glyph_id: u32,
+        ppem: f32,
+    ) -> f32 {
+        let Some(units_per_em) = self.units_per_em() else {
+            return 0.0;
+        };
+        f32::from(self.advance_width(glyph_id).unwrap_or(0)) * ppem / f32::from(units_per_em)
+    }
+
+    fn ppem_metrics(
+        &self,
+        ppem: f32,
+    ) -> Option<PpemMetrics> {
+        let scale = ppem / f32::from(self.units_per_em()?);
+        let px = |units: i16| f32::from(units) * scale;
+        Some(PpemMetrics {
+            ascent:     px(self.ascent()),
+            descent:    px(self.descent()),
+            line_gap:   px(self.line_gap()),
+            cap_height: self.cap_height().map(px),
+            x_height:   self.x_height().map(px),
+        })
+    }
+
+    fn font_summary(&self) -> FontSummary<'_, A>
+    where
+        A: Clone,
+    {
+        let allocator = self.tables.allocator().clone();
+        let cmap = self.tables.iter().find_map(|t| match t {
+            Table::Cmap(cmap) => Some(cmap),
+            _ => None,
+        });
+        let os2 = self.os2();
+
+        let mut blocks: CoreVec<&UnicodeBlock, A> = CoreVec::new_in(allocator.clone());
+        blocks.extend(UNICODE_BLOCKS.iter().filter(|block| {
+            os2.is_some_and(|os2| os2.covers(block))
+                || cmap.is_some_and(|cmap| cmap.maps_any(block.first, block.last))
+        }));
+
+        let mut scripts = CoreVec::new_in(allocator);
+        scripts.extend(blocks.iter().filter_map(|block| block.script));
+        scripts.sort_unstable();
+        scripts.dedup();
+
+        FontSummary {
+            family: self
+                .name_record(RecordType::TypographicFamily)
+                .or_else(|| self.name_record(RecordType::Family)),
+            style: self
+                .name_record(RecordType::TypographicSubfamily)
+                .or_else(|| self.name_record(RecordType::Subfamily)),
+            blocks,
+            scripts,
+        }
+    }
+}
+
+impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Collection<A> {
+    pub fn new(allocator: A) -> Self {
+        Self {
+            loaded:   Slotmap::new(allocator),
+            fallback: None,
+            merged:   None,
+        }
     }
 
     pub fn add_loaded(
@@ -262,4 +1580,939 @@ impl<A: core::alloc::Allocator + core::fmt::Debug + 'static> Collection<A> {
     ) -> &Font<A> {
         self.loaded.get(key).expect("Invalid Key")
     }
+
+    pub fn try_get(
+        &self,
+        key: SlotmapKey,
+    ) -> Option<&Font<A>> {
+        self.loaded.get(key)
+    }
+
+    pub fn contains(
+        &self,
+        key: SlotmapKey,
+    ) -> bool {
+        self.loaded.contains(key)
+    }
+
+    /// Unloads the font at `key`, returning it if it existed. Removing the
+    /// fallback font leaves the collection without one
+    pub fn remove(
+        &mut self,
+        key: SlotmapKey,
+    ) -> Option<Font<A>> {
+        let font = self.loaded.try_pop(key)?;
+        if self.fallback == Some(key) {
+            self.fallback = None;
+        }
+        if let Some(merged) = &mut self.merged {
+            merged.remove(key);
+        }
+        Some(font)
+    }
+
+    pub(crate) fn allocator(&self) -> &A {
+        self.loaded.allocator()
+    }
+
+    /// Number of fonts in the collection
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.loaded.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.loaded.is_empty()
+    }
+
+    /// Drops every font and the fallback, invalidating all of their keys
+    pub fn clear(&mut self) {
+        self.loaded.clear();
+        self.fallback = None;
+        if let Some(merged) = &mut self.merged {
+            merged.clear();
+        }
+    }
+
+    /// Adds every font of a collection, e.g. from [`open_collection`],
+    /// returning their keys in order
+    pub fn add_all_loaded(
+        &mut self,
+        fonts: CoreVec<Font<A>, A>,
+    ) -> CoreVec<SlotmapKey, A>
+    where
+        A: Clone,
+    {
+        let mut keys = CoreVec::with_capacity_in(fonts.len(), fonts.allocator().clone());
+        for font in fonts {
+            keys.push(self.loaded.push(font));
+        }
+        keys
+    }
+
+    /// Designates `key` as the font consulted for characters the requested
+    /// font doesn't cover
+    /// # Panics
+    /// - If `key` does not exist in collection
+    pub fn set_fallback(
+        &mut self,
+        key: SlotmapKey,
+    ) {
+        assert!(self.loaded.get(key).is_some(), "Invalid Key");
+        self.fallback = Some(key);
+    }
+
+    pub const fn fallback(&self) -> Option<SlotmapKey> {
+        self.fallback
+    }
+
+    /// Combines the fonts at `keys` into one face, whose characters come from
+    /// the first of them that covers each. Glyphs stay in their source fonts,
+    /// so the merged key's own font is empty and lookups go through
+    /// [`Collection::resolve_glyph`]. Merged faces among `keys` contribute
+    /// their sources
+    /// # Panics
+    /// - If any of `keys` does not exist in collection
+    pub fn merge(
+        &mut self,
+        keys: &[SlotmapKey],
+    ) -> SlotmapKey
+    where
+        A: Clone,
+    {
+        let allocator = self.allocator().clone();
+
+        let mut sources = CoreVec::new_in(allocator.clone());
+        for key in keys {
+            assert!(self.loaded.contains(*key), "Invalid Key");
+            match self.sources(*key) {
+                Some(merged) => sources.extend_from_slice(merged),
+                None => sources.push(*key),
+            }
+        }
+
+        let key = self.loaded.push(Font::empty(allocator.clone()));
+        self.merged
+            .get_or_insert_with(|| SecondaryMap::new(allocator))
+            .insert(key, sources);
+        key
+    }
+
+    /// Fonts merged into `key` by [`Collection::merge`], `None` for fonts
+    /// loaded directly
+    pub fn sources(
+        &self,
+        key: SlotmapKey,
+    ) -> Option<&[SlotmapKey]> {
+        self.merged.as_ref()?.get(key).map(|sources| &sources[..])
+    }
+
+    /// Font drawing `key`'s `.notdef`, the first remaining source of a merged
+    /// face
+    pub(crate) fn notdef_font(
+        &self,
+        key: SlotmapKey,
+    ) -> SlotmapKey {
+        self.sources(key)
+            .and_then(|sources| {
+                sources
+                    .iter()
+                    .copied()
+                    .find(|source| self.loaded.contains(*source))
+            })
+            .unwrap_or(key)
+    }
+
+    /// Font and glyph for `c`, trying `key`, or each font merged into it,
+    /// before the fallback font. Sources removed since merging are skipped
+    /// # Panics
+    /// - If `key` does not exist in collection
+    pub fn resolve_glyph(
+        &self,
+        key: SlotmapKey,
+        c: char,
+    ) -> Option<(SlotmapKey, u32)> {
+        assert!(self.loaded.contains(key), "Invalid Key");
+        let fonts = self
+            .sources(key)
+            .unwrap_or_else(|| core::slice::from_ref(&key));
+
+        fonts.iter().copied().chain(self.fallback).find_map(|key| {
+            self.try_get(key)?
+                .glyph_id(c)
+                .map(|glyph_id| (key, glyph_id))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        be,
+        checksum,
+        cmap,
+        cmap4,
+        composite_glyph,
+        font,
+        font_with,
+        glyf_loca,
+        head,
+        hhea,
+        hmtx,
+        maxp,
+        name,
+        os2,
+        rect,
+        sfnt,
+        simple_glyph,
+        square_font,
+    };
+
+    #[test]
+    fn advance_px_scales_the_advance_width() {
+        let font = crate::parse_font_bytes(&square_font()).expect("Font should parse");
+        let units_per_em = f32::from(font.units_per_em().expect("Font should have a head"));
+
+        for glyph_id in [0, 1] {
+            let advance = f32::from(
+                font.advance_width(glyph_id)
+                    .expect("Glyph should have metrics"),
+            );
+            for ppem in [12.0, 24.0, 33.5] {
+                let expected = advance * ppem / units_per_em;
+                assert!((font.advance_px(glyph_id, ppem) - expected).abs() < 1e-4);
+            }
+        }
+        assert!((font.advance_px(1, 24.0) - 14.4).abs() < 1e-4);
+        assert!(font.advance_px(2, 24.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn latin_font_summary_has_no_cjk() {
+        let utf16 = |s: &str| -> Vec<u8> { s.encode_utf16().flat_map(u16::to_be_bytes).collect() };
+        let (family, style) = (utf16("Test Sans"), utf16("Bold"));
+        let name = name(&[(3, 1, 0x409, 1, &family), (3, 1, 0x409, 2, &style)]);
+        // Latin Extended Additional is only declared by `OS/2`
+        let os2 = os2(4, [1 << 29, 0, 0, 0], (500, 700));
+        let glyphs = [
+            (Vec::new(), 500),
+            (simple_glyph(&[&rect(100, 0, 500, 400)]), 600),
+        ];
+        let bytes = font_with(&glyphs, &[('A', 1), ('é', 1)], &[
+            (*b"OS/2", &os2),
+            (*b"name", &name),
+        ]);
+        let font = crate::parse_font_bytes(&bytes).expect("Font should parse");
+
+        let summary = font.font_summary();
+        assert_eq!(
+            (summary.family, summary.style),
+            (Some("Test Sans"), Some("Bold"))
+        );
+        let blocks: Vec<_> = summary.blocks.iter().map(|block| block.name).collect();
+        assert_eq!(blocks, [
+            "Basic Latin",
+            "Latin-1 Supplement",
+            "Latin Extended Additional"
+        ]);
+        assert!(!summary
+            .blocks
+            .iter()
+            .any(|block| block.name.starts_with("CJK")));
+        assert_eq!(summary.scripts.as_slice(), [*b"latn"]);
+    }
+
+    #[test]
+    fn postscript_names_are_sanitized() {
+        let with_name = |postscript: &[u8]| {
+            let name = name(&[(1, 0, 0, 6, postscript)]);
+            let glyphs = [(Vec::new(), 500)];
+            let bytes = font_with(&glyphs, &[], &[(*b"name", &name)]);
+            crate::parse_font_bytes(&bytes).expect("Font should parse")
+        };
+
+        // Mac Roman, with 0x8e an 'é'
+        let font = with_name(b"My Caf\x8e[Bold]/Italic%");
+        assert_eq!(font.postscript_name(), Some("My Café[Bold]/Italic%"));
+        let sanitized = font
+            .postscript_name_sanitized()
+            .expect("Some characters are allowed");
+        assert_eq!(&*sanitized, "MyCafBoldItalic");
+        assert!(sanitized
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"[](){}<>/%".contains(&b)));
+
+        let long = with_name(&[b'A'; 100]);
+        assert_eq!(
+            long.postscript_name_sanitized().map(|name| name.len()),
+            Some(63)
+        );
+
+        let font = with_name(b"() <>");
+        assert!(font.postscript_name().is_some());
+        assert!(font.postscript_name_sanitized().is_none());
+    }
+
+    #[test]
+    fn batched_metrics_match_individual_lookups() {
+        let glyphs: Vec<_> = (0..12i16)
+            .map(|i| {
+                let outline = simple_glyph(&[&rect(10 * i, -5 * i, 300 + 20 * i, 400 + 10 * i)]);
+                (outline, 500 + 10 * i.unsigned_abs())
+            })
+            .collect();
+        let font = crate::parse_font_bytes(&font(&glyphs, &[])).expect("Font should parse");
+
+        // Repeated ids, and some past the last glyph
+        let glyph_ids: Vec<u32> = (0..100).map(|i| i * 7 % 15).collect();
+        let batch = font.glyph_metrics_batch(&glyph_ids);
+        assert_eq!(batch.len(), 100);
+        for (&glyph_id, metrics) in glyph_ids.iter().zip(batch.iter()) {
+            let bbox = font.glyph(glyph_id).map_or((0, 0, 0, 0), |g| {
+                (
+                    g.x_bounds.start,
+                    g.y_bounds.start,
+                    g.x_bounds.last,
+                    g.y_bounds.last,
+                )
+            });
+            // `font` gives each glyph a bearing of its x_min
+            assert_eq!(*metrics, GlyphMetrics {
+                advance: font.advance_width(glyph_id).unwrap_or(0),
+                left_side_bearing: bbox.0,
+                bbox,
+            });
+        }
+        assert_eq!(batch[1].bbox, (70, -35, 440, 470));
+    }
+
+    #[test]
+    fn outline_format_follows_the_tables_present() {
+        let ttf = crate::parse_font_bytes(&square_font()).expect("Font should parse");
+        assert_eq!(ttf.outline_format(), OutlineFormat::TrueType);
+
+        // Neither table is parsed, only their presence matters
+        let with = |tag: [u8; 4]| {
+            let bytes = sfnt(&[
+                (*b"head", &head(1000, 2, true)),
+                (*b"maxp", &maxp(1)),
+                (tag, &be!(1u16, 0u16, 0u32)),
+            ]);
+            crate::parse_font_bytes(&bytes)
+                .expect("Font should parse")
+                .outline_format()
+        };
+        assert_eq!(with(*b"sbix"), OutlineFormat::Bitmap);
+        assert_eq!(with(*b"CFF2"), OutlineFormat::Cff);
+        assert_eq!(with(*b"zzzz"), OutlineFormat::None);
+    }
+
+    #[test]
+    fn fonts_without_glyf_have_no_outlines() {
+        let cff2 = sfnt(&[
+            (*b"head", &head(1000, 2, true)),
+            (*b"maxp", &maxp(2)),
+            (*b"hhea", &hhea(800, -200, 2)),
+            (*b"hmtx", &hmtx(&[(500, 0), (600, 100)], &[])),
+            (*b"cmap", &cmap(&[(3, 1, cmap4(&[('A', 1)]))])),
+            (*b"CFF2", &be!(1u16, 0u16, 0u32)),
+        ]);
+        let font = crate::parse_font_bytes(&cff2).expect("Font should parse");
+        assert!(!font.has_outlines());
+        // Mapped and within `maxp`, but there's no outline to give
+        assert_eq!(font.glyph_id('A'), Some(1));
+        assert!(font.glyph(0).is_none());
+        assert!(font.glyph(1).is_none());
+
+        // Out of range is the only reason a TrueType font has no glyph
+        let ttf = crate::parse_font_bytes(&square_font()).expect("Font should parse");
+        assert!(ttf.has_outlines());
+        assert!(ttf.glyph(1).is_some());
+        assert!(ttf.glyph(2).is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn timings_are_collected_for_each_parsed_table() {
+        let glyphs = [
+            (Vec::new(), 500),
+            (simple_glyph(&[&rect(100, 0, 500, 400)]), 600),
+        ];
+        // Unknown tables aren't parsed, so aren't timed
+        let bytes = font_with(&glyphs, &[('A', 1)], &[(*b"zzzz", &[0; 4])]);
+        let parse = |collect_timing| {
+            let options = Options {
+                collect_timing,
+                ..Options::default()
+            };
+            open_font_with_options(
+                alloc::alloc::Global,
+                &mut SliceReader::new(&bytes),
+                &options,
+            )
+            .expect("Font should parse")
+        };
+
+        let font = parse(true);
+        let mut timed: Vec<_> = font.timings().iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(timed.len(), font.tables().len());
+        timed.sort_unstable();
+        assert_eq!(timed, [
+            *b"cmap", *b"glyf", *b"head", *b"hhea", *b"hmtx", *b"loca", *b"maxp"
+        ]);
+
+        assert!(parse(false).timings().is_empty());
+    }
+
+    #[test]
+    fn use_my_metrics_takes_the_component_advance() {
+        const USE_MY_METRICS: u8 = 1 << 1;
+
+        let accented = composite_glyph(&[(1, 0, 0), (2, 150, 500)]);
+        let mut flagged = accented.clone();
+        // High byte of the base component's flags, after the header
+        flagged[10] |= USE_MY_METRICS;
+        let glyphs = [
+            (Vec::new(), 500),
+            (simple_glyph(&[&rect(100, 0, 500, 400)]), 600),
+            (simple_glyph(&[&rect(0, 0, 100, 100)]), 300),
+            (accented, 999),
+            (flagged, 999),
+        ];
+        let bytes = font(&glyphs, &[('A', 1), ('´', 2), ('Á', 4)]);
+        let font = crate::parse_font_bytes(&bytes).expect("Font should parse");
+
+        assert_eq!(font.advance_width(3), Some(999));
+        assert_eq!(font.advance_width(4), Some(600));
+        let metrics = font.glyph_metrics_batch(&[1, 4]);
+        assert_eq!(metrics[1].advance, metrics[0].advance);
+        assert_eq!(metrics[1].left_side_bearing, metrics[0].left_side_bearing);
+    }
+
+    #[test]
+    fn ppem_metrics_scale_os2_heights() {
+        let glyphs = [(Vec::new(), 500)];
+        let with_os2 = |version| {
+            let bytes = font_with(&glyphs, &[], &[(
+                *b"OS/2",
+                &os2(version, [0; 4], (500, 700)),
+            )]);
+            crate::parse_font_bytes(&bytes).expect("Font should parse")
+        };
+
+        let font = with_os2(4);
+        assert_eq!((font.cap_height(), font.x_height()), (Some(700), Some(500)));
+        let metrics = font.ppem_metrics(20.0).expect("Font should have a head");
+        assert_eq!(metrics, PpemMetrics {
+            ascent:     16.0,
+            descent:    -4.0,
+            line_gap:   0.0,
+            cap_height: Some(14.0),
+            x_height:   Some(10.0),
+        });
+
+        // Version 1 ends before the heights
+        let font = with_os2(1);
+        assert_eq!(font.cap_height(), None);
+        let metrics = font.ppem_metrics(20.0).expect("Font should have a head");
+        assert_eq!((metrics.cap_height, metrics.x_height), (None, None));
+    }
+
+    #[test]
+    fn budgets_stop_parsing() {
+        let parse = |bytes: &[u8], options: &Options| {
+            open_font_with_options(alloc::alloc::Global, &mut SliceReader::new(bytes), options)
+        };
+
+        // Claims far more glyphs than it holds, which `loca` and `hmtx` would
+        // otherwise allocate for
+        let (glyf, loca) = glyf_loca(&[simple_glyph(&[&rect(0, 0, 100, 100)])], true);
+        let enormous = sfnt(&[
+            (*b"head", &head(1000, 2, true)),
+            (*b"maxp", &maxp(u16::MAX)),
+            (*b"hhea", &hhea(800, -200, 1)),
+            (*b"hmtx", &hmtx(&[(500, 0)], &[])),
+            (*b"loca", &loca),
+            (*b"glyf", &glyf),
+        ]);
+        let max_glyphs = Options {
+            max_glyphs: Some(1000),
+            ..Options::default()
+        };
+        assert!(matches!(
+            parse(&enormous, &max_glyphs),
+            Err(ParseError::BudgetExceeded {
+                budget: "max_glyphs",
+                limit:  1000,
+            })
+        ));
+
+        let square = square_font();
+        assert!(parse(&square, &max_glyphs).is_ok());
+        let max_tables = Options {
+            max_tables: Some(4),
+            ..Options::default()
+        };
+        assert!(matches!(
+            parse(&square, &max_tables),
+            Err(ParseError::BudgetExceeded {
+                budget: "max_tables",
+                limit:  4,
+            })
+        ));
+        let max_total_points = Options {
+            max_total_points: Some(3),
+            ..Options::default()
+        };
+        assert!(matches!(
+            parse(&square, &max_total_points),
+            Err(ParseError::BudgetExceeded {
+                budget: "max_total_points",
+                limit:  3,
+            })
+        ));
+    }
+
+    #[test]
+    fn independent_table_errors_are_all_collected() {
+        let corrupt = font_with(
+            &[
+                (Vec::new(), 500),
+                (simple_glyph(&[&rect(100, 0, 500, 400)]), 600),
+            ],
+            &[('A', 1)],
+            &[
+                (*b"PCLT", &be!(0x0002_0000_u32)),
+                (*b"kern", &be!(1u16, 0u16)),
+            ],
+        );
+
+        let (font, errors) =
+            open_font_collect_errors(alloc::alloc::Global, &mut SliceReader::new(&corrupt));
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        for tag in [*b"PCLT", *b"kern"] {
+            assert!(
+                errors.iter().any(|error| matches!(
+                    error,
+                    ParseError::UnsupportedVersion { tag: t, .. } if *t == tag
+                )),
+                "{errors:?}"
+            );
+        }
+
+        // The rest of the font is still usable
+        let font = font.expect("Font should parse past the corrupt tables");
+        assert_eq!(font.glyph_id('A'), Some(1));
+        assert!(font.pclt().is_none());
+    }
+
+    #[test]
+    fn diff_glyphs_finds_a_nudged_point() {
+        // Only the top right corner moves
+        let square = |corner| {
+            simple_glyph(&[&[
+                (100, 0, true),
+                (100, 400, true),
+                (corner, 400, true),
+                (500, 0, true),
+            ]])
+        };
+        let parse = |last_corner| {
+            let bytes = font(
+                &[
+                    (Vec::new(), 500),
+                    (square(500), 600),
+                    (square(last_corner), 600),
+                ],
+                &[('A', 1), ('B', 2)],
+            );
+            crate::parse_font_bytes(&bytes).expect("Font should parse")
+        };
+
+        let (a, b) = (parse(500), parse(501));
+        assert!(diff_glyphs(&a, &a).is_empty());
+        assert_eq!(diff_glyphs(&a, &b).as_slice(), [GlyphDiff::Modified(2)]);
+    }
+
+    #[test]
+    fn recorded_layout_matches_the_directory() {
+        let bytes = square_font();
+        let be_u32 =
+            |at: usize| u32::from_be_bytes(bytes[at..at + 4].try_into().expect("Slice is 4 bytes"));
+        let num_tables = usize::from(u16::from_be_bytes([bytes[4], bytes[5]]));
+        let directory: Vec<_> = (0..num_tables)
+            .map(|i| {
+                let record = 12 + 16 * i;
+                TableLayout {
+                    tag:      bytes[record..record + 4]
+                        .try_into()
+                        .expect("Slice is 4 bytes"),
+                    checksum: be_u32(record + 4),
+                    offset:   be_u32(record + 8),
+                    length:   be_u32(record + 12),
+                }
+            })
+            .collect();
+
+        let parse = |record_layout| {
+            let options = Options {
+                record_layout,
+                ..Options::default()
+            };
+            open_font_with_options(
+                alloc::alloc::Global,
+                &mut SliceReader::new(&bytes),
+                &options,
+            )
+            .expect("Font should parse")
+        };
+        assert_eq!(parse(true).layout(), directory.as_slice());
+        assert!(parse(false).layout().is_empty());
+    }
+
+    #[test]
+    fn direction_hints_are_checked_against_cmap() {
+        let options = Options::default();
+        let head = |hint| {
+            crate::tables::head::parse_table(
+                alloc::alloc::Global,
+                &options,
+                &[],
+                &mut SliceReader::new(&head(1000, hint, true)),
+            )
+            .expect("head should parse")
+        };
+        let cmap = |c| {
+            crate::tables::cmap::parse_table(
+                alloc::alloc::Global,
+                &options,
+                &[],
+                &mut SliceReader::new(&cmap(&[(3, 1, cmap4(&[(c, 1)]))])),
+            )
+            .expect("cmap should parse")
+        };
+
+        assert!(!direction_hint_contradicted(&head(2), &cmap('A')));
+        assert!(direction_hint_contradicted(&head(2), &cmap('\u{05d0}')));
+        assert!(direction_hint_contradicted(&head(-2), &cmap('A')));
+        assert!(!direction_hint_contradicted(&head(-2), &cmap('\u{05d0}')));
+        // Mixed directions
+        assert!(!direction_hint_contradicted(&head(0), &cmap('A')));
+        assert!(!direction_hint_contradicted(&head(0), &cmap('\u{05d0}')));
+    }
+
+    #[test]
+    fn fonts_after_a_prefix_open_at_their_offset() {
+        let mut bytes = vec![0xffu8; 128];
+        bytes.extend(square_font());
+
+        let font = open_font_at(alloc::alloc::Global, &mut SliceReader::new(&bytes), 128)
+            .expect("Font should parse");
+        assert_eq!(font.glyph_id('A'), Some(1));
+        assert_eq!(font.units_per_em(), Some(1000));
+        let glyph = font.glyph(1).expect("Glyph should exist");
+        assert_eq!(glyph.bounds(), (100, 0, 500, 400));
+
+        // The prefix isn't a font
+        assert!(open_font(alloc::alloc::Global, &mut SliceReader::new(&bytes)).is_err());
+        assert!(matches!(
+            open_font_at(
+                alloc::alloc::Global,
+                &mut SliceReader::new(&bytes[..100]),
+                128
+            ),
+            Err(ParseError::UnexpectedEop {
+                location: "base_offset",
+                needed:   28,
+            })
+        ));
+    }
+
+    #[test]
+    fn header_search_hints_fit_large_table_counts() {
+        assert_eq!(search_hints(1), (16, 0, 0));
+        assert_eq!(search_hints(12), (128, 3, 64));
+        assert_eq!(search_hints(u16::MAX), (524_288, 15, 524_272));
+
+        // The directory itself is missing, but the header alone parses
+        let header = be!(0x0001_0000u32, u16::MAX, 0xffff_u16, 15u16, 0xffff_u16);
+        let (version, num_tables) =
+            verify_header(&mut SliceReader::new(&header)).expect("Header should parse");
+        assert_eq!((version, num_tables), ([0, 1, 0, 0], u16::MAX));
+        assert!(parse_font_bytes(&header).is_err());
+    }
+
+    #[test]
+    fn header_without_tables_is_rejected() {
+        let header = be!(0x0001_0000u32, 0u16, 0u16, 0u16, 0u16);
+        assert!(matches!(
+            verify_header(&mut SliceReader::new(&header)),
+            Err(ParseError::Parsing {
+                variable: "numTables",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn wrong_search_hints_are_ignored() {
+        let mut font = square_font();
+        let num_tables = usize::from(u16::from_be_bytes([font[4], font[5]]));
+        let head = (0..num_tables)
+            .map(|idx| 12 + 16 * idx)
+            .find(|record| font[*record..*record + 4] == *b"head")
+            .map(|record| {
+                let offset =
+                    u32::from_be_bytes(font[record + 8..record + 12].try_into().expect("4 bytes"));
+                usize::try_from(offset).expect("Offset too large")
+            })
+            .expect("Font should have a head");
+
+        for hints in [[0u16; 3], [u16::MAX; 3], [16, 0, 0]] {
+            for (field, hint) in [6, 8, 10].into_iter().zip(hints) {
+                font[field..field + 2].copy_from_slice(&hint.to_be_bytes());
+            }
+            // Keeping ChecksumAdjustment right, so only the hints are wrong
+            font[head + 8..head + 12].fill(0);
+            let adjustment = 0xb1b0_afba_u32.wrapping_sub(checksum(&font));
+            font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+
+            let parsed = parse_font_bytes(&font).expect("Font should parse");
+            assert_eq!(parsed.glyph_id('A'), Some(1), "{hints:?}");
+        }
+    }
+
+    #[test]
+    fn parse_font_bytes_rejects_malformed_input() {
+        let font = square_font();
+        assert!(parse_font_bytes(&font).is_ok());
+
+        assert!(parse_font_bytes(&[]).is_err());
+        for len in 0..font.len() {
+            assert!(parse_font_bytes(&font[..len]).is_err(), "{len} bytes");
+        }
+
+        let mut empty = font[..12].to_vec();
+        empty[4..6].copy_from_slice(&0u16.to_be_bytes());
+        assert!(parse_font_bytes(&empty).is_err());
+    }
+
+    /// WOFF of the sfnt `font`, compressing every table that shrinks
+    fn woff(font: &[u8]) -> Vec<u8> {
+        let num_tables = u16::from_be_bytes([font[4], font[5]]);
+        // Data is kept in the font's order, which tables are parsed in
+        let mut records: Vec<_> = font[12..12 + 16 * usize::from(num_tables)]
+            .chunks(16)
+            .collect();
+        records.sort_by_key(|record| &record[8..12]);
+
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        let offset = 44 + 20 * usize::from(num_tables);
+        for record in records {
+            let word = |at: usize| {
+                u32::from_be_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]])
+            };
+            let [checksum, table_offset, length] = [4, 8, 12].map(word);
+            let table = &font[table_offset as usize..(table_offset + length) as usize];
+
+            let compressed = miniz_oxide::deflate::compress_to_vec_zlib(table, 6);
+            let stored = if compressed.len() < table.len() {
+                compressed.as_slice()
+            } else {
+                table
+            };
+            directory.extend_from_slice(&record[..4]);
+            directory.extend(be!(
+                (offset + data.len()) as u32,
+                stored.len() as u32,
+                length,
+                checksum
+            ));
+            data.extend_from_slice(stored);
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+
+        let length = (offset + data.len()) as u32;
+        let mut woff = b"wOFF".to_vec();
+        woff.extend(be!(
+            0x0001_0000u32,
+            length,
+            num_tables,
+            0u16,
+            font.len() as u32
+        ));
+        // No version, metadata or private data
+        woff.resize(44, 0);
+        woff.extend(directory);
+        woff.extend(data);
+        woff
+    }
+
+    #[test]
+    fn woff_inflates_to_the_original_font() {
+        // Repeated outlines, so `glyf` compresses
+        let glyphs: Vec<_> = (0..64)
+            .map(|_| (simple_glyph(&[&rect(100, 0, 500, 400)]), 600))
+            .collect();
+        let font = font(&glyphs, &[('A', 1)]);
+        let woff = woff(&font);
+        assert!(woff.len() < font.len());
+
+        let original = parse_font_bytes(&font).expect("Font should parse");
+        let inflated = open_woff(alloc::alloc::Global, &woff).expect("WOFF should parse");
+        assert_eq!(inflated.glyph_id('A'), original.glyph_id('A'));
+        assert_eq!(inflated.glyphs().as_slice(), original.glyphs().as_slice());
+        assert_eq!(inflated.advance_width(1), Some(600));
+    }
+
+    #[test]
+    fn woff_without_tables_is_rejected() {
+        let mut woff = woff(&square_font());
+        woff[12..14].copy_from_slice(&0u16.to_be_bytes());
+        assert!(matches!(
+            open_woff(alloc::alloc::Global, &woff[..44]),
+            Err(ParseError::Parsing {
+                variable: "WOFF::numTables",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn woff_with_bogus_lengths_is_rejected() {
+        let woff = woff(&square_font());
+        let record = |idx: usize| 44 + 20 * idx;
+        let word = |bytes: &[u8], at: usize| {
+            u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        let comp_length = word(&woff, record(0) + 8);
+
+        // Claims 4 GiB from a few bytes
+        let mut huge = woff.clone();
+        huge[record(0) + 12..record(0) + 16].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            open_woff(alloc::alloc::Global, &huge),
+            Err(ParseError::Parsing {
+                variable: "WOFF TableDirectoryEntry::origLength",
+                ..
+            })
+        ));
+
+        // Compressed data running past the end of the file
+        let mut past_end = woff.clone();
+        past_end[record(0) + 8..record(0) + 12].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(
+            open_woff(alloc::alloc::Global, &past_end),
+            Err(ParseError::Parsing {
+                variable: "WOFF TableDirectoryEntry::compLength",
+                ..
+            })
+        ));
+
+        // Plausible for the table, but not what the header says the font is
+        let mut longer = woff.clone();
+        longer[record(0) + 12..record(0) + 16].copy_from_slice(&(comp_length * 2).to_be_bytes());
+        assert!(matches!(
+            open_woff(alloc::alloc::Global, &longer),
+            Err(ParseError::Parsing {
+                variable: "WOFF::totalSfntSize",
+                ..
+            })
+        ));
+    }
+
+    /// `ttcf` of fonts made of the tables in `pool` at each font's indices,
+    /// stored once each in the order of `pool`
+    fn ttc(
+        pool: &[([u8; 4], Vec<u8>)],
+        fonts: &[&[usize]],
+    ) -> Vec<u8> {
+        let header = 12 + 4 * fonts.len();
+        let mut data = Vec::new();
+        let mut offsets = Vec::new();
+        for (_, table) in pool {
+            offsets.push((header + data.len()) as u32);
+            data.extend_from_slice(table);
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+
+        let mut bytes = b"ttcf".to_vec();
+        bytes.extend(be!(0x0001_0000u32, fonts.len() as u32));
+        let mut directories = Vec::new();
+        for tables in fonts {
+            bytes.extend(be!((header + data.len() + directories.len()) as u32));
+            directories.extend(be!(0x0001_0000u32, tables.len() as u16, 0u16, 0u16, 0u16));
+
+            let mut records: Vec<_> = tables
+                .iter()
+                .map(|&idx| (&pool[idx], offsets[idx]))
+                .collect();
+            records.sort_by_key(|((tag, _), _)| *tag);
+            for ((tag, table), offset) in records {
+                directories.extend_from_slice(tag);
+                directories.extend(be!(checksum(table), offset, table.len() as u32));
+            }
+        }
+        bytes.extend(data);
+        bytes.extend(directories);
+        bytes
+    }
+
+    #[test]
+    fn collections_share_tables_at_the_same_offset() {
+        let (glyf, loca) = glyf_loca(
+            &[Vec::new(), simple_glyph(&[&rect(100, 0, 500, 400)])],
+            true,
+        );
+        // Same `glyf`, but with every glyph empty
+        let (_, empty_loca) = glyf_loca(&[Vec::new(), Vec::new()], true);
+        let pool = [
+            (*b"head", head(1000, 2, true)),
+            (*b"maxp", maxp(2)),
+            (*b"hhea", hhea(800, -200, 2)),
+            (*b"hmtx", hmtx(&[(500, 0), (600, 100)], &[])),
+            (*b"loca", loca),
+            (*b"loca", empty_loca),
+            (*b"glyf", glyf),
+            (*b"cmap", cmap(&[(3, 1, cmap4(&[('A', 1)]))])),
+            (*b"cmap", cmap(&[(3, 1, cmap4(&[('B', 1)]))])),
+        ];
+        let fonts: [&[usize]; 3] = [&[0, 1, 2, 3, 4, 6, 7], &[0, 1, 2, 3, 4, 6, 8], &[
+            0, 1, 2, 3, 5, 6, 7,
+        ]];
+        let ttc = ttc(&pool, &fonts);
+
+        let parsed = open_collection(alloc::alloc::Global, &ttc).expect("Collection should parse");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].glyph_id('A'), Some(1));
+        assert_eq!(parsed[1].glyph_id('A'), None);
+        assert_eq!(parsed[1].glyph_id('B'), Some(1));
+        let glyph = |font: &Font| font.glyph(1).expect("Glyph should exist").bounds();
+        assert_eq!(glyph(&parsed[0]), (100, 0, 500, 400));
+        assert_eq!(glyph(&parsed[1]), (100, 0, 500, 400));
+        // Read through a different `loca`, so parsed again
+        assert_eq!(glyph(&parsed[2]), (0, 0, 0, 0));
+
+        // Only the second `cmap`, and the third font's `loca` and `glyf`, are
+        // parsed past the first font
+        let mut fonts = Vec::new();
+        let mut shared = Vec::new();
+        for idx in 0..3 {
+            let at = 12 + 4 * idx;
+            let offset = u32::from_be_bytes([ttc[at], ttc[at + 1], ttc[at + 2], ttc[at + 3]]);
+            let font = parse_font_slice(
+                alloc::alloc::Global,
+                &ttc,
+                offset as usize,
+                &Options::default(),
+                Some((&fonts, &mut shared)),
+            )
+            .expect("Font should parse");
+            fonts.push(font);
+        }
+        assert_eq!(shared.len(), 7 + 1 + 2);
+    }
 }
@@ -5,7 +5,6 @@
 #![feature(generic_const_exprs)] // CoreRead::read_int
 #![feature(new_range_api)] // glyf
 #![feature(new_uninit)] // name
-#![feature(array_chunks)] // name
 #![feature(array_windows)] // render
 #![allow(incomplete_features)]
 
@@ -14,19 +13,70 @@ extern crate alloc;
 mod font;
 pub mod render;
 mod tables;
+#[cfg(test)] mod testing;
 mod types;
 
 pub use font::{
+    diff_glyphs,
+    open_collection,
     open_font,
+    open_font_at,
+    open_font_collect_errors,
+    open_font_from_bytes,
+    open_font_with_options,
+    open_woff,
+    parse_font_bytes,
     Collection as FontCollection,
     Font,
+    FontSummary,
+    GlyphDiff,
+    GlyphMetrics,
+    Options as FontOptions,
+    OutlineFormat,
+    PpemMetrics,
+    TableLayout,
     Trait as FontTrait,
 };
 pub use render::{
+    hit_test,
+    render_chars,
+    render_glyphs_separate,
+    render_on_path,
+    to_band as render_band,
     to_buf as render_to_buf,
+    to_buf_rgb as render_to_buf_rgb,
     Error as RenderError,
     FormattedText,
+    GlyphBitmap,
+    GlyphPlacement,
+    HitResult,
+    InkBounds,
+    RenderReport,
+    Renderer,
     SubPixelAlignment,
 };
-pub use tables::name::RecordType as NameRecord;
+pub use tables::{
+    cbdt::{
+        ColorBitmap,
+        ColorImage,
+    },
+    glyf::{
+        Component as GlyphComponent,
+        ComponentFlags,
+        Glyph,
+        PathCmd,
+        Segment,
+    },
+    maxp::{
+        Limits as MaxpLimits,
+        Type as Maxp,
+    },
+    name::RecordType as NameRecord,
+    os2::{
+        Type as Os2,
+        UnicodeBlock,
+        UNICODE_BLOCKS,
+    },
+    pclt::Type as Pclt,
+};
 pub use types::ParseError;
@@ -0,0 +1,158 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+//! Golden-image tests, comparing renders against PNGs in `src/render/golden`.
+//! Run with `GLFONT_BLESS` set to regenerate the references after an intended
+//! change to rendering, then review the new images before committing them
+
+use super::*;
+use crate::testing::{
+    font,
+    rect,
+    simple_glyph,
+};
+
+/// Largest root mean square difference from a reference, out of 255, allowing
+/// for float rounding differences between platforms
+const TOLERANCE: f64 = 1.0;
+
+/// Font of an 'A' with a counter and a curved, overlapping 'g' descending
+/// below the baseline
+fn ag_font() -> Vec<u8> {
+    let a = simple_glyph(&[
+        &[
+            (0, 0, true),
+            (250, 700, true),
+            (350, 700, true),
+            (600, 0, true),
+            (480, 0, true),
+            (420, 180, true),
+            (180, 180, true),
+            (120, 0, true),
+        ],
+        // Counter, running counter-clockwise
+        &[(210, 280, true), (390, 280, true), (300, 560, true)],
+    ]);
+    let g = simple_glyph(&[
+        &[
+            (300, 500, true),
+            (500, 500, false),
+            (500, 300, true),
+            (500, 100, false),
+            (300, 100, true),
+            (100, 100, false),
+            (100, 300, true),
+            (100, 500, false),
+        ],
+        &[
+            (300, 400, true),
+            (200, 400, false),
+            (200, 300, true),
+            (200, 200, false),
+            (300, 200, true),
+            (400, 200, false),
+            (400, 300, true),
+            (400, 400, false),
+        ],
+        &rect(400, -200, 500, 500),
+        &rect(100, -200, 500, -100),
+    ]);
+
+    font(&[(Vec::new(), 500), (a, 650), (g, 600)], &[
+        ('A', 1),
+        ('g', 2),
+    ])
+}
+
+/// Renders `text` in [`ag_font`] at `size` pixels into a `width` by `height`
+/// buffer
+fn render(
+    text: &str,
+    size: u16,
+    (width, height): (usize, usize),
+) -> Vec<u8> {
+    let mut fonts = FontCollection::new(alloc::alloc::Global);
+    let id = fonts.add_loaded(crate::parse_font_bytes(&ag_font()).expect("Font should parse"));
+
+    let mut buffer = vec![rgb::Gray::new(0u8); width * height];
+    Renderer::new(&fonts)
+        .draw(
+            &[FormattedSlice {
+                id,
+                size,
+                text,
+                ruby: None,
+            }],
+            &mut buffer,
+            width,
+        )
+        .expect("Render should succeed");
+    buffer.into_iter().map(|pixel| pixel.0).collect()
+}
+
+/// Compares `pixels` against the reference `name`, or replaces the reference
+/// when `GLFONT_BLESS` is set
+#[allow(clippy::cast_precision_loss)]
+fn check_golden(
+    name: &str,
+    pixels: &[u8],
+    width: usize,
+) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/render/golden")
+        .join(name)
+        .with_extension("png");
+    let width = u32::try_from(width).expect("Golden too wide");
+    let height = u32::try_from(pixels.len()).expect("Golden too large") / width;
+
+    if std::env::var_os("GLFONT_BLESS").is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).expect("Reference directory should be creatable");
+        }
+        let file = std::fs::File::create(&path).expect("Reference should be writable");
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(pixels))
+            .expect("Reference should encode");
+        return;
+    }
+
+    let file = std::fs::File::open(&path)
+        .unwrap_or_else(|_| panic!("Missing {}, run with GLFONT_BLESS set", path.display()));
+    let mut decoder = png::Decoder::new(std::io::BufReader::new(file))
+        .read_info()
+        .expect("Reference should decode");
+    let mut reference = vec![0; decoder.output_buffer_size()];
+    let info = decoder
+        .next_frame(&mut reference)
+        .expect("Reference should decode");
+    assert_eq!(
+        (info.width, info.height),
+        (width, height),
+        "{name} changed size"
+    );
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+
+    let squared: f64 = pixels
+        .iter()
+        .zip(&reference)
+        .map(|(&a, &b)| f64::from(a.abs_diff(b)).powi(2))
+        .sum();
+    let rms = (squared / pixels.len() as f64).sqrt();
+    assert!(
+        rms <= TOLERANCE,
+        "{name} differs from its reference by {rms} RMS"
+    );
+}
+
+#[test]
+fn ag_at_32px() {
+    let size = (48, 48);
+    let pixels = render("Ag", 32, size);
+    assert!(pixels.iter().any(|&pixel| pixel == u8::MAX));
+    check_golden("ag_32px", &pixels, size.0);
+}
@@ -4,9 +4,17 @@
 use itertools::Itertools;
 use num_traits::PrimInt;
 
+#[cfg(test)] mod golden;
 mod shapes;
 
 use crate::{
+    tables::{
+        glyf::{
+            Glyph,
+            Segment,
+        },
+        Table,
+    },
     types::{
         CoreVec,
         SlotmapKey,
@@ -16,15 +24,42 @@ use crate::{
 };
 
 #[derive(thiserror::Error, Debug)]
-pub enum Error {}
+pub enum Error {
+    #[error("Glyph {0} not in font")]
+    GlyphNotFound(u32),
 
-#[derive(Debug)]
+    #[error("Buffer too small (needed {needed}, got {got})")]
+    BufferTooSmall { needed: usize, got: usize },
+
+    #[error("Missing required table {0}")]
+    MissingTable(&'static str),
+}
+
+/// Order of a display's subpixels from left to right, used by
+/// [`Renderer::draw_rgb`]
+#[derive(Debug, Copy, Clone)]
 pub enum SubPixelAlignment {
+    /// Red takes the coverage of the left third of each pixel and blue the
+    /// right third
     Rgb,
+    /// Blue takes the coverage of the left third of each pixel and red the
+    /// right third
     Bgr,
+    /// Every channel takes the coverage of the whole pixel
     None,
 }
 
+#[derive(Debug, Copy, Clone)]
+pub enum RenderMode {
+    /// Fills glyphs using the non-zero winding rule
+    Standard,
+    /// Strokes the outline of glyphs without filling them
+    Outline,
+    /// Draws the control polygon, marking on-curve points at full coverage
+    /// and off-curve points at half coverage
+    DebugOutline,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum DrawMode {
     Overwrite,
@@ -32,6 +67,276 @@ pub enum DrawMode {
     Add,
 }
 
+impl DrawMode {
+    /// Combines the coverage `value` with the `prev` pixel value
+    fn apply<T: PrimInt>(
+        self,
+        prev: T,
+        value: T,
+    ) -> T {
+        match self {
+            Self::Overwrite => value,
+            // Scaled so full coverage keeps `prev`, with the product widened as
+            // it overflows `T`. Only 64-bit and wider pixels fall back to f64
+            Self::Multiply => match (prev.to_i128(), value.to_i128(), T::max_value().to_i128()) {
+                (Some(prev), Some(value), Some(max)) => prev
+                    .checked_mul(value)
+                    .and_then(|product| T::from(product / max)),
+                _ => None,
+            }
+            .or_else(|| {
+                let max = T::max_value().to_f64()?;
+                T::from(prev.to_f64()? * value.to_f64()? / max)
+            })
+            .unwrap_or_else(T::max_value),
+            Self::Add => prev.saturating_add(value),
+        }
+    }
+}
+
+/// How coverage is quantized to the pixel type
+#[derive(Debug, Copy, Clone)]
+pub enum CoverageRounding {
+    /// Biases edges lighter
+    Floor,
+    Round,
+    /// Biases edges darker
+    Ceil,
+}
+
+/// Summary of a render, letting callers detect content that didn't fit
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RenderReport {
+    /// Number of glyphs drawn, including clipped glyphs
+    pub drawn_glyphs:   usize,
+    /// Number of glyphs which had pixels fall outside the buffer
+    pub clipped_glyphs: usize,
+    /// Width required to fit everything drawn
+    pub used_width:     usize,
+    /// Height required to fit everything drawn
+    pub used_height:    usize,
+    /// Tightest rectangle around the pixels given coverage, `None` when
+    /// nothing visible was drawn. Rows count from the top of the image rather
+    /// than the band when drawing with [`Renderer::draw_band`]
+    pub ink_bounds:     Option<InkBounds>,
+}
+
+/// Rectangle of pixels within the buffer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InkBounds {
+    pub x:      usize,
+    pub y:      usize,
+    pub width:  usize,
+    pub height: usize,
+}
+
+impl InkBounds {
+    /// Grows `bounds` to contain the pixel at (`x`, `y`)
+    fn include(
+        bounds: &mut Option<Self>,
+        x: usize,
+        y: usize,
+    ) {
+        *bounds = Some(bounds.map_or(
+            Self {
+                x,
+                y,
+                width: 1,
+                height: 1,
+            },
+            |b| {
+                let (x_min, y_min) = (b.x.min(x), b.y.min(y));
+                Self {
+                    x:      x_min,
+                    y:      y_min,
+                    width:  (b.x + b.width).max(x + 1) - x_min,
+                    height: (b.y + b.height).max(y + 1) - y_min,
+                }
+            },
+        ));
+    }
+}
+
+/// Where [`Renderer::layout`] put a glyph, in pixels before
+/// [`Renderer::transform`] with y pointing down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphPlacement {
+    /// Font the glyph is from, a fallback when the slice's font lacks it
+    pub font:     SlotmapKey,
+    pub glyph_id: u32,
+    /// Index of the glyph's character, counting through every slice's text as
+    /// [`Renderer::caret`] does
+    pub cluster:  usize,
+    /// Pen position on the baseline the glyph's advance starts from
+    pub x:        f32,
+    pub y:        f32,
+    /// Distance to the next glyph's pen position, a whole number of cells
+    /// within a [`Renderer::cell_width`] grid
+    pub advance:  f32,
+    /// Adjustment to the pen from `kern` before the glyph, 0 without a pair
+    /// or with [`Renderer::kerning`] off
+    pub kern:     f32,
+    /// Line box of the glyph's font, above the baseline and negative below
+    pub ascent:   f32,
+    pub descent:  f32,
+}
+
+/// Glyph found under a point by [`hit_test`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitResult {
+    /// Index into the placements searched
+    pub glyph_index: usize,
+    pub cluster:     usize,
+    /// Whether the point is in the first half of the glyph's advance, putting
+    /// a caret before the glyph rather than after
+    pub leading:     bool,
+}
+
+/// Glyph of `placements` whose advance and line box contain the centre of
+/// pixel (`x`, `y`). Placements are before [`Renderer::transform`], so the
+/// point must be too
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn hit_test(
+    placements: &[GlyphPlacement],
+    x: i32,
+    y: i32,
+) -> Option<HitResult> {
+    let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+    placements
+        .iter()
+        .enumerate()
+        .find(|(_, p)| {
+            (p.x..p.x + p.advance).contains(&x) && (p.y - p.ascent..p.y - p.descent).contains(&y)
+        })
+        .map(|(glyph_index, p)| HitResult {
+            glyph_index,
+            cluster: p.cluster,
+            leading: x < p.advance.mul_add(0.5, p.x),
+        })
+}
+
+/// A glyph drawn on its own by [`Renderer::draw_glyphs_separate`]
+#[derive(Debug, Clone)]
+pub struct GlyphBitmap<T: PrimInt, A: core::alloc::Allocator> {
+    pub placement: GlyphPlacement,
+    /// Pixel of the full render under the bitmap's top left corner, which may
+    /// be outside it
+    pub x:         i32,
+    pub y:         i32,
+    pub width:     usize,
+    pub height:    usize,
+    /// Coverage over `T::min_value()`, row by row
+    pub pixels:    CoreVec<rgb::Gray<T>, A>,
+}
+
+/// Pixels from the top of the buffer to the baseline of the text
+const BASELINE: f32 = 40.0;
+
+/// Whether `c` is East Asian Wide or Fullwidth, taking two cells of a fixed
+/// grid. Covers the main blocks rather than the full Unicode tables
+const fn is_wide(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x115f // Hangul Jamo initials
+            | 0x2e80..=0x303e // CJK radicals, punctuation
+            | 0x3041..=0x33ff // Kana, CJK compatibility
+            | 0x3400..=0x4dbf // CJK extension A
+            | 0x4e00..=0x9fff // CJK unified ideographs
+            | 0xa000..=0xa4cf // Yi
+            | 0xac00..=0xd7a3 // Hangul syllables
+            | 0xf900..=0xfaff // CJK compatibility ideographs
+            | 0xfe30..=0xfe4f // CJK compatibility forms
+            | 0xff00..=0xff60 // Fullwidth forms
+            | 0xffe0..=0xffe6
+            | 0x1f300..=0x1f64f // Pictographs, emoticons
+            | 0x1f900..=0x1f9ff
+            | 0x20000..=0x3fffd // CJK extensions B onwards
+    )
+}
+
+/// `FreeType`'s light LCD filter, spreading each subpixel's coverage onto its
+/// neighbours to soften colour fringes. Weights sum to 256
+const LCD_FILTER: [f32; 3] = [85.0, 86.0, 85.0];
+
+/// Affine transform leaving points where they are, see [`Renderer::transform`]
+const IDENTITY: [[f32; 2]; 3] = [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]];
+
+/// Maps `point` through `transform`, whose rows are the images of the x and y
+/// unit vectors followed by the translation
+const fn apply_transform(
+    transform: &[[f32; 2]; 3],
+    (x, y): (f32, f32),
+) -> (f32, f32) {
+    (
+        x.mul_add(transform[0][0], y.mul_add(transform[1][0], transform[2][0])),
+        x.mul_add(transform[0][1], y.mul_add(transform[1][1], transform[2][1])),
+    )
+}
+
+/// Transform applying `first` then `second`
+fn then_transform(
+    first: &[[f32; 2]; 3],
+    second: &[[f32; 2]; 3],
+) -> [[f32; 2]; 3] {
+    // Axes aren't translated
+    let linear = [second[0], second[1], [0.0, 0.0]];
+    [
+        apply_transform(&linear, first[0].into()).into(),
+        apply_transform(&linear, first[1].into()).into(),
+        apply_transform(second, first[2].into()).into(),
+    ]
+}
+
+/// Point `distance` pixels along `path` and the path's unit direction there,
+/// extending its first and last segments past its ends. A path without length
+/// runs to the right of its first point
+fn along_path(
+    path: &[(f32, f32)],
+    distance: f32,
+) -> ((f32, f32), (f32, f32)) {
+    let mut travelled = 0.0;
+    // (start, direction, distance to start) of the last segment with length
+    let mut last = None;
+    for (&start, &end) in path.iter().tuple_windows() {
+        let length = (end.0 - start.0).hypot(end.1 - start.1);
+        if length <= f32::EPSILON {
+            continue;
+        }
+        let direction = ((end.0 - start.0) / length, (end.1 - start.1) / length);
+        last = Some((start, direction, travelled));
+        if distance <= travelled + length {
+            break;
+        }
+        travelled += length;
+    }
+
+    let (start, direction, to_start) =
+        last.unwrap_or_else(|| (path.first().copied().unwrap_or_default(), (1.0, 0.0), 0.0));
+    let along = distance - to_start;
+    (
+        (
+            direction.0.mul_add(along, start.0),
+            direction.1.mul_add(along, start.1),
+        ),
+        direction,
+    )
+}
+
+/// Moves a glyph laid out at `placement` onto `path`, with the middle of its
+/// advance on the path and its baseline along it
+fn path_transform(
+    path: &[(f32, f32)],
+    placement: &GlyphPlacement,
+) -> [[f32; 2]; 3] {
+    let middle = placement.advance.mul_add(0.5, placement.x);
+    let ((x, y), (dx, dy)) = along_path(path, middle);
+    [[dx, dy], [-dy, dx], [
+        placement.y.mul_add(dy, middle.mul_add(-dx, x)),
+        placement.y.mul_add(-dx, middle.mul_add(-dy, y)),
+    ]]
+}
+
 pub type FormattedText<'a, A> = CoreVec<FormattedSlice<'a>, A>;
 
 #[derive(Debug)]
@@ -39,97 +344,2267 @@ pub struct FormattedSlice<'a> {
     pub id:   SlotmapKey,
     pub size: u16,
     pub text: &'a str,
+    /// Annotation drawn at half `size`, centered above `text`, e.g. furigana
+    pub ruby: Option<&'a str>,
 }
 
 struct Display<'a, T: PrimInt> {
-    pub buffer: &'a mut [rgb::Gray<T>],
-    pub width:  usize,
+    pub buffer:   &'a mut [rgb::Gray<T>],
+    pub width:    usize,
+    // Row of the image held by the first row of `buffer`
+    pub y_offset: usize,
+
+    // When false, coverage is thresholded to fully on/off at 0.5
+    pub antialias: bool,
+    // Multiplier applied to the coverage of the glyph being drawn
+    pub opacity:   f32,
+    pub rounding:  CoverageRounding,
+    // Applied to every point once the glyph is positioned
+    pub transform: [[f32; 2]; 3],
+
+    // Set when a pixel falls outside of the buffer
+    pub clipped: bool,
+    // (width, height) needed to contain every pixel drawn
+    pub extent:  (usize, usize),
+    // Pixels within the buffer given coverage
+    pub ink:     Option<InkBounds>,
+}
+
+/// Draws `glyph` with its origin (left side of the baseline) at `origin`
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn draw_glyph<A: core::alloc::Allocator + Clone, T: PrimInt>(
+    display: &mut Display<'_, T>,
+    glyph: &Glyph<A>,
+    scale: (f32, f32),
+    origin: (f32, f32),
+    draw_mode: DrawMode,
+    mode: RenderMode,
+) {
+    let transform = display.transform;
+    let to_px = |(x, y): (f32, f32)| {
+        apply_transform(
+            &transform,
+            (x.mul_add(scale.0, origin.0), y.mul_add(-scale.1, origin.1)),
+        )
+    };
+
+    // Skip glyphs entirely outside the band, leaving room for debug markers.
+    // Any corner of the bounding box may end up highest once transformed
+    let (x_bounds, y_bounds) = (glyph.x_bounds, glyph.y_bounds);
+    let corners = [
+        (x_bounds.start, y_bounds.start),
+        (x_bounds.start, y_bounds.last),
+        (x_bounds.last, y_bounds.start),
+        (x_bounds.last, y_bounds.last),
+    ]
+    .map(|(x, y)| to_px((f32::from(x), f32::from(y))).1);
+    let top = corners.into_iter().fold(f32::MAX, f32::min) - 2.0;
+    let bottom = corners.into_iter().fold(f32::MIN, f32::max) + 2.0;
+    let band_end = display.y_offset + display.buffer.len() / display.width;
+    if bottom < display.y_offset as f32 || top > band_end as f32 {
+        display.clipped = true;
+        return;
+    }
+    let to_px_int = |x: i16, y: i16| {
+        let (x, y) = to_px((f32::from(x), f32::from(y)));
+        (x as i32, y as i32)
+    };
+
+    if matches!(mode, RenderMode::Standard) {
+        // Within a quarter pixel of the curves
+        let flatness = 0.25 / scale.0.max(scale.1);
+        shapes::fill_glyph(
+            display,
+            draw_mode,
+            glyph.points.allocator().clone(),
+            glyph
+                .flattened_segments(1.0, flatness)
+                .map(|(start, end)| (to_px(start), to_px(end))),
+        );
+        return;
+    }
+
+    // Stroked a pixel wide, which accumulates coverage whatever `draw_mode` is
+    if matches!(mode, RenderMode::Outline) {
+        for segment in glyph.segments().flatten() {
+            match segment {
+                Segment::Line(start, end) => {
+                    shapes::draw_line(display, to_px(start), to_px(end), 1.0);
+                },
+                Segment::Quad(start, control, end) => {
+                    shapes::draw_quad_bezier(display, to_px(start), to_px(control), to_px(end));
+                },
+            }
+        }
+        return;
+    }
+
+    for contour in glyph.contours() {
+        for ((x1, y1, _), (x2, y2, _)) in contour.clone().circular_tuple_windows() {
+            shapes::draw_line_aliased(display, draw_mode, to_px_int(x1, y1), to_px_int(x2, y2));
+        }
+
+        for (x, y, on_curve) in contour {
+            let (x, y) = to_px_int(x, y);
+            shapes::draw_marker(
+                display,
+                draw_mode,
+                x,
+                y,
+                if on_curve { 1f32 } else { 0.5f32 },
+            );
+        }
+    }
+}
 
-    pub dpi:      u16,
-    pub subpixel: SubPixelAlignment,
+impl<A: core::alloc::Allocator + Clone> Glyph<A> {
+    /// Rasterizes the glyph into `buffer`, scaling font units by `scale` with
+    /// the glyph's origin (left side of the baseline) placed at `origin`
+    pub fn render_into<T: PrimInt>(
+        &self,
+        buffer: &mut [rgb::Gray<T>],
+        width: usize,
+        scale: f32,
+        origin: (f32, f32),
+        draw_mode: DrawMode,
+        antialias: bool,
+    ) {
+        let mut display = Display {
+            buffer,
+            width,
+            y_offset: 0,
+            antialias,
+            opacity: 1.0,
+            rounding: CoverageRounding::Floor,
+            transform: IDENTITY,
+            clipped: false,
+            extent: (0, 0),
+            ink: None,
+        };
+
+        draw_glyph(
+            &mut display,
+            self,
+            (scale, scale),
+            origin,
+            draw_mode,
+            RenderMode::Standard,
+        );
+    }
+}
+
+/// Bundles the render settings, e.g.
+/// `Renderer::new(&fonts).dpi(72).subpixel(SubPixelAlignment::Rgb).draw(&input,
+/// &mut buffer, width)`
+#[allow(clippy::struct_excessive_bools)]
+pub struct Renderer<'a, A: core::alloc::Allocator + core::fmt::Debug + 'static, T: PrimInt> {
+    fonts:       &'a FontCollection<A>,
+    dpi:         (u16, u16),
+    subpixel:    SubPixelAlignment,
+    mode:        RenderMode,
+    antialias:   bool,
+    draw_mode:   DrawMode,
+    background:  Option<T>,
+    clear:       bool,
+    opacity:     &'a [f32],
+    rounding:    CoverageRounding,
+    cell_width:  Option<f32>,
+    color:       Option<rgb::RGB<T>>,
+    premultiply: bool,
+    transform:   [[f32; 2]; 3],
+    kerning:     bool,
+    caret:       Option<usize>,
 }
 
+impl<'a, A: core::alloc::Allocator + Clone + core::fmt::Debug + 'static, T: PrimInt>
+    Renderer<'a, A, T>
+{
+    pub const fn new(fonts: &'a FontCollection<A>) -> Self {
+        Self {
+            fonts,
+            dpi: (72, 72),
+            subpixel: SubPixelAlignment::None,
+            mode: RenderMode::Standard,
+            antialias: true,
+            draw_mode: DrawMode::Overwrite,
+            background: None,
+            clear: true,
+            opacity: &[],
+            rounding: CoverageRounding::Floor,
+            cell_width: None,
+            color: None,
+            premultiply: false,
+            transform: IDENTITY,
+            kerning: true,
+            caret: None,
+        }
+    }
+
+    /// How glyph coverage is combined with the existing buffer contents
+    #[must_use]
+    pub const fn draw_mode(
+        mut self,
+        draw_mode: DrawMode,
+    ) -> Self {
+        self.draw_mode = draw_mode;
+        self
+    }
+
+    /// Fills the buffer with `background` before drawing. Without one, the
+    /// buffer is cleared to `T::min_value()` for [`DrawMode::Overwrite`] and
+    /// left untouched when compositing
+    #[must_use]
+    pub const fn background(
+        mut self,
+        background: T,
+    ) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// When false the buffer is never pre-filled, letting successive draws
+    /// accumulate into the same buffer
+    #[must_use]
+    pub const fn clear(
+        mut self,
+        clear: bool,
+    ) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    /// Opacity of each glyph in draw order, for fade effects. Glyphs past the
+    /// end of `opacity` are drawn opaque
+    #[must_use]
+    pub const fn opacity(
+        mut self,
+        opacity: &'a [f32],
+    ) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    #[must_use]
+    pub const fn coverage_rounding(
+        mut self,
+        rounding: CoverageRounding,
+    ) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Advances every glyph by `cell_width` pixels, centered within its cell,
+    /// for fixed grids such as terminals. Wide characters take two cells
+    #[must_use]
+    pub const fn cell_width(
+        mut self,
+        cell_width: f32,
+    ) -> Self {
+        self.cell_width = Some(cell_width);
+        self
+    }
+
+    /// Colour of the text drawn by [`Renderer::draw_rgba`], white by default
+    #[must_use]
+    pub const fn color(
+        mut self,
+        color: rgb::RGB<T>,
+    ) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Makes [`Renderer::draw_rgba`] multiply the colour by coverage, for
+    /// pipelines expecting premultiplied alpha
+    #[must_use]
+    pub const fn premultiply(
+        mut self,
+        premultiply: bool,
+    ) -> Self {
+        self.premultiply = premultiply;
+        self
+    }
+
+    /// Whether pairs of glyphs from the same font are spaced by its `kern`
+    /// table, on by default. Never applied within a [`Renderer::cell_width`]
+    /// grid
+    #[must_use]
+    pub const fn kerning(
+        mut self,
+        kerning: bool,
+    ) -> Self {
+        self.kerning = kerning;
+        self
+    }
+
+    /// Draws a caret before the character at `index`, counting through every
+    /// slice's text but not ruby, or after the last character when `index`
+    /// is the number of characters. It spans the font's ascent to descent,
+    /// slanted by its `hhea` caret slope
+    #[must_use]
+    pub const fn caret(
+        mut self,
+        index: usize,
+    ) -> Self {
+        self.caret = Some(index);
+        self
+    }
+
+    /// Affine transform applied to the laid out text before it's drawn, e.g.
+    /// `[[0.0, -1.0], [1.0, 0.0], [0.0, height]]` to rotate it 90°
+    /// anticlockwise. Rows are where the x and y axes map to, then the
+    /// translation, all in pixels with y pointing down
+    #[must_use]
+    pub const fn transform(
+        mut self,
+        transform: [[f32; 2]; 3],
+    ) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    #[must_use]
+    pub const fn dpi(
+        mut self,
+        dpi: u16,
+    ) -> Self {
+        self.dpi = (dpi, dpi);
+        self
+    }
+
+    /// Separate horizontal and vertical DPI, for displays with non-square
+    /// pixels
+    #[must_use]
+    pub const fn dpi_xy(
+        mut self,
+        x: u16,
+        y: u16,
+    ) -> Self {
+        self.dpi = (x, y);
+        self
+    }
+
+    #[must_use]
+    pub const fn subpixel(
+        mut self,
+        subpixel: SubPixelAlignment,
+    ) -> Self {
+        self.subpixel = subpixel;
+        self
+    }
+
+    #[must_use]
+    pub const fn mode(
+        mut self,
+        mode: RenderMode,
+    ) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub const fn antialias(
+        mut self,
+        antialias: bool,
+    ) -> Self {
+        self.antialias = antialias;
+        self
+    }
+
+    /// Value the buffer is filled with before drawing, if any
+    fn clear_value(&self) -> Option<T> {
+        match (self.clear, self.background, self.draw_mode) {
+            (true, Some(background), _) => Some(background),
+            (true, None, DrawMode::Overwrite) => Some(T::min_value()),
+            (false, ..) | (true, None, DrawMode::Multiply | DrawMode::Add) => None,
+        }
+    }
+
+    /// # Errors
+    /// - If `buffer` doesn't hold a single row of `width`
+    /// - If a font is missing its `head` table or the glyph being drawn
+    /// # Panics
+    /// - If a slice's font does not exist in the collection
+    pub fn draw(
+        &self,
+        input: &[FormattedSlice<'_>],
+        buffer: &mut [rgb::Gray<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        let height = buffer.len().checked_div(width).unwrap_or_default();
+        self.draw_band(input, 0, height.max(1), buffer, width)
+    }
+
+    /// Draws only rows `y_start..y_start + band_height` of the image [`draw`]
+    /// would produce into `buffer`, for outputs consuming a strip at a time.
+    /// Glyphs outside the band are skipped but still counted as clipped
+    ///
+    /// [`draw`]: Self::draw
+    /// # Errors
+    /// - If `buffer` doesn't hold `band_height` rows of `width`
+    /// - See [`Renderer::draw`]
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    pub fn draw_band(
+        &self,
+        input: &[FormattedSlice<'_>],
+        y_start: usize,
+        band_height: usize,
+        buffer: &mut [rgb::Gray<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        let runs = input
+            .iter()
+            .map(|slice| (slice.id, slice.size, slice.text.chars(), slice.ruby));
+        self.draw_runs(runs, None, y_start, band_height, buffer, width)
+    }
+
+    /// Draws `chars` in font `id` at `size` as [`Renderer::draw`] would draw
+    /// them as a single slice, mapping each character as it's taken rather
+    /// than collecting them into a string first
+    /// # Errors
+    /// - See [`Renderer::draw`]
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    pub fn draw_chars(
+        &self,
+        id: SlotmapKey,
+        size: u16,
+        chars: impl Iterator<Item = char>,
+        buffer: &mut [rgb::Gray<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        let height = buffer.len().checked_div(width).unwrap_or_default();
+        self.draw_runs(
+            core::iter::once((id, size, chars, None)),
+            None,
+            0,
+            height.max(1),
+            buffer,
+            width,
+        )
+    }
+
+    /// Draws `input` with each glyph's baseline following `path`, a polyline
+    /// in pixels, centered where its advance falls along it and rotated to
+    /// the path's direction there. Glyphs past either end continue along the
+    /// end segments. Ruby and the caret are laid out flat, so aren't drawn
+    /// # Errors
+    /// - See [`Renderer::draw`]
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    pub fn draw_on_path(
+        &self,
+        input: &[FormattedSlice<'_>],
+        path: &[(f32, f32)],
+        buffer: &mut [rgb::Gray<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        let height = buffer.len().checked_div(width).unwrap_or_default();
+        let runs = input
+            .iter()
+            .map(|slice| (slice.id, slice.size, slice.text.chars(), None));
+        Self {
+            caret: None,
+            ..*self
+        }
+        .draw_runs(runs, Some(path), 0, height.max(1), buffer, width)
+    }
+
+    /// Draws each run of `(font, size, characters, ruby)` after the last, see
+    /// [`Renderer::draw_band`], placing the glyphs along `path` if given
+    /// # Errors
+    /// - See [`Renderer::draw_band`]
+    fn draw_runs<'r>(
+        &self,
+        runs: impl Iterator<Item = (SlotmapKey, u16, impl Iterator<Item = char>, Option<&'r str>)>,
+        path: Option<&[(f32, f32)]>,
+        y_start: usize,
+        band_height: usize,
+        buffer: &mut [rgb::Gray<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        let needed = width.max(1) * band_height.max(1);
+        if width == 0 || band_height == 0 || buffer.len() < needed {
+            return Err(Error::BufferTooSmall {
+                needed,
+                got: buffer.len(),
+            });
+        }
+        let buffer = &mut buffer[..needed];
+
+        let clear = self.clear_value();
+        if let Some(clear) = clear {
+            for v in buffer.iter_mut() {
+                *v = rgb::Gray::new(clear);
+            }
+        }
+
+        let mut display = Display {
+            buffer,
+            width,
+            y_offset: y_start,
+            antialias: self.antialias,
+            opacity: 1.0,
+            rounding: self.rounding,
+            transform: self.transform,
+            clipped: false,
+            extent: (0, 0),
+            ink: None,
+        };
+        let mut report = RenderReport::default();
+
+        // Kept fractional so advances don't accumulate rounding error
+        let mut pen = (0f32, BASELINE);
+        let mut prev_glyph = None;
+        // (x, font, size) of the caret once its character is reached
+        let mut caret = None;
+        // Characters laid out so far
+        let mut cluster = 0;
+
+        for (id, size, mut chars, ruby) in runs {
+            let run_start = pen.0;
+
+            // The run is laid out either side of the caret, which is placed
+            // once the characters before it have all been laid out
+            let run_cluster = cluster;
+            let before = self
+                .caret
+                .and_then(|index| index.checked_sub(run_cluster))
+                .filter(|_| caret.is_none());
+            for take in [before, None] {
+                self.layout_run(
+                    id,
+                    size,
+                    chars.by_ref().take(take.unwrap_or(usize::MAX)),
+                    self.cell_width,
+                    &mut pen,
+                    &mut prev_glyph,
+                    &mut cluster,
+                    |glyph, scale, origin, placement| {
+                        if let Some(path) = path {
+                            display.transform =
+                                then_transform(&path_transform(path, placement), &self.transform);
+                        }
+                        self.place_glyph(&mut display, &mut report, glyph, scale, origin);
+                    },
+                )?;
+                if take.is_some_and(|take| cluster - run_cluster == take) {
+                    caret = Some((pen.0, id, size));
+                }
+            }
+
+            if let Some(ruby) = ruby {
+                self.draw_ruby(
+                    &mut display,
+                    &mut report,
+                    (id, size),
+                    ruby,
+                    run_start..pen.0,
+                )?;
+            }
+        }
+
+        if let Some((x, id, size)) = caret {
+            self.draw_caret(&mut display, x, id, size)?;
+        }
+
+        (report.used_width, report.used_height) = display.extent;
+        report.ink_bounds = display.ink;
+        Ok(report)
+    }
+
+    /// Positions of the glyphs [`Renderer::draw`] would draw for `input`, in
+    /// order. Ruby isn't included
+    /// # Errors
+    /// - If a font is missing its `head` table or a glyph being laid out
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    pub fn layout(
+        &self,
+        input: &[FormattedSlice<'_>],
+    ) -> Result<CoreVec<GlyphPlacement, A>, Error> {
+        let mut placements = CoreVec::new_in(self.fonts.allocator().clone());
+        let mut pen = (0f32, BASELINE);
+        let mut prev_glyph = None;
+        let mut cluster = 0;
+        for slice in input {
+            self.layout_run(
+                slice.id,
+                slice.size,
+                slice.text.chars(),
+                self.cell_width,
+                &mut pen,
+                &mut prev_glyph,
+                &mut cluster,
+                |_, _, _, placement| placements.push(*placement),
+            )?;
+        }
+        Ok(placements)
+    }
+
+    /// Draws each glyph [`Renderer::draw`] would draw for `input` into its own
+    /// bitmap, for callers redrawing only the glyphs that changed. Drawing the
+    /// bitmaps at their positions reproduces [`Renderer::draw`] where glyphs
+    /// don't overlap. Ruby, the caret and the background aren't drawn
+    /// # Errors
+    /// - See [`Renderer::layout`]
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    pub fn draw_glyphs_separate(
+        &self,
+        input: &[FormattedSlice<'_>],
+    ) -> Result<CoreVec<GlyphBitmap<T, A>, A>, Error> {
+        let mut bitmaps = CoreVec::new_in(self.fonts.allocator().clone());
+        let mut pen = (0f32, BASELINE);
+        let mut prev_glyph = None;
+        let mut cluster = 0;
+        for slice in input {
+            self.layout_run(
+                slice.id,
+                slice.size,
+                slice.text.chars(),
+                self.cell_width,
+                &mut pen,
+                &mut prev_glyph,
+                &mut cluster,
+                |glyph, scale, origin, placement| {
+                    let bitmap =
+                        self.draw_glyph_bitmap(glyph, scale, origin, placement, bitmaps.len());
+                    bitmaps.push(bitmap);
+                },
+            )?;
+        }
+        Ok(bitmaps)
+    }
+
+    /// Draws a glyph laid out by [`Renderer::layout_run`] into a bitmap just
+    /// large enough for it, as the `index`th glyph drawn
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn draw_glyph_bitmap(
+        &self,
+        glyph: &Glyph<A>,
+        scale: (f32, f32),
+        origin: (f32, f32),
+        placement: &GlyphPlacement,
+        index: usize,
+    ) -> GlyphBitmap<T, A> {
+        // Any corner of the bounding box may end up outermost once
+        // transformed. Two pixels are left for debug markers
+        let (x_bounds, y_bounds) = (glyph.x_bounds, glyph.y_bounds);
+        let corners = [
+            (x_bounds.start, y_bounds.start),
+            (x_bounds.start, y_bounds.last),
+            (x_bounds.last, y_bounds.start),
+            (x_bounds.last, y_bounds.last),
+        ]
+        .map(|(x, y)| {
+            apply_transform(
+                &self.transform,
+                (
+                    f32::from(x).mul_add(scale.0, origin.0),
+                    f32::from(y).mul_add(-scale.1, origin.1),
+                ),
+            )
+        });
+        let (x_min, y_min, x_max, y_max) = corners.into_iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(x_min, y_min, x_max, y_max), (x, y)| {
+                (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+            },
+        );
+        let (left, top) = (x_min.floor() - 2.0, y_min.floor() - 2.0);
+        let width = (x_max.ceil() + 2.0 - left).max(1.0) as usize;
+        let height = (y_max.ceil() + 2.0 - top).max(1.0) as usize;
+
+        let mut pixels = CoreVec::new_in(self.fonts.allocator().clone());
+        pixels.resize(width * height, rgb::Gray::new(T::min_value()));
+
+        // Shifted by whole pixels, so coverage matches the full render
+        let shift = [[1.0, 0.0], [0.0, 1.0], [-left, -top]];
+        let mut display = Display {
+            buffer: &mut pixels,
+            width,
+            y_offset: 0,
+            antialias: self.antialias,
+            opacity: 1.0,
+            rounding: self.rounding,
+            transform: then_transform(&self.transform, &shift),
+            clipped: false,
+            extent: (0, 0),
+            ink: None,
+        };
+        let mut report = RenderReport {
+            drawn_glyphs: index,
+            ..RenderReport::default()
+        };
+        self.place_glyph(&mut display, &mut report, glyph, scale, origin);
+
+        GlyphBitmap {
+            placement: *placement,
+            x: left as i32,
+            y: top as i32,
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Draws `ruby` over a base run in font `id` at `size`, spanning `run`
+    /// horizontally
+    /// # Errors
+    /// - See [`Renderer::draw`]
+    fn draw_ruby(
+        &self,
+        display: &mut Display<'_, T>,
+        report: &mut RenderReport,
+        (id, size): (SlotmapKey, u16),
+        ruby: &str,
+        run: core::ops::Range<f32>,
+    ) -> Result<(), Error> {
+        // Centered over the base run, resting on its ascent
+        let ruby_size = (size / 2).max(1);
+        let mut ruby_end = (0.0, 0.0);
+        self.layout_run(
+            id,
+            ruby_size,
+            ruby.chars(),
+            None,
+            &mut ruby_end,
+            &mut None,
+            &mut 0,
+            |_, _, _, _| {},
+        )?;
+
+        // A merged face's metrics are those of its first source
+        let font = self.fonts.get(self.fonts.notdef_font(id));
+        let units_per_em = font.units_per_em().ok_or(Error::MissingTable("head"))?;
+        let units_per_em = f32::from(units_per_em);
+        let ppem_y = f32::from(self.dpi.1) / 6.0;
+        let base_ascent = f32::from(font.ascent()) * f32::from(size) / 12.0 * ppem_y;
+        let ruby_descent = f32::from(font.descent()) * f32::from(ruby_size) / 12.0 * ppem_y;
+        let mut ruby_pen = (
+            run.start + (run.end - run.start - ruby_end.0) / 2.0,
+            BASELINE - (base_ascent - ruby_descent) / units_per_em,
+        );
+        self.layout_run(
+            id,
+            ruby_size,
+            ruby.chars(),
+            None,
+            &mut ruby_pen,
+            &mut None,
+            &mut 0,
+            |glyph, scale, origin, _| {
+                self.place_glyph(display, report, glyph, scale, origin);
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Fills a pixel wide caret with its baseline at `x`, sized and slanted
+    /// by the font of slice `id` at `size`
+    /// # Errors
+    /// - If the font is missing its `head` table
+    fn draw_caret(
+        &self,
+        display: &mut Display<'_, T>,
+        x: f32,
+        id: SlotmapKey,
+        size: u16,
+    ) -> Result<(), Error> {
+        // A merged face's metrics are those of its first source
+        let font = self.fonts.get(self.fonts.notdef_font(id));
+        let units_per_em = font.units_per_em().ok_or(Error::MissingTable("head"))?;
+        let em = f32::from(size) / 12.0 / f32::from(units_per_em);
+        let scale = (
+            em * f32::from(self.dpi.0) / 6.0,
+            em * f32::from(self.dpi.1) / 6.0,
+        );
+
+        // Horizontal carets are for vertical text, so are drawn upright
+        let (rise, run) = font.caret_slope();
+        let slant = if rise == 0 {
+            0.0
+        } else {
+            f32::from(run) / f32::from(rise)
+        };
+        let offset = f32::from(font.caret_offset());
+
+        let transform = display.transform;
+        let corner = |y: i16, side: f32| {
+            let y = f32::from(y);
+            apply_transform(
+                &transform,
+                (
+                    y.mul_add(slant, offset).mul_add(scale.0, x) + side,
+                    y.mul_add(-scale.1, BASELINE),
+                ),
+            )
+        };
+        let (ascent, descent) = (font.ascent(), font.descent());
+        let corners = [
+            corner(descent, -0.5),
+            corner(descent, 0.5),
+            corner(ascent, 0.5),
+            corner(ascent, -0.5),
+        ];
+
+        display.opacity = 1.0;
+        shapes::fill_glyph(
+            display,
+            self.draw_mode,
+            self.fonts.allocator().clone(),
+            corners.into_iter().circular_tuple_windows(),
+        );
+        Ok(())
+    }
+
+    /// Lays out `chars` from `pen`, advancing it past the run, and hands each
+    /// glyph to `place` with its scale, origin and placement. `prev_glyph`
+    /// carries the last glyph between runs for kerning, and `cluster` the
+    /// index of the next character
+    /// # Errors
+    /// - See [`Renderer::draw`]
+    #[allow(clippy::too_many_arguments)]
+    fn layout_run(
+        &self,
+        id: SlotmapKey,
+        size: u16,
+        chars: impl Iterator<Item = char>,
+        cell_width: Option<f32>,
+        pen: &mut (f32, f32),
+        prev_glyph: &mut Option<(SlotmapKey, u32)>,
+        cluster: &mut usize,
+        mut place: impl FnMut(&Glyph<A>, (f32, f32), (f32, f32), &GlyphPlacement),
+    ) -> Result<(), Error> {
+        let ppem = (f32::from(self.dpi.0) / 6.0, f32::from(self.dpi.1) / 6.0);
+
+        for c in chars {
+            // Unmapped characters draw the primary font's `.notdef`
+            let (key, glyph_id) = self
+                .fonts
+                .resolve_glyph(id, c)
+                .unwrap_or_else(|| (self.fonts.notdef_font(id), 0));
+            let font = self.fonts.get(key);
+            let units_per_em = font.units_per_em().ok_or(Error::MissingTable("head"))?;
+            let glyph = font.glyph(glyph_id).ok_or(Error::GlyphNotFound(glyph_id))?;
+
+            let em = f32::from(size) / 12.0 / f32::from(units_per_em);
+            let scale = (em * ppem.0, em * ppem.1);
+
+            let kern = match *prev_glyph {
+                Some((prev_key, prev_id))
+                    if self.kerning && cell_width.is_none() && prev_key == key =>
+                {
+                    font.kern_pair(prev_id, glyph_id)
+                },
+                _ => 0,
+            };
+            let kern = f32::from(kern) * scale.0;
+            pen.0 += kern;
+            *prev_glyph = Some((key, glyph_id));
+
+            let metrics = font.tables().iter().find_map(|t| match t {
+                Table::Hmtx(hmtx) => hmtx.get(font.metrics_glyph_id(glyph_id) as usize),
+                _ => None,
+            });
+            // Outlines are positioned by their bounding box, which may not
+            // sit at the left side bearing
+            let bearing = metrics.map_or(0.0, |m| {
+                f32::from(m.left_side_bearing) - f32::from(glyph.x_bounds.start)
+            });
+            let advance = metrics.map_or(0.0, |m| f32::from(m.advance)) * scale.0;
+
+            // Within a fixed grid, the glyph is centered in its cells
+            let (x, cell_advance) = cell_width.map_or((pen.0, advance), |cell_width| {
+                let cells = if is_wide(c) { 2.0 } else { 1.0 };
+                let cell_advance = cells * cell_width;
+                (pen.0 + (cell_advance - advance) / 2.0, cell_advance)
+            });
+
+            let placement = GlyphPlacement {
+                font: key,
+                glyph_id,
+                cluster: *cluster,
+                x: pen.0,
+                y: pen.1,
+                advance: cell_advance,
+                kern,
+                ascent: f32::from(font.ascent()) * scale.1,
+                descent: f32::from(font.descent()) * scale.1,
+            };
+            place(
+                glyph,
+                scale,
+                (bearing.mul_add(scale.0, x), pen.1),
+                &placement,
+            );
+            pen.0 += cell_advance;
+            *cluster += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Draws a glyph laid out by [`Renderer::layout_run`], counting it in
+    /// `report`
+    fn place_glyph(
+        &self,
+        display: &mut Display<'_, T>,
+        report: &mut RenderReport,
+        glyph: &Glyph<A>,
+        scale: (f32, f32),
+        origin: (f32, f32),
+    ) {
+        display.clipped = false;
+        display.opacity = self
+            .opacity
+            .get(report.drawn_glyphs)
+            .map_or(1.0, |opacity| opacity.clamp(0.0, 1.0));
+        // Fully transparent glyphs would still overwrite what's beneath
+        if display.opacity > 0.0 {
+            draw_glyph(display, glyph, scale, origin, self.draw_mode, self.mode);
+        }
+
+        report.drawn_glyphs += 1;
+        if display.clipped {
+            report.clipped_glyphs += 1;
+        }
+    }
+
+    /// Renders the coverage of `input` on its own, at `factor` times the
+    /// horizontal resolution, for the colour outputs to resolve into their
+    /// buffers
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn draw_coverage(
+        &self,
+        input: &[FormattedSlice<'_>],
+        width: usize,
+        height: usize,
+        factor: usize,
+    ) -> Result<(CoreVec<rgb::Gray<T>, A>, RenderReport), Error> {
+        let mut coverage = CoreVec::new_in(self.fonts.allocator().clone());
+        coverage.resize(width * factor * height, rgb::Gray::new(T::min_value()));
+
+        // The transform is in pixels, so is conjugated into the stretched
+        // coverage's coordinates
+        let factor_f = factor as f32;
+        let [[xx, xy], [yx, yy], [tx, ty]] = self.transform;
+        let mut report = Self {
+            dpi: (self.dpi.0.saturating_mul(factor as u16), self.dpi.1),
+            cell_width: self.cell_width.map(|cell_width| cell_width * factor_f),
+            transform: [[xx, xy / factor_f], [yx * factor_f, yy], [
+                tx * factor_f,
+                ty,
+            ]],
+            draw_mode: DrawMode::Overwrite,
+            background: None,
+            clear: false,
+            ..*self
+        }
+        .draw(input, &mut coverage, width * factor)?;
+        report.used_width = report.used_width.div_ceil(factor);
+
+        Ok((coverage, report))
+    }
+
+    /// Draws [`Renderer::color`] text into an RGBA buffer, with coverage as
+    /// alpha. Colour channels hold the colour as is, or multiplied by coverage
+    /// with [`Renderer::premultiply`]. A [`Renderer::background`] fills the
+    /// buffer opaque, otherwise it's cleared to transparent
+    /// # Errors
+    /// - See [`Renderer::draw`]
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    pub fn draw_rgba(
+        &self,
+        input: &[FormattedSlice<'_>],
+        buffer: &mut [rgb::RGBA<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        if width == 0 || buffer.len() < width {
+            return Err(Error::BufferTooSmall {
+                needed: width.max(1),
+                got:    buffer.len(),
+            });
+        }
+        let height = buffer.len() / width;
+
+        let (coverage, report) = self.draw_coverage(input, width, height, 1)?;
+
+        let max = T::max_value();
+        let color = self.color.unwrap_or_else(|| rgb::RGB::new(max, max, max));
+        let scale = |channel: T, alpha: T| {
+            let scaled = channel.to_f32().unwrap_or_default() * alpha.to_f32().unwrap_or_default()
+                / max.to_f32().expect("overflow on T to f32");
+            T::from(scaled.round()).unwrap_or(max)
+        };
+
+        let clear = self.clear_value().map(|clear| {
+            let alpha = if self.background.is_some() {
+                max
+            } else {
+                clear
+            };
+            rgb::RGBA::new(clear, clear, clear, alpha)
+        });
+
+        for (pixel, alpha) in buffer.iter_mut().zip(coverage.iter()).take(width * height) {
+            if let Some(clear) = clear {
+                *pixel = clear;
+            }
+
+            let alpha = alpha.0;
+            if alpha.is_zero() {
+                continue;
+            }
+
+            let src = if self.premultiply {
+                rgb::RGB::new(
+                    scale(color.r, alpha),
+                    scale(color.g, alpha),
+                    scale(color.b, alpha),
+                )
+            } else {
+                color
+            };
+            *pixel = rgb::RGBA::new(
+                self.draw_mode.apply(pixel.r, src.r),
+                self.draw_mode.apply(pixel.g, src.g),
+                self.draw_mode.apply(pixel.b, src.b),
+                self.draw_mode.apply(pixel.a, alpha),
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Draws into an RGB buffer, with each channel covering its subpixel as
+    /// laid out by [`Renderer::subpixel`]. Subpixel layouts rasterize at three
+    /// times the horizontal resolution before filtering to reduce colour
+    /// fringing
+    /// # Errors
+    /// - See [`Renderer::draw`]
+    /// # Panics
+    /// - See [`Renderer::draw`]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn draw_rgb(
+        &self,
+        input: &[FormattedSlice<'_>],
+        buffer: &mut [rgb::RGB<T>],
+        width: usize,
+    ) -> Result<RenderReport, Error> {
+        if width == 0 || buffer.len() < width {
+            return Err(Error::BufferTooSmall {
+                needed: width.max(1),
+                got:    buffer.len(),
+            });
+        }
+        let height = buffer.len() / width;
+
+        let factor = match self.subpixel {
+            SubPixelAlignment::Rgb | SubPixelAlignment::Bgr => 3,
+            SubPixelAlignment::None => 1,
+        };
+        let (coverage, mut report) = self.draw_coverage(input, width, height, factor)?;
+        report.ink_bounds = None;
+
+        let clear = self.clear_value();
+
+        let rows = buffer
+            .chunks_exact_mut(width)
+            .zip(coverage.chunks_exact(width * factor));
+        for (y, (row, subpixels)) in rows.enumerate() {
+            let sample = |idx: Option<usize>| {
+                idx.and_then(|idx| subpixels.get(idx))
+                    .and_then(|p| p.0.to_f32())
+                    .unwrap_or_default()
+            };
+            let filtered = |idx: usize| {
+                let value = if factor == 1 {
+                    sample(Some(idx))
+                } else {
+                    LCD_FILTER[0].mul_add(
+                        sample(idx.checked_sub(1)),
+                        LCD_FILTER[1]
+                            .mul_add(sample(Some(idx)), LCD_FILTER[2] * sample(Some(idx + 1))),
+                    ) / 256.0
+                };
+                T::from(value.round()).unwrap_or_else(T::max_value)
+            };
+
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if let Some(clear) = clear {
+                    *pixel = rgb::RGB::new(clear, clear, clear);
+                }
+
+                let (left, middle, right) = (
+                    filtered(x * factor),
+                    filtered(x * factor + factor / 2),
+                    filtered(x * factor + factor - 1),
+                );
+                if left.is_zero() && middle.is_zero() && right.is_zero() {
+                    continue;
+                }
+                InkBounds::include(&mut report.ink_bounds, x, y);
+
+                let (r, g, b) = match self.subpixel {
+                    SubPixelAlignment::Bgr => (right, middle, left),
+                    SubPixelAlignment::Rgb | SubPixelAlignment::None => (left, middle, right),
+                };
+                *pixel = rgb::RGB::new(
+                    self.draw_mode.apply(pixel.r, r),
+                    self.draw_mode.apply(pixel.g, g),
+                    self.draw_mode.apply(pixel.b, b),
+                );
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Positional shorthand for [`Renderer::draw`], kept from before the builder
 /// # Errors
+/// - See [`Renderer::draw`]
 /// # Panics
-#[allow(clippy::cast_possible_truncation)]
-pub fn to_buf<A: core::alloc::Allocator + core::fmt::Debug, T: PrimInt>(
+/// - See [`Renderer::draw`]
+pub fn to_buf<A: core::alloc::Allocator + Clone + core::fmt::Debug, T: PrimInt>(
     fonts: &FontCollection<A>,
     input: &[FormattedSlice<'_>],
     buffer: &mut [rgb::Gray<T>],
     width: usize,
     dpi: u16,
     subpixel: SubPixelAlignment,
-) -> Result<(), Error> {
-    let _height = buffer.len() / width;
+) -> Result<RenderReport, Error> {
+    Renderer::new(fonts)
+        .dpi(dpi)
+        .subpixel(subpixel)
+        .draw(input, buffer, width)
+}
 
-    for v in buffer.iter_mut() {
-        *v = rgb::Gray::new(T::min_value());
-    }
+/// Positional shorthand for [`Renderer::draw_chars`]
+/// # Errors
+/// - See [`Renderer::draw_chars`]
+/// # Panics
+/// - See [`Renderer::draw_chars`]
+#[allow(clippy::too_many_arguments)]
+pub fn render_chars<A: core::alloc::Allocator + Clone + core::fmt::Debug, T: PrimInt>(
+    fonts: &FontCollection<A>,
+    id: SlotmapKey,
+    size: u16,
+    chars: impl Iterator<Item = char>,
+    buffer: &mut [rgb::Gray<T>],
+    width: usize,
+    dpi: u16,
+    subpixel: SubPixelAlignment,
+) -> Result<RenderReport, Error> {
+    Renderer::new(fonts)
+        .dpi(dpi)
+        .subpixel(subpixel)
+        .draw_chars(id, size, chars, buffer, width)
+}
+
+/// Positional shorthand for [`Renderer::draw_on_path`]
+/// # Errors
+/// - See [`Renderer::draw_on_path`]
+/// # Panics
+/// - See [`Renderer::draw_on_path`]
+#[allow(clippy::too_many_arguments)]
+pub fn render_on_path<A: core::alloc::Allocator + Clone + core::fmt::Debug, T: PrimInt>(
+    fonts: &FontCollection<A>,
+    input: &[FormattedSlice<'_>],
+    path: &[(f32, f32)],
+    buffer: &mut [rgb::Gray<T>],
+    width: usize,
+    dpi: u16,
+    subpixel: SubPixelAlignment,
+) -> Result<RenderReport, Error> {
+    Renderer::new(fonts)
+        .dpi(dpi)
+        .subpixel(subpixel)
+        .draw_on_path(input, path, buffer, width)
+}
 
-    let mut display = Display {
+/// Positional shorthand for [`Renderer::draw_glyphs_separate`]
+/// # Errors
+/// - See [`Renderer::draw_glyphs_separate`]
+/// # Panics
+/// - See [`Renderer::draw_glyphs_separate`]
+pub fn render_glyphs_separate<A: core::alloc::Allocator + Clone + core::fmt::Debug, T: PrimInt>(
+    fonts: &FontCollection<A>,
+    input: &[FormattedSlice<'_>],
+    dpi: u16,
+) -> Result<CoreVec<GlyphBitmap<T, A>, A>, Error> {
+    Renderer::new(fonts).dpi(dpi).draw_glyphs_separate(input)
+}
+
+/// Positional shorthand for [`Renderer::draw_rgb`]
+/// # Errors
+/// - See [`Renderer::draw_rgb`]
+/// # Panics
+/// - See [`Renderer::draw_rgb`]
+pub fn to_buf_rgb<A: core::alloc::Allocator + Clone + core::fmt::Debug, T: PrimInt>(
+    fonts: &FontCollection<A>,
+    input: &[FormattedSlice<'_>],
+    buffer: &mut [rgb::RGB<T>],
+    width: usize,
+    dpi: u16,
+    subpixel: SubPixelAlignment,
+) -> Result<RenderReport, Error> {
+    Renderer::new(fonts)
+        .dpi(dpi)
+        .subpixel(subpixel)
+        .draw_rgb(input, buffer, width)
+}
+
+/// Positional shorthand for [`Renderer::draw_band`]
+/// # Errors
+/// - See [`Renderer::draw_band`]
+/// # Panics
+/// - See [`Renderer::draw_band`]
+#[allow(clippy::too_many_arguments)]
+pub fn to_band<A: core::alloc::Allocator + Clone + core::fmt::Debug, T: PrimInt>(
+    fonts: &FontCollection<A>,
+    input: &[FormattedSlice<'_>],
+    y_start: usize,
+    band_height: usize,
+    buffer: &mut [rgb::Gray<T>],
+    width: usize,
+    dpi: u16,
+    subpixel: SubPixelAlignment,
+) -> Result<RenderReport, Error> {
+    Renderer::new(fonts).dpi(dpi).subpixel(subpixel).draw_band(
+        input,
+        y_start,
+        band_height,
         buffer,
         width,
-        dpi,
-        subpixel,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+        cmap,
+        cmap4,
+        font,
+        font_with,
+        hhea,
+        hmtx,
+        kern,
+        maxp,
+        rect,
+        sfnt,
+        simple_glyph,
+        square_font,
     };
 
-    //shapes::draw_line_aliased(&mut display, DrawMode::Overwrite, (0, 0), (99,
-    // 99));
-    //shapes::draw_line(&mut display, (0.0, 0.0), (200.0, 49.0), 5.0);
+    /// Collection holding [`square_font`], with its key
+    fn square_collection() -> (FontCollection, SlotmapKey) {
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let font = crate::parse_font_bytes(&square_font()).expect("Font should parse");
+        let id = fonts.add_loaded(font);
+        (fonts, id)
+    }
 
-    let font = fonts.get(input[0].id);
-    let glyph = font.glyph(1).expect("e");
+    /// `text` in font `id` at 24pt
+    fn input(
+        id: SlotmapKey,
+        text: &str,
+    ) -> [FormattedSlice<'_>; 1] {
+        [FormattedSlice {
+            id,
+            size: 24,
+            text,
+            ruby: None,
+        }]
+    }
 
-    let units_per_em = f32::from(font.units_per_em());
-    let ppem = f32::from(dpi) / 6.0;
+    #[test]
+    fn uncovered_characters_come_from_the_fallback() {
+        let (mut fonts, latin) = square_collection();
+        let cjk = font(
+            &[
+                (Vec::new(), 1000),
+                (simple_glyph(&[&rect(50, -100, 950, 800)]), 1000),
+                (simple_glyph(&[&rect(100, -50, 900, 750)]), 1000),
+            ],
+            &[('中', 1), ('文', 2)],
+        );
+        let cjk = fonts.add_loaded(crate::parse_font_bytes(&cjk).expect("Font should parse"));
+        fonts.set_fallback(cjk);
 
-    for slice in input {
-        let scale = (f32::from(slice.size) / 12.0 * ppem) / units_per_em;
-        //println!("{}pt {units_per_em} {ppem}", slice.size);
+        let glyphs = |fonts: &FontCollection| {
+            Renderer::<_, u8>::new(fonts)
+                .layout(&input(latin, "A中文A"))
+                .expect("Layout should succeed")
+                .iter()
+                .map(|placement| (placement.font, placement.glyph_id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(glyphs(&fonts), [(latin, 1), (cjk, 1), (cjk, 2), (latin, 1)]);
 
-        let mut prev_x = 0;
-        let mut prev_y = 0;
+        // Without the fallback they're the primary font's `.notdef`
+        fonts.remove(cjk);
+        assert_eq!(fonts.fallback(), None);
+        assert_eq!(glyphs(&fonts), [
+            (latin, 1),
+            (latin, 0),
+            (latin, 0),
+            (latin, 1)
+        ]);
+    }
 
-        let mut prev_end = 0;
-        //println!("{:?}", glyph.end_pts);
-        for end in &glyph.end_pts {
-            let start_x = prev_x;
-            let start_y = prev_y;
+    #[test]
+    fn ink_bounds_of_a_period_are_within_its_layout_box() {
+        let period = font(
+            &[
+                (Vec::new(), 500),
+                (simple_glyph(&[&rect(100, 0, 200, 100)]), 300),
+            ],
+            &[('.', 1)],
+        );
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let id = fonts.add_loaded(crate::parse_font_bytes(&period).expect("Font should parse"));
+        let renderer = Renderer::new(&fonts);
 
-            for (start, end) in (prev_end..=*end).circular_tuple_windows() {
-                let (x1, y1, _on_curve1) = glyph.points[start as usize];
-                let (mut x2, mut y2, _on_curve2) = glyph.points[end as usize];
+        let placements = renderer
+            .layout(&input(id, "."))
+            .expect("Layout should succeed");
+        let placement = placements[0];
+        let (box_width, box_height) = (placement.advance, placement.ascent - placement.descent);
 
-                //println!("{start}: ({x1}, {y1})");
-                prev_x += x1;
-                prev_y += y1;
+        let mut buffer = vec![rgb::Gray::new(0u8); 64 * 64];
+        let ink = renderer
+            .draw(&input(id, "."), &mut buffer, 64)
+            .expect("Render should succeed")
+            .ink_bounds
+            .expect("Period should be drawn");
 
-                if end == prev_end {
-                    x2 += start_x;
-                    y2 += start_y;
-                } else {
-                    x2 += prev_x;
-                    y2 += prev_y;
-                }
+        // A third of the advance and a tenth of the line box, give or take the
+        // antialiased edges
+        let (ink_width, ink_height) = (ink.width as f32, ink.height as f32);
+        assert!(ink_width <= box_width / 2.0, "{ink:?} in {box_width}");
+        assert!(ink_height <= box_height / 5.0, "{ink:?} in {box_height}");
+        // Sitting on the baseline
+        assert!(
+            ((ink.y + ink.height) as f32 - BASELINE).abs() <= 1.0,
+            "{ink:?}"
+        );
+    }
 
-                //println!("{start} {end}: ({prev_x}, {prev_y}) -> ({x2}, {y2})");
+    #[test]
+    fn bands_concatenate_into_the_full_render() {
+        let (fonts, id) = square_collection();
+        let renderer = Renderer::new(&fonts);
+        let (width, height) = (96, 64);
+        let tall = [FormattedSlice {
+            id,
+            size: 72,
+            text: "AA",
+            ruby: None,
+        }];
 
-                let start = (
-                    (f32::from(prev_x) * scale) as i32,
-                    f32::from(prev_y).mul_add(-scale, 40.0) as i32,
-                );
-                let end = (
-                    (f32::from(x2) * scale) as i32,
-                    f32::from(y2).mul_add(-scale, 40.0) as i32,
-                );
+        let mut full = vec![rgb::Gray::new(0u8); width * height];
+        renderer
+            .draw(&tall, &mut full, width)
+            .expect("Render should succeed");
+
+        // Split through the middle of the glyphs
+        let split = 37;
+        let mut bands = vec![rgb::Gray::new(0u8); width * height];
+        let (top, bottom) = bands.split_at_mut(width * split);
+        renderer
+            .draw_band(&tall, 0, split, top, width)
+            .expect("Top band should render");
+        renderer
+            .draw_band(&tall, split, height - split, bottom, width)
+            .expect("Bottom band should render");
+
+        assert!(top.iter().any(|pixel| pixel.0 != 0));
+        assert!(bottom.iter().any(|pixel| pixel.0 != 0));
+        assert!(bands == full);
+    }
+
+    #[test]
+    fn cell_width_advances_narrow_and_wide_glyphs_alike() {
+        let mono = font(
+            &[
+                (Vec::new(), 500),
+                (simple_glyph(&[&rect(100, 0, 150, 500)]), 250),
+                (simple_glyph(&[&rect(50, 0, 850, 700)]), 900),
+                (simple_glyph(&[&rect(50, -100, 950, 800)]), 1000),
+            ],
+            &[('i', 1), ('W', 2), ('中', 3)],
+        );
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let id = fonts.add_loaded(crate::parse_font_bytes(&mono).expect("Font should parse"));
+        let renderer = Renderer::new(&fonts).cell_width(20.0);
+
+        let placements = |renderer: &Renderer<'_, _, u8>| {
+            renderer
+                .layout(&input(id, "iW中i"))
+                .expect("Layout should succeed")
+                .iter()
+                .map(|placement| (placement.x, placement.advance))
+                .collect::<Vec<_>>()
+        };
+        // CJK takes two cells
+        assert_eq!(placements(&renderer), [
+            (0.0, 20.0),
+            (20.0, 20.0),
+            (40.0, 40.0),
+            (80.0, 20.0)
+        ]);
+        let proportional = placements(&Renderer::new(&fonts));
+        assert!(proportional[0].1 < proportional[1].1);
+
+        // Both sit in the middle of their cell
+        let centre = |text| {
+            let mut buffer = vec![rgb::Gray::new(0u8); 64 * 64];
+            let ink = renderer
+                .draw(&input(id, text), &mut buffer, 64)
+                .expect("Render should succeed")
+                .ink_bounds
+                .expect("Glyph should be drawn");
+            (ink.x * 2 + ink.width) as f32 / 2.0
+        };
+        assert!((centre("i") - 10.0).abs() <= 1.0, "{}", centre("i"));
+        assert!((centre("W") - 10.0).abs() <= 1.0, "{}", centre("W"));
+    }
+
+    #[test]
+    fn premultiplied_colour_never_exceeds_alpha() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+        let render = |premultiply| {
+            let mut buffer = vec![rgb::RGBA::new(0u8, 0, 0, 0); width * 64];
+            Renderer::new(&fonts)
+                .color(rgb::RGB::new(255, 128, 32))
+                .premultiply(premultiply)
+                .draw_rgba(&input(id, "A"), &mut buffer, width)
+                .expect("Render should succeed");
+            buffer
+        };
+
+        let premultiplied = render(true);
+        assert!(premultiplied.iter().any(|pixel| pixel.a == u8::MAX));
+        // Antialiased edges are where the colour gets scaled
+        assert!(premultiplied
+            .iter()
+            .any(|pixel| pixel.a > 0 && pixel.a < u8::MAX));
+        for pixel in &premultiplied {
+            assert!(
+                pixel.r <= pixel.a && pixel.g <= pixel.a && pixel.b <= pixel.a,
+                "{pixel:?}"
+            );
+        }
+
+        // Straight alpha keeps the colour at faint edges
+        let straight = render(false);
+        assert!(straight
+            .iter()
+            .any(|pixel| pixel.a > 0 && pixel.g > pixel.a));
+    }
+
+    #[test]
+    fn rotated_text_is_taller_than_wide() {
+        let (fonts, id) = square_collection();
+        let (width, height) = (96, 96);
+
+        let ink = |transform| {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * height];
+            Renderer::new(&fonts)
+                .transform(transform)
+                .draw(&input(id, "AAAA"), &mut buffer, width)
+                .expect("Render should succeed")
+                .ink_bounds
+                .expect("Text should be drawn")
+        };
+
+        let level = ink(IDENTITY);
+        assert!(level.width > level.height * 3, "{level:?}");
+
+        #[allow(clippy::cast_precision_loss)]
+        let rotated = ink([[0.0, -1.0], [1.0, 0.0], [0.0, height as f32]]);
+        assert!(rotated.height > rotated.width * 3, "{rotated:?}");
+        assert!(rotated.height.abs_diff(level.width) <= 1);
+        assert!(rotated.width.abs_diff(level.height) <= 1);
+    }
+
+    #[test]
+    fn merged_faces_draw_from_each_source() {
+        let (mut fonts, latin) = square_collection();
+        let symbols = font(
+            &[
+                (Vec::new(), 500),
+                (
+                    simple_glyph(&[&[(0, 0, true), (400, 0, true), (200, 600, true)]]),
+                    500,
+                ),
+                (simple_glyph(&[&rect(0, 0, 50, 50)]), 100),
+            ],
+            // 'A' is also in the Latin font, which is merged first
+            &[('★', 1), ('A', 2)],
+        );
+        let symbols =
+            fonts.add_loaded(crate::parse_font_bytes(&symbols).expect("Font should parse"));
+        let merged = fonts.merge(&[latin, symbols]);
+
+        let renderer = Renderer::new(&fonts);
+        let text = input(merged, "A★A?");
+        let glyphs: Vec<_> = renderer
+            .layout(&text)
+            .expect("Layout should succeed")
+            .iter()
+            .map(|placement| (placement.font, placement.glyph_id))
+            .collect();
+        assert_eq!(glyphs, [
+            (latin, 1),
+            (symbols, 1),
+            (latin, 1),
+            // Covered by neither, so the first source's `.notdef`
+            (latin, 0)
+        ]);
 
-                //println!("{start:?} {end:?}");
+        let mut buffer = vec![rgb::Gray::new(0u8); 96 * 64];
+        let report = renderer
+            .draw(&text, &mut buffer, 96)
+            .expect("Render should succeed");
+        assert_eq!(report.drawn_glyphs, 4);
+        // The triangle rises above the squares
+        let ink = report.ink_bounds.expect("Text should be drawn");
+        let square = renderer
+            .draw(&input(latin, "A"), &mut buffer, 96)
+            .expect("Render should succeed")
+            .ink_bounds
+            .expect("Square should be drawn");
+        assert!(ink.height > square.height, "{ink:?} {square:?}");
+    }
+
+    #[test]
+    fn ruby_sits_above_and_smaller_than_its_base() {
+        let kana = font(
+            &[
+                (Vec::new(), 500),
+                (simple_glyph(&[&rect(50, 0, 950, 800)]), 1000),
+                (simple_glyph(&[&rect(100, 0, 900, 700)]), 1000),
+            ],
+            &[('漢', 1), ('か', 2)],
+        );
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let id = fonts.add_loaded(crate::parse_font_bytes(&kana).expect("Font should parse"));
+        let renderer = Renderer::new(&fonts);
+        let width = 64;
+
+        let draw = |ruby| {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+            let report = renderer
+                .draw(
+                    &[FormattedSlice {
+                        id,
+                        size: 24,
+                        text: "漢",
+                        ruby,
+                    }],
+                    &mut buffer,
+                    width,
+                )
+                .expect("Render should succeed");
+            (buffer, report)
+        };
+        let (plain, plain_report) = draw(None);
+        let (annotated, annotated_report) = draw(Some("か"));
+        let base = plain_report.ink_bounds.expect("Base should be drawn");
+        assert_eq!(annotated_report.drawn_glyphs, 2);
+
+        // Whatever the ruby added to the plain render
+        let mut ruby = None;
+        for (idx, (plain, annotated)) in plain.iter().zip(&annotated).enumerate() {
+            if plain.0 != annotated.0 {
+                InkBounds::include(&mut ruby, idx % width, idx / width);
+            }
+        }
+        let ruby = ruby.expect("Ruby should be drawn");
+
+        assert!(ruby.y + ruby.height <= base.y, "{ruby:?} over {base:?}");
+        assert!(ruby.width < base.width && ruby.height < base.height);
+        // Centred over the base
+        let centre = |bounds: InkBounds| bounds.x * 2 + bounds.width;
+        assert!(
+            centre(ruby).abs_diff(centre(base)) <= 2,
+            "{ruby:?} over {base:?}"
+        );
+    }
+
+    #[test]
+    fn caret_is_a_vertical_line_between_glyphs() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+        let draw = |renderer: Renderer<'_, _, u8>| {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+            renderer
+                .draw(&input(id, "AAA"), &mut buffer, width)
+                .expect("Render should succeed");
+            buffer
+        };
+        let plain = draw(Renderer::new(&fonts));
+        let with_caret = draw(Renderer::new(&fonts).caret(2));
+
+        // Whatever the caret added to the plain render
+        let mut caret = None;
+        for (idx, (plain, with_caret)) in plain.iter().zip(&with_caret).enumerate() {
+            if plain.0 != with_caret.0 {
+                InkBounds::include(&mut caret, idx % width, idx / width);
+            }
+        }
+        let caret = caret.expect("Caret should be drawn");
 
-                shapes::draw_line_aliased(&mut display, DrawMode::Overwrite, start, end);
+        // After two 14.4 pixel advances, in the gap before the third square
+        assert!(
+            (28..=29).contains(&caret.x) && caret.width <= 2,
+            "{caret:?}"
+        );
+        // From the ascent at 19.2 above the baseline to the descent 4.8 below
+        assert!(caret.height >= 23, "{caret:?}");
+        let column = caret.x;
+        let solid = (caret.y..caret.y + caret.height)
+            .filter(|y| with_caret[y * width + column].0 > u8::MAX / 2)
+            .count();
+        assert!(solid + 2 >= caret.height, "{solid} of {caret:?}");
+    }
+
+    #[test]
+    fn fractional_advances_do_not_drift() {
+        let (fonts, id) = square_collection();
+        let text = "A".repeat(40);
+        let renderer = Renderer::new(&fonts);
+
+        // 600 units at 24 pixels per 1000 is 14.4 pixels
+        let placements = renderer
+            .layout(&input(id, &text))
+            .expect("Layout should succeed");
+        let last = placements.last().expect("Text should be laid out");
+        assert!((last.x - 39.0 * 14.4).abs() < 0.01, "{}", last.x);
+
+        let width = 600;
+        let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+        let ink = renderer
+            .draw(&input(id, &text), &mut buffer, width)
+            .expect("Render should succeed")
+            .ink_bounds
+            .expect("Text should be drawn");
+        // The last square ends 12 pixels into its advance, at 573.6
+        assert_eq!(ink.x + ink.width, 574, "{ink:?}");
+        // Rather than snapping to a pixel, the second square's edge at 16.8
+        // covers a fifth of column 16
+        let row = 35 * width;
+        assert!(
+            (1..u8::MAX / 2).contains(&buffer[row + 16].0),
+            "{:?}",
+            buffer[row + 16]
+        );
+        assert_eq!(buffer[row + 17].0, u8::MAX);
+    }
+
+    #[test]
+    fn multiply_scales_by_coverage_without_overflowing() {
+        assert_eq!(DrawMode::Multiply.apply(200u8, 255), 200);
+        assert_eq!(DrawMode::Multiply.apply(200u8, 0), 0);
+        assert_eq!(DrawMode::Multiply.apply(255u8, 128), 128);
+        assert_eq!(DrawMode::Multiply.apply(u16::MAX, u16::MAX), u16::MAX);
+        assert_eq!(DrawMode::Multiply.apply(u64::MAX, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn background_is_kept_around_text() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+
+        for draw_mode in [DrawMode::Overwrite, DrawMode::Multiply, DrawMode::Add] {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+            Renderer::new(&fonts)
+                .background(128)
+                .draw_mode(draw_mode)
+                .draw(&input(id, "A"), &mut buffer, width)
+                .expect("Render should succeed");
+
+            let expected = draw_mode.apply(128, u8::MAX);
+            let ink = buffer.iter().filter(|pixel| pixel.0 != 128).count();
+            assert!(buffer.contains(&rgb::Gray::new(expected)), "{draw_mode:?}");
+            assert_eq!(buffer[0].0, 128, "{draw_mode:?}");
+            assert_eq!(buffer[width * 64 - 1].0, 128, "{draw_mode:?}");
+            if !matches!(draw_mode, DrawMode::Multiply) {
+                assert_ne!(ink, 0, "{draw_mode:?}");
             }
-            prev_end = *end + 1;
         }
     }
 
-    Ok(())
+    #[test]
+    fn anamorphic_dpi_stretches_glyphs() {
+        let (fonts, id) = square_collection();
+        let width = 128;
+
+        let ink = |dpi: (u16, u16)| {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+            Renderer::new(&fonts)
+                .dpi_xy(dpi.0, dpi.1)
+                .draw(&input(id, "A"), &mut buffer, width)
+                .expect("Render should succeed")
+                .ink_bounds
+                .expect("Glyph should be drawn")
+        };
+
+        let square = ink((72, 72));
+        let stretched = ink((144, 72));
+        assert_eq!(stretched.height, square.height);
+        assert!(stretched.width.abs_diff(square.width * 2) <= 1);
+    }
+
+    #[test]
+    fn outline_mode_strokes_without_filling() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+
+        let render = |mode: RenderMode| {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+            let bounds = Renderer::new(&fonts)
+                .mode(mode)
+                .draw(&input(id, "A"), &mut buffer, width)
+                .expect("Render should succeed")
+                .ink_bounds
+                .expect("Glyph should be drawn");
+            (buffer, bounds)
+        };
+
+        let (filled, square) = render(RenderMode::Standard);
+        let (outlined, outline) = render(RenderMode::Outline);
+        assert!(outline.width.abs_diff(square.width) <= 2);
+        assert!(outline.height.abs_diff(square.height) <= 2);
+
+        let centre = (square.y + square.height / 2) * width + square.x + square.width / 2;
+        assert_eq!(filled[centre].0, u8::MAX);
+        assert_eq!(outlined[centre].0, 0);
+
+        let ink = |buffer: &[rgb::Gray<u8>]| buffer.iter().filter(|pixel| pixel.0 != 0).count();
+        assert!(ink(&outlined) < ink(&filled));
+    }
+
+    #[test]
+    fn debug_outline_marks_off_curve_points_at_half_value() {
+        let glyph = simple_glyph(&[&[(100, 0, true), (500, 0, true), (300, 600, false)]]);
+        let bytes = font(&[(Vec::new(), 500), (glyph, 600)], &[('A', 1)]);
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let id = fonts.add_loaded(crate::parse_font_bytes(&bytes).expect("Font should parse"));
+
+        let width = 64;
+        let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+        Renderer::new(&fonts)
+            .mode(RenderMode::DebugOutline)
+            .draw(&input(id, "A"), &mut buffer, width)
+            .expect("Render should succeed");
+
+        // The off-curve apex is marked by a 3x3 square drawn over the outline
+        let half: Vec<_> = (0..buffer.len())
+            .filter(|&i| buffer[i].0 == u8::MAX / 2)
+            .map(|i| (i % width, i / width))
+            .collect();
+        assert_eq!(half.len(), 9, "{half:?}");
+        let (x, y) = half[4];
+        assert!(half
+            .iter()
+            .all(|&(hx, hy)| hx.abs_diff(x) <= 1 && hy.abs_diff(y) <= 1));
+        // Above the on-curve corners along the baseline
+        let lowest_full = (0..buffer.len())
+            .filter(|&i| buffer[i].0 == u8::MAX)
+            .map(|i| i / width)
+            .max()
+            .expect("On-curve points should be marked");
+        assert!(y + 4 < lowest_full);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn opacity_fades_each_glyph() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+        let input = input(id, "AAA");
+
+        let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+        let renderer = Renderer::new(&fonts).opacity(&[1.0, 0.5, 0.0]);
+        let report = renderer
+            .draw(&input, &mut buffer, width)
+            .expect("Render should succeed");
+        assert_eq!(report.drawn_glyphs, 3);
+
+        // Brightest pixel within each glyph's advance
+        let placements = renderer.layout(&input).expect("Layout should succeed");
+        let brightest: Vec<_> = placements
+            .iter()
+            .map(|p| {
+                let columns = p.x.ceil() as usize..(p.x + p.advance) as usize;
+                buffer
+                    .chunks_exact(width)
+                    .flat_map(|row| &row[columns.clone()])
+                    .map(|pixel| pixel.0)
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        assert_eq!(brightest[0], u8::MAX);
+        assert!(brightest[1].abs_diff(u8::MAX / 2) <= 1, "{brightest:?}");
+        assert_eq!(brightest[2], 0);
+    }
+
+    #[test]
+    fn aliased_text_is_only_on_or_off() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+
+        let render = |antialias: bool| {
+            let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+            Renderer::new(&fonts)
+                .antialias(antialias)
+                .draw(&input(id, "AA"), &mut buffer, width)
+                .expect("Render should succeed");
+            buffer
+        };
+        let is_edge = |pixel: &rgb::Gray<u8>| pixel.0 != 0 && pixel.0 != u8::MAX;
+
+        // The squares' edges fall between pixels
+        assert!(render(true).iter().any(is_edge));
+        let aliased = render(false);
+        assert!(aliased.contains(&rgb::Gray::new(u8::MAX)));
+        assert!(!aliased.iter().any(is_edge));
+    }
+
+    #[test]
+    fn draws_accumulate_without_clearing() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+        // Unmapped spaces move the second 'A' right of the first
+        let (first, second) = (input(id, "A"), input(id, "  A"));
+        let ink_columns = |buffer: &[rgb::Gray<u8>]| {
+            let bounds = |text: &[FormattedSlice<'_>]| {
+                let mut alone = vec![rgb::Gray::new(0u8); width * 64];
+                Renderer::new(&fonts)
+                    .draw(text, &mut alone, width)
+                    .expect("Render should succeed")
+                    .ink_bounds
+                    .expect("Glyph should be drawn")
+            };
+            [bounds(&first), bounds(&second)].map(|b| {
+                let centre = b.x + b.width / 2;
+                buffer
+                    .chunks_exact(width)
+                    .any(|row| row[centre].0 == u8::MAX)
+            })
+        };
+
+        let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+        let renderer = Renderer::new(&fonts).clear(false);
+        for text in [&first, &second] {
+            renderer
+                .draw(text, &mut buffer, width)
+                .expect("Render should succeed");
+        }
+        assert_eq!(ink_columns(&buffer), [true, true]);
+
+        // Clearing leaves only the last draw
+        Renderer::new(&fonts)
+            .draw(&second, &mut buffer, width)
+            .expect("Render should succeed");
+        assert_eq!(ink_columns(&buffer), [false, true]);
+    }
+
+    #[test]
+    fn render_into_fills_the_glyph_alone() {
+        let font = crate::parse_font_bytes(&square_font()).expect("Font should parse");
+        let glyph = font.glyph(1).expect("Glyph should exist");
+
+        // The square spans (100, 0) to (500, 400) units, so pixels (5, 4) to
+        // (25, 24) at a twentieth of a pixel per unit
+        let width = 32;
+        let mut buffer = vec![rgb::Gray::new(0u8); width * 32];
+        glyph.render_into(
+            &mut buffer,
+            width,
+            0.05,
+            (0.0, 24.0),
+            DrawMode::Overwrite,
+            true,
+        );
+
+        let covered: Vec<_> = (0..buffer.len())
+            .filter(|&i| buffer[i].0 != 0)
+            .map(|i| (i % width, i / width))
+            .collect();
+        assert_eq!(covered.len(), 20 * 20);
+        assert!(covered
+            .iter()
+            .all(|&(x, y)| (5..25).contains(&x) && (4..24).contains(&y)));
+        // Edges may lose a little coverage to float rounding
+        let faintest = covered.iter().map(|&(x, y)| buffer[y * width + x].0).min();
+        assert!(faintest >= Some(u8::MAX - 1), "{faintest:?}");
+    }
+
+    #[test]
+    fn reversed_glyphs_fill_solid() {
+        let (fonts, id) = square_collection();
+        let mut reversed = rect(100, 0, 500, 400);
+        reversed.reverse();
+        let reversed = font(&[(Vec::new(), 500), (simple_glyph(&[&reversed]), 600)], &[
+            ('A', 1),
+        ]);
+        let mut reversed_fonts = FontCollection::new(alloc::alloc::Global);
+        let reversed_id = reversed_fonts
+            .add_loaded(crate::parse_font_bytes(&reversed).expect("Font should parse"));
+        let width = 64;
+
+        // The non-zero rule only counts crossings, whichever way they run
+        let mut clockwise = vec![rgb::Gray::new(0u8); width * 64];
+        Renderer::new(&fonts)
+            .draw(&input(id, "A"), &mut clockwise, width)
+            .expect("Render should succeed");
+        let mut counter_clockwise = vec![rgb::Gray::new(0u8); width * 64];
+        Renderer::new(&reversed_fonts)
+            .draw(&input(reversed_id, "A"), &mut counter_clockwise, width)
+            .expect("Render should succeed");
+
+        assert!(clockwise.contains(&rgb::Gray::new(u8::MAX)));
+        assert_eq!(clockwise, counter_clockwise);
+    }
+
+    #[test]
+    fn builder_matches_positional_call() {
+        let (fonts, id) = square_collection();
+        let width = 64;
+
+        for subpixel in [SubPixelAlignment::None, SubPixelAlignment::Rgb] {
+            let mut positional = vec![rgb::Gray::new(0u8); width * 64];
+            let positional_report = to_buf(
+                &fonts,
+                &input(id, "AA"),
+                &mut positional,
+                width,
+                96,
+                subpixel,
+            )
+            .expect("Render should succeed");
+
+            let mut built = vec![rgb::Gray::new(0u8); width * 64];
+            let built_report = Renderer::new(&fonts)
+                .dpi(96)
+                .subpixel(subpixel)
+                .draw(&input(id, "AA"), &mut built, width)
+                .expect("Render should succeed");
+
+            assert!(positional.contains(&rgb::Gray::new(u8::MAX)));
+            assert_eq!(positional, built, "{subpixel:?}");
+            let summary = |report: RenderReport| {
+                (
+                    report.drawn_glyphs,
+                    report.clipped_glyphs,
+                    report.used_width,
+                    report.used_height,
+                    report.ink_bounds,
+                )
+            };
+            assert_eq!(summary(positional_report), summary(built_report));
+        }
+    }
+
+    #[test]
+    fn text_wider_than_the_buffer_is_clipped() {
+        let (fonts, id) = square_collection();
+        let width = 32;
+
+        // Each 'A' advances 14.4 pixels, so only the first two start inside
+        let mut buffer = vec![rgb::Gray::new(0u8); width * 64];
+        let report = to_buf(
+            &fonts,
+            &input(id, "AAAA"),
+            &mut buffer,
+            width,
+            72,
+            SubPixelAlignment::None,
+        )
+        .expect("Render should succeed");
+        assert_eq!(report.drawn_glyphs, 4);
+        assert!(report.clipped_glyphs > 0);
+        assert!(report.used_width > width);
+
+        let report = to_buf(
+            &fonts,
+            &input(id, "A"),
+            &mut buffer,
+            width,
+            72,
+            SubPixelAlignment::None,
+        )
+        .expect("Render should succeed");
+        assert_eq!((report.drawn_glyphs, report.clipped_glyphs), (1, 0));
+    }
+
+    #[test]
+    fn chars_render_like_the_equivalent_str() {
+        let (fonts, id) = square_collection();
+        let width = 128;
+        // Unmapped characters draw `.notdef`, and the caret splits the run
+        let text = "A A?A";
+        let renderer = Renderer::new(&fonts).caret(2);
+
+        let mut from_str = vec![rgb::Gray::new(0u8); width * 64];
+        let str_report = renderer
+            .draw(&input(id, text), &mut from_str, width)
+            .expect("Render should succeed");
+
+        let mut from_chars = vec![rgb::Gray::new(0u8); width * 64];
+        let chars_report = renderer
+            .draw_chars(id, 24, text.chars(), &mut from_chars, width)
+            .expect("Render should succeed");
+
+        assert!(from_str.contains(&rgb::Gray::new(u8::MAX)));
+        assert_eq!(from_str, from_chars);
+        assert_eq!(str_report.drawn_glyphs, chars_report.drawn_glyphs);
+        assert_eq!(str_report.ink_bounds, chars_report.ink_bounds);
+
+        let mut positional = vec![rgb::Gray::new(0u8); width * 64];
+        render_chars(
+            &fonts,
+            id,
+            24,
+            text.chars().filter(|&c| c != '?'),
+            &mut positional,
+            width,
+            72,
+            SubPixelAlignment::None,
+        )
+        .expect("Render should succeed");
+        let mut expected = vec![rgb::Gray::new(0u8); width * 64];
+        Renderer::new(&fonts)
+            .draw(&input(id, "A AA"), &mut expected, width)
+            .expect("Render should succeed");
+        assert_eq!(positional, expected);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn glyphs_turn_along_an_arc() {
+        let (fonts, id) = square_collection();
+        let (width, height) = (256, 160);
+        let input = input(id, "AAAAAAAA");
+
+        // Clockwise over the top of a circle, from its left to its right
+        let arc: Vec<_> = (0..=32)
+            .map(|i| {
+                let angle = core::f32::consts::PI * (1.0 + i as f32 / 32.0);
+                (
+                    100f32.mul_add(angle.cos(), 128.0),
+                    100f32.mul_add(angle.sin(), 140.0),
+                )
+            })
+            .collect();
+        let angles: Vec<_> = Renderer::<_, u8>::new(&fonts)
+            .layout(&input)
+            .expect("Layout should succeed")
+            .iter()
+            .map(|placement| {
+                let transform = path_transform(&arc, placement);
+                transform[0][1].atan2(transform[0][0])
+            })
+            .collect();
+        assert!(angles[0] < -1.0, "{angles:?}");
+        assert!(angles.array_windows().all(|[a, b]| a < b), "{angles:?}");
+
+        let mut on_arc = vec![rgb::Gray::new(0u8); width * height];
+        let report = render_on_path(
+            &fonts,
+            &input,
+            &arc,
+            &mut on_arc,
+            width,
+            72,
+            SubPixelAlignment::None,
+        )
+        .expect("Render should succeed");
+        assert_eq!((report.drawn_glyphs, report.clipped_glyphs), (8, 0));
+        assert!(on_arc.contains(&rgb::Gray::new(u8::MAX)));
+
+        // Along the baseline, the text is drawn as it's laid out
+        let mut on_line = vec![rgb::Gray::new(0u8); width * height];
+        let mut flat = vec![rgb::Gray::new(0u8); width * height];
+        let renderer = Renderer::new(&fonts);
+        renderer
+            .draw_on_path(
+                &input,
+                &[(0.0, BASELINE), (1.0, BASELINE)],
+                &mut on_line,
+                width,
+            )
+            .expect("Render should succeed");
+        renderer
+            .draw(&input, &mut flat, width)
+            .expect("Render should succeed");
+        assert_eq!(on_line, flat);
+        assert_ne!(on_arc, flat);
+    }
+
+    #[test]
+    fn separate_glyphs_composite_into_the_full_render() {
+        let (fonts, id) = square_collection();
+        let width = 128;
+        let input = input(id, "A AA");
+
+        let bitmaps =
+            render_glyphs_separate::<_, u8>(&fonts, &input, 72).expect("Render should succeed");
+        let placements = Renderer::<_, u8>::new(&fonts)
+            .layout(&input)
+            .expect("Layout should succeed");
+        assert_eq!(bitmaps.len(), 4);
+        assert!(bitmaps
+            .iter()
+            .zip(placements.iter())
+            .all(|(bitmap, placement)| bitmap.placement == *placement));
+
+        let mut composited = vec![rgb::Gray::new(0u8); width * 64];
+        for bitmap in &bitmaps {
+            let rows = bitmap.pixels.chunks_exact(bitmap.width);
+            for (row, y) in rows.zip(bitmap.y..) {
+                for (pixel, x) in row.iter().zip(bitmap.x..) {
+                    let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+                        continue;
+                    };
+                    if pixel.0 != 0 && x < width {
+                        composited[y * width + x] = *pixel;
+                    }
+                }
+            }
+        }
+
+        let mut full = vec![rgb::Gray::new(0u8); width * 64];
+        Renderer::new(&fonts)
+            .draw(&input, &mut full, width)
+            .expect("Render should succeed");
+        assert!(full.contains(&rgb::Gray::new(u8::MAX)));
+        assert_eq!(composited, full);
+    }
+
+    #[test]
+    fn clicks_find_the_glyph_and_its_half() {
+        let (fonts, id) = square_collection();
+        let placements = Renderer::<_, u8>::new(&fonts)
+            .layout(&input(id, "AAA"))
+            .expect("Layout should succeed");
+        let second = placements[1];
+        #[allow(clippy::cast_possible_truncation)]
+        let click = |along: f32| {
+            hit_test(
+                &placements,
+                second.advance.mul_add(along, second.x) as i32,
+                (second.y - second.ascent / 2.0) as i32,
+            )
+        };
+
+        assert_eq!(
+            click(0.25),
+            Some(HitResult {
+                glyph_index: 1,
+                cluster:     1,
+                leading:     true,
+            })
+        );
+        assert_eq!(
+            click(0.75),
+            Some(HitResult {
+                glyph_index: 1,
+                cluster:     1,
+                leading:     false,
+            })
+        );
+        // Above the line
+        assert_eq!(hit_test(&placements, 20, -5), None);
+    }
+
+    #[test]
+    fn fonts_without_head_fail_to_render() {
+        let headless = sfnt(&[
+            (*b"maxp", &maxp(2)),
+            (*b"hhea", &hhea(800, -200, 2)),
+            (*b"hmtx", &hmtx(&[(500, 0), (600, 100)], &[])),
+            (*b"cmap", &cmap(&[(3, 1, cmap4(&[('A', 1)]))])),
+        ]);
+        // Without `head` there's no ChecksumAdjustment to match
+        let (font, errors) = crate::open_font_collect_errors(
+            alloc::alloc::Global,
+            &mut crate::types::SliceReader::new(&headless),
+        );
+        assert!(matches!(errors[..], [crate::ParseError::Parsing {
+            variable: "ChecksumAdjustment",
+            ..
+        }]));
+        let font = font.expect("Font should parse");
+        assert_eq!(font.units_per_em(), None);
+
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let id = fonts.add_loaded(font);
+        let mut buffer = vec![rgb::Gray::new(0u8); 64 * 64];
+        assert!(matches!(
+            Renderer::new(&fonts).draw(&input(id, "A"), &mut buffer, 64),
+            Err(Error::MissingTable("head"))
+        ));
+        assert!(matches!(
+            to_buf(
+                &fonts,
+                &input(id, "A"),
+                &mut buffer,
+                64,
+                72,
+                SubPixelAlignment::None
+            ),
+            Err(Error::MissingTable("head"))
+        ));
+    }
+
+    #[test]
+    fn kerning_moves_the_pen_by_each_glyphs_kern() {
+        let bytes = font_with(
+            &[
+                (Vec::new(), 500),
+                (simple_glyph(&[&rect(0, 0, 600, 700)]), 600),
+                (simple_glyph(&[&rect(0, 0, 600, 700)]), 600),
+            ],
+            &[('A', 1), ('V', 2)],
+            &[(*b"kern", &kern(&[((1, 2), -100)]))],
+        );
+        let mut fonts = FontCollection::new(alloc::alloc::Global);
+        let id = fonts.add_loaded(crate::parse_font_bytes(&bytes).expect("Font should parse"));
+
+        let layout = |kerning: bool| {
+            Renderer::<_, u8>::new(&fonts)
+                .kerning(kerning)
+                .layout(&input(id, "AV"))
+                .expect("Layout should succeed")
+        };
+        let (kerned, unkerned) = (layout(true), layout(false));
+
+        // 24pt at 72 dpi is 24 pixels per em, so -100 units is -2.4 pixels
+        assert_eq!(kerned.len(), 2);
+        assert!(kerned[0].kern.abs() < f32::EPSILON);
+        assert!((kerned[1].kern + 2.4).abs() < 1e-4);
+        assert!((unkerned[1].kern).abs() < f32::EPSILON);
+        assert!((unkerned[1].x - kerned[1].x - 2.4).abs() < 1e-4);
+        assert!((kerned[0].x - unkerned[0].x).abs() < f32::EPSILON);
+        assert_eq!(
+            kerned
+                .iter()
+                .map(|p| (p.glyph_id, p.cluster))
+                .collect::<Vec<_>>(),
+            [(1, 0), (2, 1)]
+        );
+    }
 }
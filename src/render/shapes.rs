@@ -3,12 +3,23 @@
 
 use core::mem;
 
+use itertools::Itertools;
+
 use super::{
+    CoreVec,
+    CoverageRounding,
     Display,
     DrawMode,
+    InkBounds,
 };
 
-#[allow(clippy::cast_sign_loss)]
+/// Draws the pixel at (`x`, `y`) of the image, which is only within the buffer
+/// for rows of the band it holds
+#[allow(
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap
+)]
 pub fn draw_pixel<T: num_traits::PrimInt>(
     display: &mut Display<'_, T>,
     draw_mode: DrawMode,
@@ -16,23 +27,57 @@ pub fn draw_pixel<T: num_traits::PrimInt>(
     y: i32,
     value: f32,
 ) {
+    if x >= 0 && y >= 0 {
+        display.extent.0 = display.extent.0.max(x as usize + 1);
+        display.extent.1 = display.extent.1.max(y as usize + 1);
+    }
+
     let height = display.buffer.len() / display.width;
-    if x < 0 || y < 0 || (x as usize) >= display.width || (y as usize) >= height {
+    let row = y - display.y_offset as i32;
+    if x < 0 || row < 0 || (x as usize) >= display.width || (row as usize) >= height {
+        display.clipped = true;
         return;
     }
+
+    let value = if display.antialias {
+        value
+    } else if value.abs() >= 0.5 {
+        1f32
+    } else {
+        return;
+    } * display.opacity;
     //println!("{x} {y} {value}");
-    let pixel_val: T =
-        T::from(T::max_value().to_f32().expect("overflow on T to f32") * value.abs())
-            .unwrap_or_else(|| T::max_value());
-
-    let prev_pixel = display.buffer[y as usize * display.width + x as usize].0;
-    display.buffer[y as usize * display.width + x as usize] = rgb::Gray::new(match draw_mode {
-        DrawMode::Overwrite => pixel_val,
-        DrawMode::Multiply => prev_pixel * pixel_val,
-        DrawMode::Add => prev_pixel.saturating_add(pixel_val),
-    });
+    if value != 0.0 {
+        InkBounds::include(&mut display.ink, x as usize, y as usize);
+    }
+    let scaled = T::max_value().to_f32().expect("overflow on T to f32") * value.abs();
+    let pixel_val: T = T::from(match display.rounding {
+        CoverageRounding::Floor => scaled.floor(),
+        CoverageRounding::Round => scaled.round(),
+        CoverageRounding::Ceil => scaled.ceil(),
+    })
+    .unwrap_or_else(|| T::max_value());
+
+    let idx = row as usize * display.width + x as usize;
+    let prev_pixel = display.buffer[idx].0;
+    display.buffer[idx] = rgb::Gray::new(draw_mode.apply(prev_pixel, pixel_val));
 }
 
+/// Draws a 3x3 square centered on (`x`, `y`)
+pub fn draw_marker<T: num_traits::PrimInt>(
+    display: &mut Display<'_, T>,
+    draw_mode: DrawMode,
+    x: i32,
+    y: i32,
+    value: f32,
+) {
+    for (dx, dy) in itertools::iproduct!(-1..=1, -1..=1) {
+        draw_pixel(display, draw_mode, x + dx, y + dy, value);
+    }
+}
+
+/// Strokes a `width` pixel wide line by drawing a cross-section along the
+/// line's normal every pixel along its length
 #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
 pub fn draw_line<T: num_traits::PrimInt>(
     display: &mut Display<'_, T>,
@@ -40,8 +85,6 @@ pub fn draw_line<T: num_traits::PrimInt>(
     end: (f32, f32),
     width: f32,
 ) {
-    let half_width = width / 4.0;
-
     let (mut x1, mut y1) = start;
     let (mut x2, mut y2) = end;
 
@@ -55,38 +98,31 @@ pub fn draw_line<T: num_traits::PrimInt>(
         mem::swap(&mut y1, &mut y2);
     }
 
-    // draw rectangle outline
+    // |slope| <= 1 after the swaps, dx is only 0 for a single point
     let dx = x2 - x1;
     let dy = y2 - y1;
-    let slope = if dx == 0f32 { 1f32 } else { dy / dx };
+    let slope = if dx == 0f32 { 0f32 } else { dy / dx };
 
-    // perp line = end lines
-    // perp: m1 x m2 = -1
-    // -1/m1 = m2
-    // -1 / slope = perp_slope
-    let perp_slope = -1f32 / slope;
-
-    let to_coords = |x: f32, c: f32| {
-        if is_steep {
-            (perp_slope.mul_add(x, c), x)
-        } else {
-            (x, perp_slope.mul_add(x, c))
-        }
-    };
+    // Normal of the line, scaled to half the stroke width
+    let half_width = width / 2.0;
+    let normal_len = slope.hypot(1.0);
+    let normal = (-slope * half_width / normal_len, half_width / normal_len);
 
-    let mut y = slope.mul_add(x1.round() - x1, y1) + slope;
+    let to_coords = |x: f32, y: f32| if is_steep { (y, x) } else { (x, y) };
 
-    let scale_factor = width * 4.0;
-    for x in ((x1.round() * scale_factor) as i32)..=((x2.round() * scale_factor) as i32) {
-        let x = x as f32 / scale_factor;
-        let c = perp_slope.mul_add(-x, y);
+    // Stepping by a pixel of length rather than of x keeps diagonals as dense
+    // as straight lines
+    let step = normal_len.recip();
+    let x_start = x1.round();
+    for idx in 0..=((x2.round() - x_start) / step) as i32 {
+        let x = (idx as f32).mul_add(step, x_start);
+        let y = slope.mul_add(x - x1, y1);
         draw_line_antialiased(
             display,
             DrawMode::Add,
-            to_coords(x - half_width, c),
-            to_coords(x + half_width, c),
+            to_coords(x - normal.0, y - normal.1),
+            to_coords(x + normal.0, y + normal.1),
         );
-        y += slope / scale_factor;
     }
 }
 
@@ -130,6 +166,175 @@ pub fn draw_line_aliased<T: num_traits::PrimInt>(
     }
 }
 
+/// Strokes the quadratic bezier from `start` to `end` a pixel wide, splitting
+/// it in half until each piece is within a quarter pixel of a straight line
+pub fn draw_quad_bezier<T: num_traits::PrimInt>(
+    display: &mut Display<'_, T>,
+    start: (f32, f32),
+    control: (f32, f32),
+    end: (f32, f32),
+) {
+    flatten_quad_bezier(display, start, control, end, 0);
+}
+
+fn flatten_quad_bezier<T: num_traits::PrimInt>(
+    display: &mut Display<'_, T>,
+    start: (f32, f32),
+    control: (f32, f32),
+    end: (f32, f32),
+    depth: u32,
+) {
+    const TOLERANCE: f32 = 0.25;
+    // Bounds the recursion for huge or degenerate curves
+    const MAX_DEPTH: u32 = 10;
+
+    let midpoint = |a: (f32, f32), b: (f32, f32)| (a.0.midpoint(b.0), a.1.midpoint(b.1));
+
+    // Distance between the curve's midpoint and its chord
+    let deviation = 2.0f32
+        .mul_add(-control.0, start.0 + end.0)
+        .hypot(2.0f32.mul_add(-control.1, start.1 + end.1))
+        / 4.0;
+    if deviation <= TOLERANCE || depth == MAX_DEPTH {
+        draw_line(display, start, end, 1.0);
+        return;
+    }
+
+    let start_control = midpoint(start, control);
+    let control_end = midpoint(control, end);
+    let split = midpoint(start_control, control_end);
+    flatten_quad_bezier(display, start, start_control, split, depth + 1);
+    flatten_quad_bezier(display, split, control_end, end, depth + 1);
+}
+
+struct Edge {
+    // x at `y_top`
+    x:       f32,
+    y_top:   f32,
+    y_bot:   f32,
+    dx_dy:   f32,
+    // +1 for edges heading down, -1 for up
+    winding: i32,
+}
+
+/// Fills the closed outline made of `lines` using the non-zero winding rule,
+/// sampling `SAMPLES` scanlines per pixel row for coverage
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn fill_glyph<A: core::alloc::Allocator + Clone, T: num_traits::PrimInt>(
+    display: &mut Display<'_, T>,
+    draw_mode: DrawMode,
+    allocator: A,
+    lines: impl Iterator<Item = ((f32, f32), (f32, f32))>,
+) {
+    const SAMPLES: usize = 4;
+
+    let mut edges = CoreVec::new_in(allocator.clone());
+    for ((x1, y1), (x2, y2)) in lines {
+        // Horizontal edges never cross a scanline
+        if y2 - y1 == 0.0 {
+            continue;
+        }
+        let (top, bot, winding) = if y1 < y2 {
+            ((x1, y1), (x2, y2), 1)
+        } else {
+            ((x2, y2), (x1, y1), -1)
+        };
+        edges.push(Edge {
+            x: top.0,
+            y_top: top.1,
+            y_bot: bot.1,
+            dx_dy: (bot.0 - top.0) / (bot.1 - top.1),
+            winding,
+        });
+    }
+    if edges.is_empty() {
+        return;
+    }
+    edges.sort_by(|a, b| a.y_top.total_cmp(&b.y_top));
+
+    // Pixels outside the buffer are never visited, so track them up front
+    let (x_min, x_max) = edges.iter().fold((f32::MAX, f32::MIN), |(min, max), e| {
+        let x_bot = e.dx_dy.mul_add(e.y_bot - e.y_top, e.x);
+        (min.min(e.x).min(x_bot), max.max(e.x).max(x_bot))
+    });
+    let y_min = edges[0].y_top;
+    let y_max = edges.iter().map(|e| e.y_bot).fold(f32::MIN, f32::max);
+
+    // Only the rows of the band held by the buffer are rasterized
+    let band = display.y_offset..display.y_offset + display.buffer.len() / display.width;
+    if x_min < 0.0
+        || y_min < band.start as f32
+        || x_max > display.width as f32
+        || y_max > band.end as f32
+    {
+        display.clipped = true;
+    }
+    if x_max > 0.0 && y_max > 0.0 {
+        display.extent.0 = display.extent.0.max(x_max.ceil() as usize);
+        display.extent.1 = display.extent.1.max(y_max.ceil() as usize);
+    }
+
+    let mut coverage = CoreVec::new_in(allocator.clone());
+    coverage.resize(display.width, 0f32);
+    let mut crossings = CoreVec::new_in(allocator.clone());
+    let mut active = CoreVec::new_in(allocator);
+    let mut next_edge = 0;
+
+    let rows = (y_min.floor().max(0.0) as usize).max(band.start)
+        ..(y_max.ceil().max(0.0) as usize).min(band.end);
+    for row in rows {
+        for sample in 0..SAMPLES {
+            let y = row as f32 + (sample as f32 + 0.5) / SAMPLES as f32;
+
+            while next_edge < edges.len() && edges[next_edge].y_top <= y {
+                active.push(next_edge);
+                next_edge += 1;
+            }
+            active.retain(|&idx| edges[idx].y_bot > y);
+
+            crossings.clear();
+            crossings.extend(active.iter().map(|&idx| {
+                let edge = &edges[idx];
+                (edge.dx_dy.mul_add(y - edge.y_top, edge.x), edge.winding)
+            }));
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for (&(start, direction), &(end, _)) in crossings.iter().tuple_windows() {
+                winding += direction;
+                if winding == 0 {
+                    continue;
+                }
+
+                // Spread the span over the pixels it partially covers
+                let (start, end) = (start.max(0.0), end.min(display.width as f32));
+                let first = start.floor() as usize;
+                let pixels = coverage
+                    .iter_mut()
+                    .enumerate()
+                    .take(end.ceil() as usize)
+                    .skip(first);
+                for (px, value) in pixels {
+                    let covered = end.min(px as f32 + 1.0) - start.max(px as f32);
+                    *value += covered / SAMPLES as f32;
+                }
+            }
+        }
+
+        for (x, value) in coverage.iter_mut().enumerate() {
+            if *value > 0.0 {
+                draw_pixel(display, draw_mode, x as i32, row as i32, value.min(1.0));
+                *value = 0.0;
+            }
+        }
+    }
+}
+
 /// [f32] coords should be fine, but if an image is larger than `u16::MAX`,
 /// precision issues might occur
 #[allow(clippy::cast_possible_truncation)]
@@ -190,7 +395,7 @@ pub fn draw_line_antialiased<T: num_traits::PrimInt>(
     draw_steep_pixel(x_px1, y_px1, (1f32 - ev1) * x_gap);
     draw_steep_pixel(x_px1, y_px1 + 1, ev1 * x_gap);
 
-    let x_gap = 1f32 - (x2 + 0.5f32).fract();
+    let x_gap = (x2 + 0.5f32).fract();
     draw_steep_pixel(x_px2, y_px2, (1f32 - ev2) * x_gap);
     draw_steep_pixel(x_px2, y_px2 + 1, ev2 * x_gap);
 
@@ -200,3 +405,96 @@ pub fn draw_line_antialiased<T: num_traits::PrimInt>(
         y_int += gradient;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::IDENTITY;
+
+    /// Coverage of `line` stroked `width` pixels wide across a 32x32 buffer,
+    /// from 0 to 1
+    #[allow(clippy::cast_precision_loss)]
+    fn stroke(
+        (start, end): ((f32, f32), (f32, f32)),
+        width: f32,
+    ) -> Vec<f32> {
+        let mut buffer = vec![rgb::Gray::new(0u16); 32 * 32];
+        let mut display = Display {
+            buffer:    &mut buffer,
+            width:     32,
+            y_offset:  0,
+            antialias: true,
+            opacity:   1.0,
+            rounding:  CoverageRounding::Round,
+            transform: IDENTITY,
+            clipped:   false,
+            extent:    (0, 0),
+            ink:       None,
+        };
+        draw_line(&mut display, start, end, width);
+
+        buffer
+            .iter()
+            .map(|pixel| f32::from(pixel.0) / f32::from(u16::MAX))
+            .collect()
+    }
+
+    #[test]
+    fn line_strokes_are_as_wide_as_asked() {
+        // Summing a column across the stroke gives its width
+        let horizontal = stroke(((4.0, 16.5), (28.0, 16.5)), 1.0);
+        for x in 6..26 {
+            let column: f32 = horizontal.iter().skip(x).step_by(32).sum();
+            assert!((column - 1.0).abs() < 0.25, "{column} at x {x}");
+        }
+
+        let vertical = stroke(((16.5, 4.0), (16.5, 28.0)), 1.0);
+        for y in 6..26 {
+            let row: f32 = vertical[y * 32..(y + 1) * 32].iter().sum();
+            assert!((row - 1.0).abs() < 0.25, "{row} at y {y}");
+        }
+
+        let thick = stroke(((4.0, 16.5), (28.0, 16.5)), 3.0);
+        let column: f32 = thick.iter().skip(16).step_by(32).sum();
+        assert!((column - 3.0).abs() < 0.5, "{column}");
+    }
+
+    /// Pixels of an antialiased edge drawn with `rounding` across a 32x32
+    /// buffer
+    fn edge(rounding: CoverageRounding) -> Vec<u8> {
+        let mut buffer = vec![rgb::Gray::new(0u8); 32 * 32];
+        let mut display = Display {
+            buffer: &mut buffer,
+            width: 32,
+            y_offset: 0,
+            antialias: true,
+            opacity: 1.0,
+            rounding,
+            transform: IDENTITY,
+            clipped: false,
+            extent: (0, 0),
+            ink: None,
+        };
+        draw_line_antialiased(&mut display, DrawMode::Overwrite, (2.0, 4.3), (28.0, 9.7));
+
+        buffer.into_iter().map(|pixel| pixel.0).collect()
+    }
+
+    #[test]
+    fn coverage_rounding_biases_edges() {
+        let floor = edge(CoverageRounding::Floor);
+        let round = edge(CoverageRounding::Round);
+        let ceil = edge(CoverageRounding::Ceil);
+
+        for ((&floor, &round), &ceil) in floor.iter().zip(&round).zip(&ceil) {
+            assert!(floor <= round && round <= ceil, "{floor} {round} {ceil}");
+            // Quantizing moves coverage by less than a step
+            assert!(ceil - floor <= 1, "{floor} {ceil}");
+        }
+
+        // Floor is lighter and ceil darker where coverage is fractional
+        let ink = |pixels: &[u8]| pixels.iter().map(|&pixel| u32::from(pixel)).sum::<u32>();
+        assert!(ink(&floor) < ink(&round));
+        assert!(ink(&round) < ink(&ceil));
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreReadError,
+        CoreVec,
+        SliceReader,
+        ValidType,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Baseline positions of a script, in font units
+#[derive(Debug, Clone)]
+pub struct Script<A: core::alloc::Allocator> {
+    pub tag:              [u8; 4],
+    /// Index into [`Type::baseline_tags`] of the baseline the script aligns
+    /// to
+    pub default_baseline: u16,
+    /// Coordinate of each baseline in [`Type::baseline_tags`], empty if the
+    /// script has none
+    pub coords:           CoreVec<i16, A>,
+}
+
+/// Horizontal axis baselines, for aligning scripts within a line
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    /// e.g. `romn`, `ideo`, `hang`
+    pub baseline_tags: CoreVec<[u8; 4], A>,
+    /// Sorted by tag
+    pub scripts:       CoreVec<Script<A>, A>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    fn script(
+        &self,
+        script: [u8; 4],
+    ) -> Option<&Script<A>> {
+        self.scripts
+            .binary_search_by_key(&script, |s| s.tag)
+            .ok()
+            .map(|idx| &self.scripts[idx])
+    }
+
+    /// Coordinate of `script`'s default baseline
+    pub fn baseline_offset(
+        &self,
+        script: [u8; 4],
+    ) -> Option<i16> {
+        let script = self.script(script)?;
+        script.coords.get(usize::from(script.default_baseline)).copied()
+    }
+
+    /// Coordinate of the `baseline` tag for `script`
+    pub fn baseline(
+        &self,
+        script: [u8; 4],
+        baseline: [u8; 4],
+    ) -> Option<i16> {
+        let index = self.baseline_tags.iter().position(|t| *t == baseline)?;
+        self.script(script)?.coords.get(index).copied()
+    }
+}
+
+/// Reader over `bytes` starting at `offset`, as `BASE` is addressed by offsets
+fn reader_at<'a, E: core::error::Error>(
+    bytes: &'a [u8],
+    offset: usize,
+    location: &'static str,
+) -> Result<SliceReader<'a>, ParseError<E>> {
+    bytes
+        .get(offset..)
+        .map(SliceReader::new)
+        .ok_or(ParseError::Parsing {
+            variable: location,
+            expected: ValidType::USize(bytes.len()),
+            parsed:   ValidType::USize(offset),
+        })
+}
+
+/// Reads from the in-memory table, whose only failure is running out of bytes
+macro_rules! read {
+    ($read:expr) => {
+        $read.map_err(|error| match error {
+            CoreReadError::UnexpectedEnd(needed) => ParseError::UnexpectedEop {
+                location: "BASE",
+                needed,
+            },
+            CoreReadError::Io(never) => match never {},
+        })?
+    };
+}
+
+/// Parses the `BaseScript` table at `offset`, ignoring language systems and
+/// extents
+fn parse_script<A: core::alloc::Allocator + Copy, E: core::error::Error>(
+    allocator: A,
+    table: &[u8],
+    tag: [u8; 4],
+    offset: usize,
+) -> Result<Script<A>, ParseError<E>> {
+    let mut reader = reader_at(table, offset, "BaseScriptRecord::baseScriptOffset")?;
+    let base_values_offset = usize::from(read!(reader.read_int::<u16>()));
+
+    let mut script = Script {
+        tag,
+        default_baseline: 0,
+        coords: CoreVec::new_in(allocator),
+    };
+    if base_values_offset == 0 {
+        return Ok(script);
+    }
+
+    let values_offset = offset + base_values_offset;
+    let mut reader = reader_at(table, values_offset, "BaseScript::baseValuesOffset")?;
+    script.default_baseline = read!(reader.read_int());
+    let coord_count: u16 = read!(reader.read_int());
+    for _ in 0..coord_count {
+        let coord_offset = usize::from(read!(reader.read_int::<u16>()));
+        // Every format starts with the coordinate, later formats only add
+        // device adjustments
+        let mut coord_reader = reader_at(
+            table,
+            values_offset + coord_offset,
+            "BaseValues::baseCoordOffsets",
+        )?;
+        let _format: u16 = read!(coord_reader.read_int());
+        script.coords.push(read!(coord_reader.read_int()));
+    }
+
+    Ok(script)
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    // Everything is addressed by offsets from the start of the table
+    let mut table = CoreVec::new_in(allocator);
+    reader.read_to_vec(&mut table)?;
+    let mut reader = SliceReader::new(&table);
+
+    let major_version: u16 = read!(reader.read_int());
+    let minor_version: u16 = read!(reader.read_int());
+    if major_version != 1 || minor_version > 1 {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"BASE",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0001_0000, 0x0001_0001],
+        });
+    }
+
+    let mut base = Type {
+        baseline_tags: CoreVec::new_in(allocator),
+        scripts:       CoreVec::new_in(allocator),
+    };
+
+    let axis_offset = usize::from(read!(reader.read_int::<u16>()));
+    if axis_offset == 0 {
+        return Ok(base);
+    }
+
+    let mut reader = reader_at(&table, axis_offset, "BASE::horizAxisOffset")?;
+    let tag_list_offset = usize::from(read!(reader.read_int::<u16>()));
+    let script_list_offset = axis_offset + usize::from(read!(reader.read_int::<u16>()));
+
+    if tag_list_offset != 0 {
+        let mut reader = reader_at(
+            &table,
+            axis_offset + tag_list_offset,
+            "Axis::baseTagListOffset",
+        )?;
+        let tag_count: u16 = read!(reader.read_int());
+        for _ in 0..tag_count {
+            base.baseline_tags.push(read!(reader.read_array()));
+        }
+    }
+
+    let mut reader = reader_at(&table, script_list_offset, "Axis::baseScriptListOffset")?;
+    let script_count: u16 = read!(reader.read_int());
+    for _ in 0..script_count {
+        let tag: [u8; 4] = read!(reader.read_array());
+        let offset = usize::from(read!(reader.read_int::<u16>()));
+        base.scripts.push(parse_script(
+            allocator,
+            &table,
+            tag,
+            script_list_offset + offset,
+        )?);
+    }
+    base.scripts.sort_by_key(|s| s.tag);
+
+    Ok(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+    use crate::{
+        testing::{
+            be,
+            head,
+            maxp,
+            sfnt,
+        },
+        FontTrait,
+    };
+
+    /// `BASE` with a horizontal axis of `tags`, and `scripts` of their default
+    /// baseline's index and a coordinate per tag
+    #[allow(clippy::cast_possible_truncation)]
+    fn base(
+        tags: &[[u8; 4]],
+        scripts: &[([u8; 4], u16, &[i16])],
+    ) -> Vec<u8> {
+        // Version 1.0, with the horizontal axis straight after the header
+        let mut bytes = be!(1u16, 0u16, 8u16, 0u16);
+
+        let tag_list = be!(tags.len() as u16);
+        let script_list_offset = 4 + tag_list.len() + 4 * tags.len();
+        bytes.extend(be!(4u16, script_list_offset as u16));
+        bytes.extend(tag_list);
+        bytes.extend(tags.iter().flatten());
+
+        // Each script is its BaseScript, BaseValues, then coordinates
+        let script_len = |coords: usize| 6 + 4 + 2 * coords + 4 * coords;
+        let mut offset = 2 + 6 * scripts.len();
+        bytes.extend(be!(scripts.len() as u16));
+        for (tag, _, coords) in scripts {
+            bytes.extend(tag);
+            bytes.extend(be!(offset as u16));
+            offset += script_len(coords.len());
+        }
+        for &(_, default, coords) in scripts {
+            bytes.extend(be!(6u16, 0u16, 0u16, default, coords.len() as u16));
+            for index in 0..coords.len() {
+                bytes.extend(be!((4 + 2 * coords.len() + 4 * index) as u16));
+            }
+            for &coord in coords {
+                bytes.extend(be!(1u16, coord));
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn ideographic_baseline_is_read_for_cjk() {
+        let table = base(&[*b"ideo", *b"romn"], &[
+            (*b"latn", 1, &[-120, 0]),
+            (*b"hani", 0, &[-120, 0]),
+        ]);
+        let font = sfnt(&[
+            (*b"head", &head(1000, 0, true)),
+            (*b"maxp", &maxp(1)),
+            (*b"BASE", &table),
+        ]);
+        let font = crate::parse_font_bytes(&font).expect("Font should parse");
+
+        assert_eq!(font.baseline_offset(*b"hani"), Some(-120));
+        assert_eq!(font.baseline_offset(*b"latn"), Some(0));
+        assert_eq!(font.baseline_offset(*b"arab"), None);
+
+        let Some(Table::Base(base)) = font
+            .tables()
+            .iter()
+            .find(|t| matches!(t, Table::Base(_)))
+        else {
+            panic!("Font should have a BASE table");
+        };
+        assert_eq!(base.baseline(*b"latn", *b"ideo"), Some(-120));
+        assert_eq!(base.baseline(*b"hani", *b"romn"), Some(0));
+        assert_eq!(base.baseline(*b"hani", *b"hang"), None);
+    }
+}
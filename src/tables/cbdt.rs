@@ -0,0 +1,177 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::{
+    cblc::GlyphLocation,
+    Table,
+};
+use crate::{
+    types::{
+        CoreRead,
+        CoreVec,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Raw bitmap data, located through `CBLC`
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    pub data: CoreVec<u8, A>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    /// PNG data of the glyph at `location`, for the PNG image formats 17-19
+    pub fn png(
+        &self,
+        location: &GlyphLocation,
+    ) -> Option<&[u8]> {
+        let start = location.offset as usize;
+        let image = self
+            .data
+            .get(start..start.checked_add(location.length as usize)?)?;
+
+        // Skip the small (5 byte) or big (8 byte) glyph metrics
+        let image = match location.image_format {
+            17 => image.get(5..)?,
+            18 => image.get(8..)?,
+            19 => image,
+            _ => return None,
+        };
+
+        let (data_len, data) = image.split_first_chunk::<4>()?;
+        data.get(..u32::from_be_bytes(*data_len) as usize)
+    }
+}
+
+/// PNG image of a glyph from the `CBDT` strike best matching a size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorBitmap<'a> {
+    /// Pixels per em of the strike the image was drawn for
+    pub strike_ppem: u16,
+    /// Pixels per em asked for, which [`ColorBitmap::decode`] scales the image
+    /// to
+    pub ppem:        u16,
+    pub png:         &'a [u8],
+}
+
+/// Decoded [`ColorBitmap`]
+#[derive(Debug, Clone)]
+pub struct ColorImage<A: core::alloc::Allocator> {
+    pub width:  usize,
+    pub height: usize,
+    /// Rows from the top, without premultiplied alpha
+    pub pixels: CoreVec<rgb::RGBA8, A>,
+}
+
+impl ColorBitmap<'_> {
+    /// Factor scaling the strike's image to the size asked for
+    #[must_use]
+    pub fn scale(&self) -> f32 {
+        f32::from(self.ppem) / f32::from(self.strike_ppem.max(1))
+    }
+
+    /// Decodes the image, resampled bilinearly by [`ColorBitmap::scale`]
+    /// # Errors
+    /// - If the PNG data is invalid
+    #[cfg(feature = "std")]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn decode<A: core::alloc::Allocator>(
+        &self,
+        allocator: A,
+    ) -> Result<ColorImage<A>, png::DecodingError> {
+        let mut decoder = png::Decoder::new(self.png);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+
+        let channels = info.color_type.samples();
+        let (source_width, source_height) = (info.width as usize, info.height as usize);
+        // Premultiplied, so transparent pixels' colours don't bleed into their
+        // neighbours
+        let source = |x: usize, y: usize| {
+            let pixel = data
+                .get(y * info.line_size + x * channels..)
+                .and_then(|pixel| pixel.get(..channels))
+                .unwrap_or_default();
+            let [r, g, b, a] = match *pixel {
+                [l] => [l, l, l, u8::MAX],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, u8::MAX],
+                [r, g, b, a] => [r, g, b, a],
+                _ => [0; 4],
+            }
+            .map(f32::from);
+            [r * a, g * a, b * a, a]
+        };
+
+        let scale = self.scale();
+        let width = ((source_width as f32 * scale).round() as usize).max(1);
+        let height = ((source_height as f32 * scale).round() as usize).max(1);
+        let mut pixels = CoreVec::with_capacity_in(width * height, allocator);
+        for (y, x) in itertools::iproduct!(0..height, 0..width) {
+            // Sampled at pixel centres
+            let at = |pos: usize, len: usize| {
+                let pos = ((pos as f32 + 0.5) / scale - 0.5).clamp(0.0, len.saturating_sub(1) as f32);
+                let start = pos as usize;
+                (start, (start + 1).min(len.saturating_sub(1)), pos.fract())
+            };
+            let (x0, x1, tx) = at(x, source_width);
+            let (y0, y1, ty) = at(y, source_height);
+
+            let lerp = |a: [f32; 4], b: [f32; 4], t: f32| {
+                core::array::from_fn::<_, 4, _>(|idx| (b[idx] - a[idx]).mul_add(t, a[idx]))
+            };
+            let [r, g, b, a] = lerp(
+                lerp(source(x0, y0), source(x1, y0), tx),
+                lerp(source(x0, y1), source(x1, y1), tx),
+                ty,
+            );
+            let unpremultiply = |c: f32| if a > 0.0 { (c / a).round() as u8 } else { 0 };
+            pixels.push(rgb::RGBA8::new(
+                unpremultiply(r),
+                unpremultiply(g),
+                unpremultiply(b),
+                a.round() as u8,
+            ));
+        }
+
+        Ok(ColorImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    let mut data = CoreVec::new_in(allocator);
+    reader.read_to_vec(&mut data)?;
+
+    // Header is kept so `CBLC` offsets index `data` directly
+    let version = data
+        .first_chunk::<4>()
+        .map_or(0, |version| u32::from_be_bytes(*version));
+    if version != 0x0003_0000 {
+        return Err(ParseError::UnsupportedVersion {
+            tag: *b"CBDT",
+            version,
+            supported: &[0x0003_0000],
+        });
+    }
+
+    Ok(Type { data })
+}
@@ -0,0 +1,491 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreReadError,
+        CoreVec,
+        SliceReader,
+        ValidType,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Location of a glyph's image within `CBDT`
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphLocation {
+    pub glyph_id:     u16,
+    pub image_format: u16,
+    /// From the start of `CBDT`
+    pub offset:       u32,
+    pub length:       u32,
+}
+
+/// Set of bitmaps for a single size
+#[derive(Debug, Clone)]
+pub struct Strike<A: core::alloc::Allocator> {
+    pub ppem_x:    u8,
+    pub ppem_y:    u8,
+    pub bit_depth: u8,
+    /// Sorted by glyph id
+    pub glyphs:    CoreVec<GlyphLocation, A>,
+}
+
+impl<A: core::alloc::Allocator> Strike<A> {
+    pub fn get(
+        &self,
+        glyph_id: u16,
+    ) -> Option<&GlyphLocation> {
+        self.glyphs
+            .binary_search_by_key(&glyph_id, |g| g.glyph_id)
+            .ok()
+            .map(|idx| &self.glyphs[idx])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    pub strikes: CoreVec<Strike<A>, A>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    /// Smallest strike of at least `ppem`, falling back to the largest
+    pub fn best_strike(
+        &self,
+        ppem: u16,
+    ) -> Option<&Strike<A>> {
+        self.strikes
+            .iter()
+            .filter(|s| u16::from(s.ppem_y) >= ppem)
+            .min_by_key(|s| s.ppem_y)
+            .or_else(|| self.strikes.iter().max_by_key(|s| s.ppem_y))
+    }
+}
+
+/// Reader over `bytes` starting at `offset`, as `CBLC` is addressed by offsets
+fn reader_at<'a, E: core::error::Error>(
+    bytes: &'a [u8],
+    offset: usize,
+    location: &'static str,
+) -> Result<SliceReader<'a>, ParseError<E>> {
+    bytes
+        .get(offset..)
+        .map(SliceReader::new)
+        .ok_or(ParseError::Parsing {
+            variable: location,
+            expected: ValidType::USize(bytes.len()),
+            parsed:   ValidType::USize(offset),
+        })
+}
+
+/// Reads from the in-memory table, whose only failure is running out of bytes
+macro_rules! read {
+    ($read:expr) => {
+        $read.map_err(|error| match error {
+            CoreReadError::UnexpectedEnd(needed) => ParseError::UnexpectedEop {
+                location: "CBLC",
+                needed,
+            },
+            CoreReadError::Io(never) => match never {},
+        })?
+    };
+}
+
+/// Appends the locations of subtable at `offset`, covering `first_glyph` to
+/// `last_glyph`, to `glyphs`. Each glyph entry read takes one of
+/// `entries_left`, stopping once none are left. Shared by the strike's
+/// subtables, as a font's own can't list more than `num_glyphs` between them
+/// but many may point at the same long one
+fn parse_index_subtable<A: core::alloc::Allocator + Copy, E: core::error::Error>(
+    table: &[u8],
+    offset: usize,
+    (first_glyph, last_glyph): (u16, u16),
+    num_glyphs: u16,
+    entries_left: &mut usize,
+    glyphs: &mut CoreVec<GlyphLocation, A>,
+) -> Result<(), ParseError<E>> {
+    let mut reader = reader_at(table, offset, "CBLC::indexSubtableOffset")?;
+
+    let index_format: u16 = read!(reader.read_int());
+    let image_format: u16 = read!(reader.read_int());
+    let image_data_offset: u32 = read!(reader.read_int());
+
+    // Ids past `num_glyphs` are never kept, so aren't worth reading
+    let last_glyph = last_glyph.min(num_glyphs.saturating_sub(1));
+    let mut take_entry = || {
+        let taken = *entries_left > 0;
+        *entries_left = entries_left.saturating_sub(1);
+        taken
+    };
+
+    let mut push = |glyph_id: u16, start: u32, end: u32| {
+        if end > start && glyph_id < num_glyphs {
+            glyphs.push(GlyphLocation {
+                glyph_id,
+                image_format,
+                offset: image_data_offset.saturating_add(start),
+                length: end - start,
+            });
+        }
+    };
+
+    match index_format {
+        // Offsets for every glyph in the range, u32 (1) or u16 (3)
+        1 | 3 => {
+            let mut read_offset = || -> Result<u32, ParseError<E>> {
+                Ok(if index_format == 1 {
+                    read!(reader.read_int())
+                } else {
+                    let offset: u16 = read!(reader.read_int());
+                    u32::from(offset)
+                })
+            };
+
+            let mut prev = read_offset()?;
+            for glyph_id in first_glyph..=last_glyph {
+                if !take_entry() {
+                    break;
+                }
+                let next = read_offset()?;
+                push(glyph_id, prev, next);
+                prev = next;
+            }
+        },
+        // Every glyph in the range has the same image size. Nothing else bounds
+        // the range, which would otherwise cost a location per id
+        2 => {
+            let image_size: u32 = read!(reader.read_int());
+            for (idx, glyph_id) in (0u32..)
+                .zip(first_glyph..=last_glyph)
+                .take_while(|_| take_entry())
+            {
+                let start = image_size.saturating_mul(idx);
+                push(glyph_id, start, start.saturating_add(image_size));
+            }
+        },
+        // Sparse (glyph id, offset) pairs, with a trailing sentinel
+        4 => {
+            let num_glyphs: u32 = read!(reader.read_int());
+            let mut prev: Option<(u16, u32)> = None;
+            for idx in 0..=num_glyphs {
+                // The sentinel ends the last glyph rather than being one
+                if idx < num_glyphs && !take_entry() {
+                    break;
+                }
+                let glyph_id: u16 = read!(reader.read_int());
+                let offset: u16 = read!(reader.read_int());
+                let offset = u32::from(offset);
+                if let Some((prev_glyph, prev_offset)) = prev {
+                    push(prev_glyph, prev_offset, offset);
+                }
+                prev = Some((glyph_id, offset));
+            }
+        },
+        // Sparse glyph ids, each with the same image size
+        5 => {
+            let image_size: u32 = read!(reader.read_int());
+            // bigMetrics
+            let _big_metrics: [u8; 8] = read!(reader.read_array());
+            let num_glyphs: u32 = read!(reader.read_int());
+            for idx in 0..num_glyphs {
+                if !take_entry() {
+                    break;
+                }
+                let glyph_id: u16 = read!(reader.read_int());
+                let start = image_size.saturating_mul(idx);
+                push(glyph_id, start, start.saturating_add(image_size));
+            }
+        },
+        _ => {
+            tracing::event!(
+                tracing::Level::WARN,
+                "Unsupported index subtable format {index_format}"
+            );
+        },
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    _options: &FontOptions,
+    prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    let Some(Table::Maxp(maxp)) = prev_tables.iter().find(|v| matches!(v, Table::Maxp(_))) else {
+        return Err(ParseError::MissingTable {
+            missing: "maxp",
+            parsing: "CBLC",
+        });
+    };
+
+    // Everything is addressed by offsets from the start of the table
+    let mut table = CoreVec::new_in(allocator);
+    reader.read_to_vec(&mut table)?;
+    let mut reader = SliceReader::new(&table);
+
+    let major_version: u16 = read!(reader.read_int());
+    let minor_version: u16 = read!(reader.read_int());
+    if major_version != 3 || minor_version != 0 {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"CBLC",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0003_0000],
+        });
+    }
+
+    let num_sizes: u32 = read!(reader.read_int());
+    let mut strikes = CoreVec::new_in(allocator);
+    for _ in 0..num_sizes {
+        let array_offset = read!(reader.read_int::<u32>()) as usize;
+        let _index_tables_size: u32 = read!(reader.read_int());
+        let num_index_subtables: u32 = read!(reader.read_int());
+        let _color_ref: u32 = read!(reader.read_int());
+
+        // hori + vert SbitLineMetrics
+        let _line_metrics: [u8; 24] = read!(reader.read_array());
+
+        let _start_glyph: u16 = read!(reader.read_int());
+        let _end_glyph: u16 = read!(reader.read_int());
+        let ppem_x: u8 = read!(reader.read_int());
+        let ppem_y: u8 = read!(reader.read_int());
+        let bit_depth: u8 = read!(reader.read_int());
+        let _flags: i8 = read!(reader.read_int());
+
+        let mut glyphs = CoreVec::new_in(allocator);
+        let mut entries_left = usize::from(maxp.num_glyphs());
+        let mut array_reader = reader_at(&table, array_offset, "CBLC::indexSubTableArrayOffset")?;
+        for _ in 0..num_index_subtables {
+            if entries_left == 0 {
+                tracing::event!(
+                    tracing::Level::WARN,
+                    "CBLC strike lists more glyphs than maxp::numGlyphs, ignoring the rest"
+                );
+                break;
+            }
+            let first_glyph: u16 = read!(array_reader.read_int());
+            let last_glyph: u16 = read!(array_reader.read_int());
+            let additional_offset: u32 = read!(array_reader.read_int());
+
+            parse_index_subtable(
+                &table,
+                array_offset.saturating_add(additional_offset as usize),
+                (first_glyph, last_glyph),
+                maxp.num_glyphs(),
+                &mut entries_left,
+                &mut glyphs,
+            )?;
+        }
+        glyphs.sort_by_key(|g| g.glyph_id);
+
+        strikes.push(Strike {
+            ppem_x,
+            ppem_y,
+            bit_depth,
+            glyphs,
+        });
+    }
+
+    Ok(Type { strikes })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tables::Table,
+        testing::{
+            be,
+            head,
+            maxp,
+            sfnt,
+        },
+        FontTrait,
+    };
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    /// `CBLC` with a strike at each `ppem`, of one index subtable covering
+    /// `first_glyph` to `last_glyph`
+    fn cblc_strikes(strikes: &[(u8, (u16, u16), &[u8])]) -> Vec<u8> {
+        let mut bytes = be!(3u16, 0u16, strikes.len() as u32);
+        // BitmapSizes, followed by each index subtable array
+        let mut array_offset = 8 + 48 * strikes.len();
+        for &(ppem, (first_glyph, last_glyph), subtable) in strikes {
+            bytes.extend(be!(
+                array_offset as u32,
+                8 + subtable.len() as u32,
+                1u32,
+                0u32
+            ));
+            bytes.extend([0; 24]);
+            bytes.extend(be!(first_glyph, last_glyph, ppem, ppem, 32u8, 1i8));
+            array_offset += 8 + subtable.len();
+        }
+        for &(_, (first_glyph, last_glyph), subtable) in strikes {
+            bytes.extend(be!(first_glyph, last_glyph, 8u32));
+            bytes.extend_from_slice(subtable);
+        }
+        bytes
+    }
+
+    /// `CBLC` with a single strike at `ppem`, of one index subtable covering
+    /// `first_glyph` to `last_glyph`
+    fn cblc(
+        ppem: u8,
+        glyphs: (u16, u16),
+        subtable: &[u8],
+    ) -> Vec<u8> {
+        cblc_strikes(&[(ppem, glyphs, subtable)])
+    }
+
+    /// `CBDT` holding each of `images` as glyph data of image format 17, one
+    /// after the other
+    fn cbdt_of(images: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = be!(0x0003_0000_u32);
+        for image in images {
+            bytes.extend([136, 128, 0, 0, 136]);
+            bytes.extend(be!(image.len() as u32));
+            bytes.extend_from_slice(image);
+        }
+        bytes
+    }
+
+    /// `CBDT` holding `PNG` as glyph data of image format 17
+    fn cbdt() -> Vec<u8> {
+        cbdt_of(&[PNG])
+    }
+
+    /// `size` pixels square PNG, filled with `color`
+    fn png(
+        size: u32,
+        color: [u8; 4],
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, size, size);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&color.repeat(size as usize * size as usize)))
+            .expect("PNG should encode");
+        bytes
+    }
+
+    #[test]
+    fn color_bitmap_returns_png_data() {
+        // Glyph 1's data is the whole of `CBDT` after its header
+        let length = cbdt().len() as u32 - 4;
+        let subtable = be!(1u16, 17u16, 4u32, 0u32, length);
+        let font = sfnt(&[
+            (*b"head", &head(2048, 0, true)),
+            (*b"maxp", &maxp(2)),
+            (*b"CBLC", &cblc(109, (1, 1), &subtable)),
+            (*b"CBDT", &cbdt()),
+        ]);
+
+        let font = crate::parse_font_bytes(&font).expect("Font should parse");
+        let png = |ppem| font.color_bitmap(1, ppem).map(|bitmap| bitmap.png);
+        assert_eq!(png(109), Some(PNG));
+        assert_eq!(png(20), Some(PNG));
+        assert_eq!(font.color_bitmap(0, 109), None);
+    }
+
+    #[test]
+    fn fixed_size_ranges_are_bounded_by_maxp() {
+        let length = cbdt().len() as u32 - 4;
+        let mut subtable = be!(2u16, 17u16, 4u32, length);
+        subtable.extend([0; 8]);
+        let font = sfnt(&[
+            (*b"head", &head(2048, 0, true)),
+            (*b"maxp", &maxp(2)),
+            (*b"CBLC", &cblc(109, (0, u16::MAX), &subtable)),
+            (*b"CBDT", &cbdt()),
+        ]);
+
+        let font = crate::parse_font_bytes(&font).expect("Font should parse");
+        let cblc = font
+            .tables()
+            .iter()
+            .find_map(|t| match t {
+                Table::Cblc(cblc) => Some(cblc),
+                _ => None,
+            })
+            .expect("CBLC should parse");
+        assert_eq!(cblc.strikes[0].glyphs.len(), 2);
+        assert_eq!(font.color_bitmap(0, 109).map(|bitmap| bitmap.png), Some(PNG));
+    }
+
+    #[test]
+    fn shared_subtables_are_bounded_by_maxp() {
+        // Every one of many index subtables points at the same format 1
+        // subtable of empty images, which would otherwise be read for each
+        const SUBTABLES: u32 = 10_000;
+        let num_glyphs: u16 = 1000;
+
+        let mut bytes = be!(3u16, 0u16, 1u32);
+        bytes.extend(be!(56u32, 0u32, SUBTABLES, 0u32));
+        bytes.extend([0; 24]);
+        bytes.extend(be!(0u16, num_glyphs - 1, 109u8, 109u8, 32u8, 1i8));
+        for _ in 0..SUBTABLES {
+            bytes.extend(be!(0u16, num_glyphs - 1, 8 * SUBTABLES));
+        }
+        bytes.extend(be!(1u16, 17u16, 4u32));
+        bytes.extend((0..=num_glyphs).flat_map(|_| 0u32.to_be_bytes()));
+
+        let font = sfnt(&[
+            (*b"head", &head(2048, 0, true)),
+            (*b"maxp", &maxp(num_glyphs)),
+            (*b"CBLC", &bytes),
+            (*b"CBDT", &cbdt()),
+        ]);
+        let font = crate::parse_font_bytes(&font).expect("Font should parse");
+        assert_eq!(font.color_bitmap(1, 109), None);
+    }
+
+    #[test]
+    fn sizes_between_strikes_scale_the_larger() {
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        const BLUE: [u8; 4] = [0, 0, 255, 255];
+        let (small, large) = (png(8, RED), png(32, BLUE));
+
+        // Glyph 1's image in each strike, one after the other in `CBDT`
+        let small_end = 9 + small.len() as u32;
+        let large_end = small_end + 9 + large.len() as u32;
+        let small_subtable = be!(1u16, 17u16, 4u32, 0u32, small_end);
+        let large_subtable = be!(1u16, 17u16, 4u32, small_end, large_end);
+        let font = sfnt(&[
+            (*b"head", &head(2048, 0, true)),
+            (*b"maxp", &maxp(2)),
+            (
+                *b"CBLC",
+                &cblc_strikes(&[(8, (1, 1), &small_subtable), (32, (1, 1), &large_subtable)]),
+            ),
+            (*b"CBDT", &cbdt_of(&[&small, &large])),
+        ]);
+        let font = crate::parse_font_bytes(&font).expect("Font should parse");
+
+        let bitmap = font.color_bitmap(1, 20).expect("Glyph should have a bitmap");
+        assert_eq!((bitmap.strike_ppem, bitmap.ppem), (32, 20));
+        assert_eq!(bitmap.png, large.as_slice());
+        assert!((bitmap.scale() - 0.625).abs() < f32::EPSILON);
+
+        let image = bitmap.decode(alloc::alloc::Global).expect("PNG should decode");
+        assert_eq!((image.width, image.height), (20, 20));
+        assert!(image.pixels.iter().all(|pixel| <[u8; 4]>::from(*pixel) == BLUE));
+
+        // Exact and past the largest strike
+        let bitmap = font.color_bitmap(1, 8).expect("Glyph should have a bitmap");
+        assert_eq!((bitmap.strike_ppem, bitmap.png), (8, small.as_slice()));
+        let bitmap = font.color_bitmap(1, 48).expect("Glyph should have a bitmap");
+        assert_eq!(bitmap.strike_ppem, 32);
+        let image = bitmap.decode(alloc::alloc::Global).expect("PNG should decode");
+        assert_eq!((image.width, image.height), (48, 48));
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreReadError,
+        CoreVec,
+        SliceReader,
+        ValidType,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Ranges of the table holding each object of an INDEX
+type Index<A> = CoreVec<core::range::Range<usize>, A>;
+
+/// Top DICT operator locating the `CharStrings` INDEX
+const OP_CHAR_STRINGS: u16 = 17;
+/// Top DICT operator for the charstring format, escaped
+const OP_CHARSTRING_TYPE: u16 = 0x0c06;
+
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    data:         CoreVec<u8, A>,
+    /// Ranges of `data` holding each glyph's Type 2 charstring
+    charstrings:  Index<A>,
+    /// Ranges of `data` holding the global subroutines
+    global_subrs: Index<A>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    #[must_use]
+    pub const fn num_glyphs(&self) -> usize {
+        self.charstrings.len()
+    }
+
+    /// Undecoded Type 2 charstring of `glyph_id`
+    pub fn charstring(
+        &self,
+        glyph_id: u32,
+    ) -> Option<&[u8]> {
+        let range = self.charstrings.get(glyph_id as usize)?;
+        self.data.get(range.start..range.end)
+    }
+
+    pub fn global_subr(
+        &self,
+        index: usize,
+    ) -> Option<&[u8]> {
+        let range = self.global_subrs.get(index)?;
+        self.data.get(range.start..range.end)
+    }
+}
+
+/// Reads from the in-memory table, whose only failure is running out of bytes
+macro_rules! read {
+    ($read:expr) => {
+        $read.map_err(|error| match error {
+            CoreReadError::UnexpectedEnd(needed) => ParseError::UnexpectedEop {
+                location: "CFF ",
+                needed,
+            },
+            CoreReadError::Io(never) => match never {},
+        })?
+    };
+}
+
+/// Parses the INDEX at `offset`, returning the range of each of its objects
+/// and the offset just past it
+fn parse_index<A: core::alloc::Allocator, E: core::error::Error>(
+    allocator: A,
+    table: &[u8],
+    offset: usize,
+    location: &'static str,
+) -> Result<(Index<A>, usize), ParseError<E>> {
+    let mut reader = SliceReader::new(table.get(offset..).ok_or(ParseError::Parsing {
+        variable: location,
+        expected: ValidType::USize(table.len()),
+        parsed:   ValidType::USize(offset),
+    })?);
+
+    let mut objects = CoreVec::new_in(allocator);
+    let count = usize::from(read!(reader.read_int::<u16>()));
+    if count == 0 {
+        return Ok((objects, offset + 2));
+    }
+
+    let off_size: u8 = read!(reader.read_int());
+    if !(1..=4).contains(&off_size) {
+        return Err(ParseError::Parsing {
+            variable: "INDEX::offSize",
+            expected: ValidType::U8(4),
+            parsed:   ValidType::U8(off_size),
+        });
+    }
+
+    // Offsets count from the byte before the object data
+    let data_start = offset + 3 + (count + 1) * usize::from(off_size) - 1;
+    let mut read_offset = || -> Result<usize, ParseError<E>> {
+        let mut bytes = [0u8; 4];
+        read!(reader.read(&mut bytes[4 - usize::from(off_size)..]));
+        Ok(data_start + u32::from_be_bytes(bytes) as usize)
+    };
+
+    let mut start = read_offset()?;
+    for _ in 0..count {
+        let end = read_offset()?;
+        if end < start || end > table.len() {
+            return Err(ParseError::Parsing {
+                variable: location,
+                expected: ValidType::USize(table.len()),
+                parsed:   ValidType::USize(end),
+            });
+        }
+        objects.push(core::range::Range { start, end });
+        start = end;
+    }
+
+    Ok((objects, start))
+}
+
+/// Last operand of `operator` within a DICT, only handling integers
+fn dict_operand<E: core::error::Error>(
+    dict: &[u8],
+    operator: u16,
+) -> Result<Option<i32>, ParseError<E>> {
+    let mut last = None;
+
+    let mut reader = SliceReader::new(dict);
+    loop {
+        let mut b0 = [0u8; 1];
+        if read!(reader.read(&mut b0)) == 0 {
+            return Ok(None);
+        }
+
+        let operand = match b0[0] {
+            0..=21 => {
+                let op = if b0[0] == 12 {
+                    0x0c00 | u16::from(read!(reader.read_int::<u8>()))
+                } else {
+                    u16::from(b0[0])
+                };
+                if op == operator {
+                    return Ok(last);
+                }
+                last = None;
+                continue;
+            },
+            28 => i32::from(read!(reader.read_int::<i16>())),
+            29 => read!(reader.read_int::<i32>()),
+            // No operator read takes a real, so their value is skipped
+            30 => {
+                loop {
+                    let nibbles: u8 = read!(reader.read_int());
+                    if nibbles & 0x0f == 0x0f || nibbles >> 4 == 0x0f {
+                        break;
+                    }
+                }
+                0
+            },
+            b0 @ 32..=246 => i32::from(b0) - 139,
+            b0 @ 247..=250 => {
+                (i32::from(b0) - 247) * 256 + i32::from(read!(reader.read_int::<u8>())) + 108
+            },
+            b0 @ 251..=254 => {
+                -(i32::from(b0) - 251) * 256 - i32::from(read!(reader.read_int::<u8>())) - 108
+            },
+            reserved => {
+                return Err(ParseError::Parsing {
+                    variable: "DICT::b0",
+                    expected: ValidType::U8(254),
+                    parsed:   ValidType::U8(reserved),
+                })
+            },
+        };
+        last = Some(operand);
+    }
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    // Structures are addressed by offsets from the start of the table
+    let mut data = CoreVec::new_in(allocator);
+    reader.read_to_vec(&mut data)?;
+    let mut reader = SliceReader::new(&data);
+
+    let major_version: u8 = read!(reader.read_int());
+    let minor_version: u8 = read!(reader.read_int());
+    if major_version != 1 {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"CFF ",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0001_0000],
+        });
+    }
+    let header_size: u8 = read!(reader.read_int());
+
+    // Name, Top DICT, String and Global Subr INDEXes follow one another
+    let (_names, offset) = parse_index(allocator, &data, header_size.into(), "CFF::Name")?;
+    let (top_dicts, offset) = parse_index(allocator, &data, offset, "CFF::TopDICT")?;
+    let (_strings, offset) = parse_index(allocator, &data, offset, "CFF::String")?;
+    let (global_subrs, _) = parse_index(allocator, &data, offset, "CFF::GlobalSubr")?;
+
+    // Only the first font of a FontSet is used, as OpenType requires
+    let top_dict = top_dicts
+        .first()
+        .map_or(&[][..], |range| &data[range.start..range.end]);
+
+    let charstring_type = dict_operand(top_dict, OP_CHARSTRING_TYPE)?.unwrap_or(2);
+    if charstring_type != 2 {
+        return Err(ParseError::Parsing {
+            variable: "TopDICT::CharstringType",
+            expected: ValidType::I32(2),
+            parsed:   ValidType::I32(charstring_type),
+        });
+    }
+
+    let charstrings_offset = dict_operand(top_dict, OP_CHAR_STRINGS)?.unwrap_or(0);
+    let charstrings = match usize::try_from(charstrings_offset) {
+        Ok(offset) if offset != 0 => {
+            parse_index(allocator, &data, offset, "TopDICT::CharStrings")?.0
+        },
+        _ => {
+            return Err(ParseError::Parsing {
+                variable: "TopDICT::CharStrings",
+                expected: ValidType::USize(data.len()),
+                parsed:   ValidType::I32(charstrings_offset),
+            })
+        },
+    };
+
+    Ok(Type {
+        data,
+        charstrings,
+        global_subrs,
+    })
+}
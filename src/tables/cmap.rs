@@ -0,0 +1,447 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreReadError,
+        CoreVec,
+        PeekReader,
+        SliceReader,
+        ValidType,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Format 4 range of characters, sorted by `end_code`
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    pub end_code:        u16,
+    pub start_code:      u16,
+    pub id_delta:        i16,
+    /// In bytes, from this segment's entry in the `idRangeOffset` array
+    pub id_range_offset: u16,
+}
+
+/// Format 12 range of characters mapped to consecutive glyphs
+#[derive(Debug, Clone, Copy)]
+pub struct Group {
+    pub start_char:  u32,
+    pub end_char:    u32,
+    pub start_glyph: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Subtable<A: core::alloc::Allocator> {
+    /// Format 4, covering the BMP only
+    SegmentMapping {
+        segments:  CoreVec<Segment, A>,
+        glyph_ids: CoreVec<u16, A>,
+    },
+    /// Format 12
+    SegmentedCoverage { groups: CoreVec<Group, A> },
+}
+
+impl<A: core::alloc::Allocator> Subtable<A> {
+    fn lookup(
+        &self,
+        code: u32,
+    ) -> Option<u32> {
+        match self {
+            Self::SegmentMapping {
+                segments,
+                glyph_ids,
+            } => {
+                let code = u16::try_from(code).ok()?;
+                let index = segments.partition_point(|s| s.end_code < code);
+                let segment = segments.get(index).filter(|s| s.start_code <= code)?;
+
+                let glyph_id = if segment.id_range_offset == 0 {
+                    code.wrapping_add_signed(segment.id_delta)
+                } else {
+                    // Offsets are relative to the segment's own `idRangeOffset`
+                    // entry, which directly precede `glyphIdArray`
+                    let offset = usize::from(segment.id_range_offset / 2)
+                        + usize::from(code - segment.start_code)
+                        + index;
+                    let glyph_id = *glyph_ids.get(offset.checked_sub(segments.len())?)?;
+                    if glyph_id == 0 {
+                        return None;
+                    }
+                    glyph_id.wrapping_add_signed(segment.id_delta)
+                };
+
+                Some(u32::from(glyph_id))
+            },
+            Self::SegmentedCoverage { groups } => {
+                let group = groups
+                    .get(groups.partition_point(|g| g.end_char < code))
+                    .filter(|g| g.start_char <= code)?;
+                group.start_glyph.checked_add(code - group.start_char)
+            },
+        }
+    }
+
+    fn maps_any(
+        &self,
+        first: u32,
+        last: u32,
+    ) -> bool {
+        // Only the characters of ranges overlapping `first..=last` need looking up
+        let overlap_maps = |start: u32, end: u32| {
+            (start.max(first)..=end.min(last)).any(|code| self.lookup(code).is_some_and(|g| g != 0))
+        };
+        match self {
+            Self::SegmentMapping { segments, .. } => segments
+                .iter()
+                .any(|s| overlap_maps(u32::from(s.start_code), u32::from(s.end_code))),
+            Self::SegmentedCoverage { groups } => groups
+                .iter()
+                .any(|g| overlap_maps(g.start_char, g.end_char)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    /// Taken from a `(3, 0)` encoding record, whose characters are offset
+    /// into the private use area
+    pub symbol:   bool,
+    /// [`FontOptions::preferred_cmap`] or else the best Unicode subtable,
+    /// `None` when the font has none in a supported format
+    pub subtable: Option<Subtable<A>>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    /// Glyph mapped to `c`, `None` when unmapped or mapped to `.notdef`
+    pub fn glyph_id(
+        &self,
+        c: char,
+    ) -> Option<u32> {
+        let subtable = self.subtable.as_ref()?;
+
+        let glyph_id = subtable.lookup(u32::from(c)).or_else(|| {
+            // Symbol fonts conventionally move their 8-bit codes to U+F0xx
+            (self.symbol && u32::from(c) <= 0xff)
+                .then(|| subtable.lookup(0xf000 | u32::from(c)))
+                .flatten()
+        })?;
+        (glyph_id != 0).then_some(glyph_id)
+    }
+
+    /// Whether any character of `first..=last` maps to a glyph. Unlike
+    /// [`Type::glyph_id`], a symbol font's characters are only found at U+F0xx
+    #[must_use]
+    pub fn maps_any(
+        &self,
+        first: u32,
+        last: u32,
+    ) -> bool {
+        self.subtable
+            .as_ref()
+            .is_some_and(|subtable| subtable.maps_any(first, last))
+    }
+}
+
+/// Reads from the in-memory table, whose only failure is running out of bytes
+macro_rules! read {
+    ($read:expr) => {
+        $read.map_err(|error| match error {
+            CoreReadError::UnexpectedEnd(needed) => ParseError::UnexpectedEop {
+                location: "cmap",
+                needed,
+            },
+            CoreReadError::Io(never) => match never {},
+        })?
+    };
+}
+
+/// Preference of an encoding record, lower is better. `None` for encodings
+/// that aren't Unicode
+const fn encoding_rank(
+    platform_id: u16,
+    encoding_id: u16,
+) -> Option<u8> {
+    match (platform_id, encoding_id) {
+        // Full repertoire
+        (0, 4 | 6) | (3, 10) => Some(0),
+        // BMP only
+        (0, 0..=3) | (3, 1) => Some(1),
+        (3, 0) => Some(2),
+        _ => None,
+    }
+}
+
+/// `available` is the number of bytes from the start of the subtable to the end
+/// of `cmap`
+fn parse_segment_mapping<A: core::alloc::Allocator + Copy, R, E: core::error::Error>(
+    allocator: A,
+    reader: &mut R,
+    available: usize,
+) -> Result<Subtable<A>, ParseError<E>>
+where
+    R: CoreRead<IoError = core::convert::Infallible>,
+{
+    let _format: u16 = read!(reader.read_int());
+    let length: u16 = read!(reader.read_int());
+    let _language: u16 = read!(reader.read_int());
+    let seg_count = usize::from(read!(reader.read_int::<u16>()) / 2);
+    // searchRange, entrySelector, rangeShift
+    let _search: [u8; 6] = read!(reader.read_array());
+
+    // The four segment arrays must fit, whatever `length` claims
+    if 16 + seg_count * 8 > available {
+        return Err(ParseError::Parsing {
+            variable: "cmap4::segCountX2",
+            expected: ValidType::USize(available.saturating_sub(16) / 8 * 2),
+            parsed:   ValidType::USize(seg_count * 2),
+        });
+    }
+
+    let mut segments = CoreVec::with_capacity_in(seg_count, allocator);
+    for _ in 0..seg_count {
+        segments.push(Segment {
+            end_code:        read!(reader.read_int()),
+            start_code:      0,
+            id_delta:        0,
+            id_range_offset: 0,
+        });
+    }
+    let _reserved_pad: u16 = read!(reader.read_int());
+    for segment in &mut segments {
+        segment.start_code = read!(reader.read_int());
+    }
+    for segment in &mut segments {
+        segment.id_delta = read!(reader.read_int());
+    }
+    for segment in &mut segments {
+        segment.id_range_offset = read!(reader.read_int());
+    }
+
+    // `length` overflows for large subtables, so a short read just ends the
+    // array
+    let num_glyph_ids = usize::from(length)
+        .min(available)
+        .saturating_sub(16 + seg_count * 8)
+        / 2;
+    let mut glyph_ids = CoreVec::with_capacity_in(num_glyph_ids, allocator);
+    for _ in 0..num_glyph_ids {
+        let Ok(glyph_id) = reader.read_int() else {
+            break;
+        };
+        glyph_ids.push(glyph_id);
+    }
+
+    Ok(Subtable::SegmentMapping {
+        segments,
+        glyph_ids,
+    })
+}
+
+/// `available` is the number of bytes from the start of the subtable to the end
+/// of `cmap`
+fn parse_segmented_coverage<A: core::alloc::Allocator + Copy, R, E: core::error::Error>(
+    allocator: A,
+    reader: &mut R,
+    available: usize,
+) -> Result<Subtable<A>, ParseError<E>>
+where
+    R: CoreRead<IoError = core::convert::Infallible>,
+{
+    let _format: u16 = read!(reader.read_int());
+    let _reserved: u16 = read!(reader.read_int());
+    let length = read!(reader.read_int::<u32>()) as usize;
+    let _language: u32 = read!(reader.read_int());
+    let num_groups: u32 = read!(reader.read_int());
+
+    if length > available {
+        return Err(ParseError::Parsing {
+            variable: "cmap12::length",
+            expected: ValidType::USize(available),
+            parsed:   ValidType::USize(length),
+        });
+    }
+    let max_groups = length.saturating_sub(16) / 12;
+    if num_groups as usize > max_groups {
+        return Err(ParseError::Parsing {
+            variable: "cmap12::numGroups",
+            expected: ValidType::USize(max_groups),
+            parsed:   ValidType::U32(num_groups),
+        });
+    }
+
+    let mut groups = CoreVec::with_capacity_in(num_groups as usize, allocator);
+    for _ in 0..num_groups {
+        groups.push(Group {
+            start_char:  read!(reader.read_int()),
+            end_char:    read!(reader.read_int()),
+            start_glyph: read!(reader.read_int()),
+        });
+    }
+
+    Ok(Subtable::SegmentedCoverage { groups })
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    // Subtables are addressed by offsets from the start of the table
+    let mut table = CoreVec::new_in(allocator);
+    reader.read_to_vec(&mut table)?;
+    let mut reader = SliceReader::new(&table);
+
+    let version: u16 = read!(reader.read_int());
+    if version != 0 {
+        return Err(ParseError::UnsupportedVersion {
+            tag: *b"cmap",
+            version: u32::from(version),
+            supported: &[0],
+        });
+    }
+
+    let num_tables: u16 = read!(reader.read_int());
+    let mut records = CoreVec::with_capacity_in(usize::from(num_tables), allocator);
+    for _ in 0..num_tables {
+        let platform_id: u16 = read!(reader.read_int());
+        let encoding_id: u16 = read!(reader.read_int());
+        let offset = read!(reader.read_int::<u32>()) as usize;
+
+        // Falling back to the best Unicode subtable after the preferred one
+        let rank = if options.preferred_cmap == Some((platform_id, encoding_id)) {
+            Some(0)
+        } else {
+            encoding_rank(platform_id, encoding_id).map(|rank| rank + 1)
+        };
+        if let Some(rank) = rank {
+            records.push((rank, platform_id, encoding_id, offset));
+        }
+    }
+    records.sort_by_key(|(rank, ..)| *rank);
+
+    for (_, platform_id, encoding_id, offset) in records {
+        let subtable_bytes = table.get(offset..).ok_or(ParseError::Parsing {
+            variable: "EncodingRecord::subtableOffset",
+            expected: ValidType::USize(table.len()),
+            parsed:   ValidType::USize(offset),
+        })?;
+        let mut subtable_reader = SliceReader::new(subtable_bytes);
+        let mut subtable_reader = PeekReader::<_, 2>::new(&mut subtable_reader);
+
+        let mut format = [0u8; 2];
+        read!(subtable_reader.peek(&mut format));
+
+        let subtable = match u16::from_be_bytes(format) {
+            4 => parse_segment_mapping(allocator, &mut subtable_reader, subtable_bytes.len())?,
+            12 => parse_segmented_coverage(allocator, &mut subtable_reader, subtable_bytes.len())?,
+            format => {
+                tracing::event!(
+                    tracing::Level::TRACE,
+                    "Skipping ({platform_id}, {encoding_id}) subtable of format {format}"
+                );
+                continue;
+            },
+        };
+
+        return Ok(Type {
+            symbol:   (platform_id, encoding_id) == (3, 0),
+            subtable: Some(subtable),
+        });
+    }
+
+    tracing::event!(tracing::Level::WARN, "No supported Unicode cmap subtable");
+    Ok(Type {
+        symbol:   false,
+        subtable: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{be, cmap, cmap4};
+
+    fn parse(
+        table: &[u8],
+        preferred_cmap: Option<(u16, u16)>,
+    ) -> Type<alloc::alloc::Global> {
+        let options = FontOptions {
+            preferred_cmap,
+            ..FontOptions::default()
+        };
+        parse_table(alloc::alloc::Global, &options, &[], &mut SliceReader::new(table))
+            .expect("cmap should parse")
+    }
+
+    #[test]
+    fn preferred_subtable_is_used() {
+        let table = cmap(&[(0, 3, cmap4(&[('A', 1)])), (3, 1, cmap4(&[('A', 2)]))]);
+
+        assert_eq!(parse(&table, None).glyph_id('A'), Some(1));
+        assert_eq!(parse(&table, Some((0, 3))).glyph_id('A'), Some(1));
+        assert_eq!(parse(&table, Some((3, 1))).glyph_id('A'), Some(2));
+        // Missing from the font
+        assert_eq!(parse(&table, Some((3, 10))).glyph_id('A'), Some(1));
+    }
+
+    #[test]
+    fn unsupported_preferred_subtable_falls_back() {
+        // Format 0 isn't supported
+        let mut byte_encoding = be!(0u16, 262u16, 0u16);
+        byte_encoding.extend([0; 256]);
+        let table = cmap(&[(1, 0, byte_encoding), (3, 1, cmap4(&[('A', 2)]))]);
+
+        assert_eq!(parse(&table, Some((1, 0))).glyph_id('A'), Some(2));
+    }
+
+    #[test]
+    fn subtables_past_the_end_are_rejected() {
+        let parse = |table: &[u8]| {
+            parse_table::<_, SliceReader>(
+                alloc::alloc::Global,
+                &FontOptions::default(),
+                &[],
+                &mut SliceReader::new(table),
+            )
+        };
+
+        let mut table = cmap(&[(3, 1, cmap4(&[('A', 1)]))]);
+        let past_end = u32::try_from(table.len() + 4).expect("Table too large");
+        table[8..12].copy_from_slice(&past_end.to_be_bytes());
+        assert!(matches!(
+            parse(&table),
+            Err(ParseError::Parsing {
+                variable: "EncodingRecord::subtableOffset",
+                ..
+            })
+        ));
+
+        // Segment arrays running past the end
+        let mut table = cmap(&[(3, 1, cmap4(&[('A', 1)]))]);
+        table[12 + 6..12 + 8].copy_from_slice(&be!(200u16));
+        assert!(matches!(
+            parse(&table),
+            Err(ParseError::Parsing {
+                variable: "cmap4::segCountX2",
+                ..
+            })
+        ));
+
+        // Groups running past the end
+        let groups = be!(12u16, 0u16, 40u32, 0u32, 2u32, 0x41u32, 0x41u32, 1u32);
+        assert!(matches!(
+            parse(&cmap(&[(3, 10, groups)])),
+            Err(ParseError::Parsing {
+                variable: "cmap12::length",
+                ..
+            })
+        ));
+    }
+}
@@ -7,15 +7,22 @@ use crate::{
         CoreRead,
         CoreVec,
         TrackingReader,
+        ValidType,
     },
+    FontOptions,
     ParseError,
 };
 
 pub type ParsedType<A> = CoreVec<Glyph<A>, A>;
 
+/// Composite glyphs nested deeper than this aren't followed
+pub const MAX_COMPONENT_DEPTH: usize = 16;
+
 pub struct Flags;
 impl Flags {
     pub const ON_CURVE: u8 = 1 << 0;
+    // Only meaningful on the first flag of a glyph
+    pub const OVERLAP_SIMPLE: u8 = 1 << 6;
     pub const REPEAT: u8 = 1 << 3;
     pub const X_SHORT: u8 = 1 << 1;
     // Determines sign if Short, to skip if Long
@@ -24,6 +31,42 @@ impl Flags {
     pub const Y_SIGN_SKIP: u8 = 1 << 5;
 }
 
+pub struct ComponentFlags;
+impl ComponentFlags {
+    pub const ARGS_ARE_WORDS: u16 = 1 << 0;
+    // Offsets if set, otherwise point numbers to align
+    pub const ARGS_ARE_XY_VALUES: u16 = 1 << 1;
+    pub const MORE_COMPONENTS: u16 = 1 << 5;
+    pub const OVERLAP_COMPOUND: u16 = 1 << 10;
+    pub const ROUND_XY_TO_GRID: u16 = 1 << 2;
+    pub const SCALE: u16 = 1 << 3;
+    pub const TWO_BY_TWO: u16 = 1 << 7;
+    pub const USE_MY_METRICS: u16 = 1 << 9;
+    pub const WE_HAVE_INSTRUCTIONS: u16 = 1 << 8;
+    pub const X_AND_Y_SCALE: u16 = 1 << 6;
+}
+
+/// Glyph placed within a composite glyph
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Component {
+    pub glyph_id:  u16,
+    pub flags:     u16,
+    /// (x, y) offset in font units with [`ComponentFlags::ARGS_ARE_XY_VALUES`],
+    /// otherwise the (composite, component) point numbers to align
+    pub arguments: (i32, i32),
+    /// [xx, xy, yx, yy] applied to the component before it's offset
+    pub transform: [f32; 4],
+}
+
+impl Component {
+    /// Whether the composite takes its advance and left side bearing from this
+    /// component instead of its own `hmtx` entry
+    #[must_use]
+    pub const fn use_my_metrics(&self) -> bool {
+        self.flags & ComponentFlags::USE_MY_METRICS != 0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Glyph<A: core::alloc::Allocator> {
     pub num_contours: i16,
@@ -32,10 +75,385 @@ pub struct Glyph<A: core::alloc::Allocator> {
     pub y_bounds: core::range::RangeInclusive<i16>,
 
     // Simple Glyph
-    pub end_pts: CoreVec<u16, A>,
+    pub end_pts:     CoreVec<u16, A>,
     // (x, y, on_curve)
-    pub points:  CoreVec<(i16, i16, bool), A>,
-    // instructions: io::CoreVec<u8, A>, // TODO: Parse bytecodes
+    pub points:      CoreVec<(i16, i16, bool), A>,
+    // Contours may overlap, so fills must use the non-zero winding rule
+    pub overlapping: bool,
+    // Hinting bytecode, only kept with `FontOptions::keep_instructions`
+    pub instructions: CoreVec<u8, A>,
+
+    // Composite Glyph, whose outline is still `.notdef`'s
+    pub components: CoreVec<Component, A>,
+}
+
+impl<A: core::alloc::Allocator> Glyph<A> {
+    pub const fn empty(allocator: A) -> Self
+    where
+        A: Copy,
+    {
+        Self {
+            num_contours: 0,
+            x_bounds:     core::range::RangeInclusive { start: 0, last: 0 },
+            y_bounds:     core::range::RangeInclusive { start: 0, last: 0 },
+            end_pts:      CoreVec::new_in(allocator),
+            points:       CoreVec::new_in(allocator),
+            overlapping:  false,
+            instructions: CoreVec::new_in(allocator),
+            components:   CoreVec::new_in(allocator),
+        }
+    }
+
+    /// `(x_min, y_min, x_max, y_max)` in font units. A composite's bounds
+    /// cover its placed components rather than its placeholder outline
+    #[must_use]
+    pub const fn bounds(&self) -> (i16, i16, i16, i16) {
+        (
+            self.x_bounds.start,
+            self.y_bounds.start,
+            self.x_bounds.last,
+            self.y_bounds.last,
+        )
+    }
+
+    /// Undecoded TrueType instructions, empty unless parsed with
+    /// [`FontOptions::keep_instructions`]
+    pub fn instructions(&self) -> &[u8] {
+        &self.instructions
+    }
+
+    /// Glyphs making up a composite glyph, empty for simple glyphs
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Glyph id of the component with [`ComponentFlags::USE_MY_METRICS`],
+    /// whose horizontal metrics the composite uses
+    #[must_use]
+    pub fn metrics_component(&self) -> Option<u16> {
+        self.components
+            .iter()
+            .find(|component| component.use_my_metrics())
+            .map(|component| component.glyph_id)
+    }
+
+    /// Iterates over each contour, resolving the stored point deltas into
+    /// absolute coordinates
+    pub fn contours(
+        &self
+    ) -> impl Iterator<Item = impl ExactSizeIterator<Item = (i16, i16, bool)> + Clone + '_> + '_
+    {
+        let mut start = 0;
+        let mut origin = (0i16, 0i16);
+        self.end_pts.iter().map(move |end| {
+            let points = self
+                .points
+                .get(start..=usize::from(*end))
+                .unwrap_or_default();
+            start = usize::from(*end) + 1;
+
+            let (mut x, mut y) = origin;
+            for (dx, dy, _) in points {
+                origin = (origin.0.wrapping_add(*dx), origin.1.wrapping_add(*dy));
+            }
+
+            points.iter().map(move |(dx, dy, on_curve)| {
+                x = x.wrapping_add(*dx);
+                y = y.wrapping_add(*dy);
+                (x, y, *on_curve)
+            })
+        })
+    }
+
+    /// Returns a copy with the on-curve midpoint between consecutive off-curve
+    /// points made explicit, and every contour starting on an on-curve point.
+    /// Implied points are rounded towards zero, and instructions are dropped
+    #[must_use]
+    pub fn reconstruct_implied_points(
+        &self,
+        allocator: A,
+    ) -> Self
+    where
+        A: Copy,
+    {
+        let midpoint = |a: (i16, i16, bool), b: (i16, i16, bool)| {
+            (i16::midpoint(a.0, b.0), i16::midpoint(a.1, b.1), true)
+        };
+
+        let mut end_pts = CoreVec::with_capacity_in(self.end_pts.len(), allocator);
+        let mut points = CoreVec::with_capacity_in(self.points.len(), allocator);
+
+        for contour in self.contours() {
+            let len = contour.len();
+            let (start, skip, take) = match (contour.clone().next(), contour.clone().last()) {
+                (Some(first @ (_, _, true)), _) => (first, 1, len - 1),
+                (Some(_), Some(last @ (_, _, true))) => (last, 0, len - 1),
+                (Some(first), Some(last)) => (midpoint(last, first), 0, len),
+                _ => continue,
+            };
+
+            points.push(start);
+            for point in contour.skip(skip).take(take) {
+                if let (Some(prev @ (_, _, false)), false) = (points.last().copied(), point.2) {
+                    points.push(midpoint(prev, point));
+                }
+                points.push(point);
+            }
+
+            // Inserted points can push very large glyphs past what `end_pts`
+            // can index
+            end_pts.push(u16::try_from(points.len() - 1).unwrap_or(u16::MAX));
+        }
+
+        to_deltas(&mut points);
+
+        let mut components = CoreVec::with_capacity_in(self.components.len(), allocator);
+        components.extend_from_slice(&self.components);
+
+        Self {
+            num_contours: i16::try_from(end_pts.len()).unwrap_or(i16::MAX),
+            x_bounds: self.x_bounds,
+            y_bounds: self.y_bounds,
+            end_pts,
+            points,
+            overlapping: self.overlapping,
+            // Instructions address points by index, which no longer match
+            instructions: CoreVec::new_in(allocator),
+            components,
+        }
+    }
+
+    /// Iterates over the line and quadratic segments of each contour
+    pub fn segments(&self) -> impl Iterator<Item = impl Iterator<Item = Segment> + '_> + '_ {
+        self.contours().map(Segments::new)
+    }
+
+    /// Approximates the outline with straight lines, scaled by `scale`. Curves
+    /// are split until they stray at most `flatness` (after scaling) from
+    /// their lines. Each contour's lines form a closed loop
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn flattened_segments(
+        &self,
+        scale: f32,
+        flatness: f32,
+    ) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+        // Bounds work on degenerate input, e.g. a flatness of 0
+        const MAX_STEPS: u32 = 256;
+
+        self.segments().flatten().flat_map(move |segment| {
+            let scaled = |(x, y): (f32, f32)| (x * scale, y * scale);
+            // Lines are quads with the control point at their middle, so they
+            // don't deviate and stay a single step
+            let (start, control, end) = match segment {
+                Segment::Line(start, end) => (
+                    scaled(start),
+                    scaled((start.0.midpoint(end.0), start.1.midpoint(end.1))),
+                    scaled(end),
+                ),
+                Segment::Quad(start, control, end) => {
+                    (scaled(start), scaled(control), scaled(end))
+                },
+            };
+
+            // Distance between the curve's midpoint and its chord, which
+            // shrinks with the square of the number of steps
+            let deviation = 2.0f32.mul_add(-control.0, start.0 + end.0).hypot(2.0f32.mul_add(
+                -control.1,
+                start.1 + end.1,
+            )) / 4.0;
+            let steps = ((deviation / flatness).sqrt().ceil() as u32).clamp(1, MAX_STEPS);
+
+            let point = move |t: f32| {
+                let (a, b, c) = ((1.0 - t) * (1.0 - t), 2.0 * (1.0 - t) * t, t * t);
+                (
+                    c.mul_add(end.0, a.mul_add(start.0, b * control.0)),
+                    c.mul_add(end.1, a.mul_add(start.1, b * control.1)),
+                )
+            };
+            (0..steps).map(move |step| {
+                (
+                    point(step as f32 / steps as f32),
+                    point((step + 1) as f32 / steps as f32),
+                )
+            })
+        })
+    }
+
+    /// Converts the outline to cubic beziers by degree elevation of each
+    /// quadratic segment
+    pub fn to_cubic_path(
+        &self,
+        allocator: A,
+    ) -> CoreVec<PathCmd, A> {
+        let mut path = CoreVec::new_in(allocator);
+
+        for contour in self.segments() {
+            let mut contour = contour.peekable();
+            let Some(start) = contour.peek().map(Segment::start) else {
+                continue;
+            };
+
+            path.push(PathCmd::MoveTo(start));
+            path.extend(contour.map(|segment| match segment {
+                Segment::Line(_, end) => PathCmd::LineTo(end),
+                Segment::Quad(start, control, end) => PathCmd::CurveTo(
+                    (
+                        (control.0 - start.0).mul_add(2.0 / 3.0, start.0),
+                        (control.1 - start.1).mul_add(2.0 / 3.0, start.1),
+                    ),
+                    (
+                        (control.0 - end.0).mul_add(2.0 / 3.0, end.0),
+                        (control.1 - end.1).mul_add(2.0 / 3.0, end.1),
+                    ),
+                    end,
+                ),
+            }));
+            path.push(PathCmd::Close);
+        }
+
+        path
+    }
+}
+
+/// Outline segment in font units, with implied on-curve points resolved
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    Line((f32, f32), (f32, f32)),
+    /// (start, control, end)
+    Quad((f32, f32), (f32, f32), (f32, f32)),
+}
+
+impl Segment {
+    #[must_use]
+    pub const fn start(&self) -> (f32, f32) {
+        match self {
+            Self::Line(start, _) | Self::Quad(start, ..) => *start,
+        }
+    }
+
+    #[must_use]
+    pub const fn end(&self) -> (f32, f32) {
+        match self {
+            Self::Line(_, end) | Self::Quad(.., end) => *end,
+        }
+    }
+}
+
+/// Command of a cubic bezier path, in font units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCmd {
+    MoveTo((f32, f32)),
+    LineTo((f32, f32)),
+    /// (control 1, control 2, end)
+    CurveTo((f32, f32), (f32, f32), (f32, f32)),
+    Close,
+}
+
+/// Walks a contour's points, synthesizing the on-curve midpoint between
+/// consecutive off-curve points
+pub struct Segments<I: Iterator<Item = (i16, i16, bool)>> {
+    points:  I,
+    start:   (f32, f32),
+    prev:    (f32, f32),
+    control: Option<(f32, f32)>,
+    closed:  bool,
+}
+
+impl<I: ExactSizeIterator<Item = (i16, i16, bool)> + Clone> Segments<core::iter::Take<core::iter::Skip<I>>> {
+    fn new(contour: I) -> Self {
+        let len = contour.len();
+        let to_f32 = |(x, y, _): (i16, i16, bool)| (f32::from(x), f32::from(y));
+
+        // Contours may start on an off-curve point, in which case the last
+        // point (or the midpoint to it) is where the contour begins
+        let (start, skip, take) = match (contour.clone().next(), contour.clone().last()) {
+            (Some(first @ (_, _, true)), _) => (to_f32(first), 1, len - 1),
+            (Some(_), Some(last @ (_, _, true))) => (to_f32(last), 0, len - 1),
+            (Some(first), Some(last)) => (midpoint(to_f32(first), to_f32(last)), 0, len),
+            _ => ((0.0, 0.0), 0, 0),
+        };
+
+        Self {
+            points: contour.skip(skip).take(take),
+            start,
+            prev: start,
+            control: None,
+            closed: len == 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = (i16, i16, bool)>> Iterator for Segments<I> {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((x, y, on_curve)) = self.points.next() else {
+                if self.closed {
+                    return None;
+                }
+                self.closed = true;
+
+                return Some(match self.control.take() {
+                    Some(control) => Segment::Quad(self.prev, control, self.start),
+                    None => Segment::Line(self.prev, self.start),
+                });
+            };
+            let point = (f32::from(x), f32::from(y));
+
+            if on_curve {
+                let prev = core::mem::replace(&mut self.prev, point);
+                return Some(self.control.take().map_or(
+                    Segment::Line(prev, point),
+                    |control| Segment::Quad(prev, control, point),
+                ));
+            }
+
+            if let Some(control) = self.control.replace(point) {
+                let implied = midpoint(control, point);
+                let prev = core::mem::replace(&mut self.prev, implied);
+                return Some(Segment::Quad(prev, control, implied));
+            }
+        }
+    }
+}
+
+const fn midpoint(
+    a: (f32, f32),
+    b: (f32, f32),
+) -> (f32, f32) {
+    (f32::midpoint(a.0, b.0), f32::midpoint(a.1, b.1))
+}
+
+/// Converts absolute points back to the stored deltas
+fn to_deltas(points: &mut [(i16, i16, bool)]) {
+    let mut prev = (0i16, 0i16);
+    for (x, y, _) in points {
+        let absolute = (*x, *y);
+        (*x, *y) = (x.wrapping_sub(prev.0), y.wrapping_sub(prev.1));
+        prev = absolute;
+    }
+}
+
+// Manual impl as deriving would require `A: PartialEq`. Only the outline is
+// compared, as instructions depend on the options the font was parsed with
+impl<A: core::alloc::Allocator> PartialEq for Glyph<A> {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.num_contours == other.num_contours
+            && self.x_bounds == other.x_bounds
+            && self.y_bounds == other.y_bounds
+            && self.end_pts == other.end_pts
+            && self.points == other.points
+            && self.overlapping == other.overlapping
+            && self.components == other.components
+    }
 }
 
 macro_rules! read_coords {
@@ -62,8 +480,257 @@ macro_rules! read_coords {
     }};
 }
 
+fn parse_simple<A: core::alloc::Allocator + Copy, R: CoreRead>(
+    allocator: A,
+    options: &FontOptions,
+    total_points: &mut usize,
+    reader: &mut R,
+    num_contours: i16,
+    x_bounds: core::range::RangeInclusive<i16>,
+    y_bounds: core::range::RangeInclusive<i16>,
+) -> Result<Glyph<A>, ParseError<R::IoError>> {
+    // Read end_pts bytes
+    let mut end_pts = CoreVec::with_capacity_in(
+        usize::try_from(num_contours).expect("Signed to Unsigned cast failed"),
+        allocator,
+    );
+    for _ in 0..num_contours {
+        end_pts.push(reader.read_int()?);
+    }
+
+    let num_instructions: u16 = reader.read_int()?;
+    let mut instructions = CoreVec::new_in(allocator);
+    if options.keep_instructions {
+        instructions.reserve_exact(usize::from(num_instructions));
+        for _ in 0..num_instructions {
+            instructions.push(reader.read_int()?);
+        }
+    } else {
+        reader.skip(usize::from(num_instructions))?;
+    }
+
+    // flags has to be handled manually as we need to duplicate the repeats
+    let num_points = end_pts.last().map_or(0, |end| usize::from(*end) + 1);
+
+    *total_points += num_points;
+    if let Some(limit) = options.max_total_points.filter(|limit| *total_points > *limit) {
+        return Err(ParseError::BudgetExceeded {
+            budget: "max_total_points",
+            limit,
+        });
+    }
+
+    let mut flags_vec = CoreVec::with_capacity_in(num_points, allocator);
+    while flags_vec.len() < num_points {
+        let flags: u8 = reader.read_int()?;
+
+        let mut repeat = 1;
+        if flags & Flags::REPEAT != 0 {
+            repeat += u16::from(reader.read_int::<u8>()?);
+        }
+
+        for _ in 0..repeat {
+            flags_vec.push(flags & !Flags::REPEAT);
+        }
+    }
+    flags_vec.truncate(num_points);
+    let overlapping = flags_vec
+        .first()
+        .is_some_and(|flags| flags & Flags::OVERLAP_SIMPLE != 0);
+    flags_vec.shrink_to_fit();
+
+    // in the quest for lower LOC count
+    let x_coords = read_coords!(allocator reader flags_vec X);
+    let y_coords = read_coords!(allocator reader flags_vec Y);
+
+    let mut points = CoreVec::with_capacity_in(num_points, allocator);
+    points.extend(
+        itertools::izip!(flags_vec, x_coords, y_coords)
+            .map(|(f, x, y)| (x, y, f & Flags::ON_CURVE != 0)),
+    );
+
+    Ok(Glyph {
+        num_contours,
+        x_bounds,
+        y_bounds,
+        end_pts,
+        points,
+        overlapping,
+        instructions,
+        components: CoreVec::new_in(allocator),
+    })
+}
+
+/// Reads 2.14 fixed point
+fn read_f2dot14<R: CoreRead>(reader: &mut R) -> Result<f32, ParseError<R::IoError>> {
+    Ok(f32::from(reader.read_int::<i16>()?) / 16384.0)
+}
+
+#[allow(clippy::type_complexity)]
+fn parse_components<A: core::alloc::Allocator + Copy, R: CoreRead>(
+    allocator: A,
+    options: &FontOptions,
+    reader: &mut R,
+) -> Result<(CoreVec<Component, A>, CoreVec<u8, A>), ParseError<R::IoError>> {
+    let mut components = CoreVec::new_in(allocator);
+    loop {
+        let flags: u16 = reader.read_int()?;
+        let glyph_id: u16 = reader.read_int()?;
+
+        let arguments = match (
+            flags & ComponentFlags::ARGS_ARE_WORDS != 0,
+            flags & ComponentFlags::ARGS_ARE_XY_VALUES != 0,
+        ) {
+            (true, true) => (
+                i32::from(reader.read_int::<i16>()?),
+                i32::from(reader.read_int::<i16>()?),
+            ),
+            (true, false) => (
+                i32::from(reader.read_int::<u16>()?),
+                i32::from(reader.read_int::<u16>()?),
+            ),
+            (false, true) => (
+                i32::from(reader.read_int::<i8>()?),
+                i32::from(reader.read_int::<i8>()?),
+            ),
+            (false, false) => (
+                i32::from(reader.read_int::<u8>()?),
+                i32::from(reader.read_int::<u8>()?),
+            ),
+        };
+
+        let transform = if flags & ComponentFlags::SCALE != 0 {
+            let scale = read_f2dot14(reader)?;
+            [scale, 0.0, 0.0, scale]
+        } else if flags & ComponentFlags::X_AND_Y_SCALE != 0 {
+            [read_f2dot14(reader)?, 0.0, 0.0, read_f2dot14(reader)?]
+        } else if flags & ComponentFlags::TWO_BY_TWO != 0 {
+            [
+                read_f2dot14(reader)?,
+                read_f2dot14(reader)?,
+                read_f2dot14(reader)?,
+                read_f2dot14(reader)?,
+            ]
+        } else {
+            [1.0, 0.0, 0.0, 1.0]
+        };
+
+        components.push(Component {
+            glyph_id,
+            flags,
+            arguments,
+            transform,
+        });
+
+        if flags & ComponentFlags::MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+
+    // Only the last component's flags say whether instructions follow
+    let mut instructions = CoreVec::new_in(allocator);
+    let has_instructions = components
+        .last()
+        .is_some_and(|c| c.flags & ComponentFlags::WE_HAVE_INSTRUCTIONS != 0);
+    if options.keep_instructions && has_instructions {
+        let num_instructions: u16 = reader.read_int()?;
+        instructions.reserve_exact(usize::from(num_instructions));
+        for _ in 0..num_instructions {
+            instructions.push(reader.read_int()?);
+        }
+    }
+
+    components.shrink_to_fit();
+    Ok((components, instructions))
+}
+
+/// Union of the bounds of each glyph's outline as `(x_min, y_min, x_max,
+/// y_max)`, following components up to [`MAX_COMPONENT_DEPTH`] levels deep.
+/// `None` for glyphs without points. Components attached by point numbers are
+/// placed at the origin
+///
+/// Worked out a level at a time for every glyph at once, as following each
+/// composite's components separately is exponential in the depth when they're
+/// shared, or refer back to the composite
+fn placed_bounds<A: core::alloc::Allocator + Copy>(
+    allocator: A,
+    glyphs: &[Glyph<A>],
+) -> CoreVec<Option<(f32, f32, f32, f32)>, A> {
+    let mut bounds = CoreVec::with_capacity_in(glyphs.len(), allocator);
+    bounds.extend(glyphs.iter().map(|glyph| {
+        (glyph.components.is_empty() && glyph.num_contours > 0).then(|| {
+            let (x_min, y_min, x_max, y_max) = glyph.bounds();
+            (
+                f32::from(x_min),
+                f32::from(y_min),
+                f32::from(x_max),
+                f32::from(y_max),
+            )
+        })
+    }));
+
+    let mut next = bounds.clone();
+    for _ in 0..MAX_COMPONENT_DEPTH {
+        for (glyph, placed) in glyphs.iter().zip(next.iter_mut()) {
+            if glyph.components.is_empty() {
+                continue;
+            }
+
+            *placed = glyph
+                .components
+                .iter()
+                .filter_map(|component| {
+                    let (x_min, y_min, x_max, y_max) =
+                        (*bounds.get(usize::from(component.glyph_id))?)?;
+
+                    #[allow(clippy::cast_precision_loss)]
+                    let (dx, dy) = if component.flags & ComponentFlags::ARGS_ARE_XY_VALUES != 0 {
+                        (component.arguments.0 as f32, component.arguments.1 as f32)
+                    } else {
+                        (0.0, 0.0)
+                    };
+                    let [xx, xy, yx, yy] = component.transform;
+                    [(x_min, y_min), (x_min, y_max), (x_max, y_min), (x_max, y_max)]
+                        .map(|(x, y)| {
+                            (x.mul_add(xx, y.mul_add(yx, dx)), x.mul_add(xy, y.mul_add(yy, dy)))
+                        })
+                        .into_iter()
+                        .map(|(x, y)| (x, y, x, y))
+                        .reduce(union_bounds)
+                })
+                .reduce(union_bounds);
+        }
+
+        // Settled once a level adds nothing, which is soon for real fonts
+        if next == bounds {
+            break;
+        }
+        bounds.clone_from(&next);
+    }
+    bounds
+}
+
+/// Rounds bounds outwards to whole font units
+#[allow(clippy::cast_possible_truncation)]
+const fn round_out((x_min, y_min, x_max, y_max): (f32, f32, f32, f32)) -> (i16, i16, i16, i16) {
+    (
+        x_min.floor() as i16,
+        y_min.floor() as i16,
+        x_max.ceil() as i16,
+        y_max.ceil() as i16,
+    )
+}
+
+const fn union_bounds(
+    a: (f32, f32, f32, f32),
+    b: (f32, f32, f32, f32),
+) -> (f32, f32, f32, f32) {
+    (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))
+}
+
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static, R: CoreRead>(
     allocator: A,
+    options: &FontOptions,
     prev_tables: &[Table<A>],
     reader: &mut R,
 ) -> Result<ParsedType<A>, ParseError<R::IoError>> {
@@ -77,38 +744,33 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static
 
     let mut glyphs = CoreVec::with_capacity_in(loca.len(), allocator);
     let mut reader = TrackingReader::new(reader);
+    let mut total_points = 0;
 
-    let mut prev_complex = false;
     for idx in 0..loca.len() {
         let (offset, len) = loca.index(idx);
+        let (offset, end) = (offset as usize, offset as usize + len as usize);
         if len == 0 {
-            //println!("Empty contour {idx}");
-            glyphs.push(Glyph {
-                num_contours: 0,
-                x_bounds:     (0..=0).into(),
-                y_bounds:     (0..=0).into(),
-                end_pts:      CoreVec::new_in(allocator),
-                points:       CoreVec::new_in(allocator),
-            });
+            glyphs.push(Glyph::empty(allocator));
             continue;
         }
 
-        if !prev_complex && offset as usize != reader.total_read() {
+        // The previous glyph's padding has already been skipped, so we should
+        // always be at `offset` unless `loca` has gaps or overlaps
+        if reader.total_read() > offset {
+            return Err(ParseError::Parsing {
+                variable: "glyf::offset",
+                expected: ValidType::USize(reader.total_read()),
+                parsed:   ValidType::USize(offset),
+            });
+        }
+        if reader.total_read() != offset {
             tracing::event!(
                 tracing::Level::DEBUG,
-                "Index mismatch! expected {} got {}",
-                loca.index(idx).0,
+                "Index mismatch! expected {offset} got {}",
                 reader.total_read()
             );
+            reader.skip(offset - reader.total_read())?;
         }
-        let _ = reader.skip(loca.index(idx).0 as usize - reader.total_read())?;
-        if reader.total_read() != loca.index(idx).0 as usize {
-            return Err(ParseError::UnexpectedEop {
-                location: "glyf",
-                needed:   loca.index(idx).0 as usize - reader.total_read(),
-            });
-        }
-        prev_complex = false;
 
         let num_contours: i16 = reader.read_int()?;
 
@@ -125,65 +787,359 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static
         }
 
         if num_contours < 0 {
-            // TODO: Implement Composite Glyphs
+            // TODO: Resolve composite outlines
             // For now, we're gonna duplicate the 0th glyph (NULL_CHAR)
-            glyphs.push(glyphs[0].clone());
-            prev_complex = true;
-            continue;
+            let notdef = glyphs.first().cloned();
+            let mut glyph = notdef.unwrap_or_else(|| Glyph::empty(allocator));
+            (glyph.components, glyph.instructions) =
+                parse_components(allocator, options, &mut reader)?;
+            glyphs.push(glyph);
+        } else {
+            glyphs.push(parse_simple(
+                allocator,
+                options,
+                &mut total_points,
+                &mut reader,
+                num_contours,
+                x_bounds,
+                y_bounds,
+            )?);
+        }
+
+        // Skip the unread composite data and/or padding up to the next glyph
+        if reader.total_read() > end {
+            return Err(ParseError::Parsing {
+                variable: "glyf::length",
+                expected: ValidType::USize(end - offset),
+                parsed:   ValidType::USize(reader.total_read() - offset),
+            });
+        }
+        reader.skip(end - reader.total_read())?;
+        if reader.total_read() != end {
+            return Err(ParseError::UnexpectedEop {
+                location: "glyf",
+                needed:   end - reader.total_read(),
+            });
         }
+    }
 
-        // Read end_pts bytes
-        let mut end_pts = CoreVec::with_capacity_in(
-            usize::try_from(num_contours).expect("Signed to Unsigned cast failed"),
-            allocator,
-        );
-        for _ in 0..num_contours {
-            end_pts.push(reader.read_int()?);
+    // Components may come after the composite, so bounds are only known once
+    // every glyph is read
+    let bounds = placed_bounds(allocator, &glyphs);
+    for (glyph, bounds) in glyphs.iter_mut().zip(bounds) {
+        if glyph.components.is_empty() {
+            continue;
         }
 
-        let num_instructions: u16 = reader.read_int()?;
-        for _ in 0..num_instructions {
-            let _instruction: u8 = reader.read_int()?;
-            // TODO: Parse
+        let (x_min, y_min, x_max, y_max) = bounds.map_or((0, 0, 0, 0), round_out);
+        glyph.x_bounds = core::range::RangeInclusive::from(x_min..=x_max);
+        glyph.y_bounds = core::range::RangeInclusive::from(y_min..=y_max);
+    }
+
+    glyphs.shrink_to_fit();
+    Ok(glyphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            be,
+            composite_glyph,
+            font,
+            glyf_loca,
+            head,
+            maxp,
+            rect,
+            simple_glyph,
+            square_font,
+        },
+        types::SliceReader,
+        FontTrait,
+    };
+
+    /// `glyf` of `glyphs`, located by a long or short `loca`
+    fn parse(
+        glyphs: &[Vec<u8>],
+        long_loca: bool,
+    ) -> ParsedType<alloc::alloc::Global> {
+        let options = FontOptions::default();
+        let (glyf, loca) = glyf_loca(glyphs, long_loca);
+        let num_glyphs = u16::try_from(glyphs.len()).expect("Too many glyphs");
+
+        let mut prev_tables = Vec::new();
+        for (tag, bytes) in [
+            (*b"head", head(1000, 2, long_loca)),
+            (*b"maxp", maxp(num_glyphs)),
+            (*b"loca", loca),
+        ] {
+            let table = crate::tables::parse_table(
+                alloc::alloc::Global,
+                &options,
+                &prev_tables,
+                tag,
+                &mut SliceReader::new(&bytes),
+            )
+            .expect("Table should parse");
+            prev_tables.push(table);
         }
 
-        // flags has to be handled manually as we need to duplicate the repeats
-        let num_points = usize::from(*end_pts.last().expect("No points in Glyph")) + 1;
+        parse_table(alloc::alloc::Global, &options, &prev_tables, &mut SliceReader::new(&glyf))
+            .expect("glyf should parse")
+    }
+
+    fn points(glyph: &Glyph<alloc::alloc::Global>) -> Vec<(i16, i16, bool)> {
+        glyph.contours().flatten().collect()
+    }
 
-        let mut flags_vec = CoreVec::with_capacity_in(num_points, allocator);
-        while flags_vec.len() != num_points {
-            let flags: u8 = reader.read_int()?;
+    #[test]
+    fn clones_equal_the_original() {
+        let font = crate::parse_font_bytes(&square_font()).expect("Font should parse");
+        let Some(Table::Glyf(glyphs)) = font.tables().iter().find(|t| matches!(t, Table::Glyf(_)))
+        else {
+            panic!("Font should have a glyf table");
+        };
 
-            let mut repeat = 1;
-            if flags & Flags::REPEAT != 0 {
-                repeat += u16::from(reader.read_int::<u8>()?);
+        let square = &glyphs[1];
+        assert_eq!(square.clone(), *square);
+        assert_ne!(glyphs[0], *square);
+    }
+
+    #[test]
+    fn nan_transforms_are_unequal() {
+        // Why `Glyph` is only `PartialEq`
+        let mut glyph = Glyph::empty(alloc::alloc::Global);
+        glyph.components.push(Component {
+            glyph_id:  1,
+            flags:     0,
+            arguments: (0, 0),
+            transform: [f32::NAN, 0.0, 0.0, 1.0],
+        });
+        assert_ne!(glyph.clone(), glyph);
+    }
+
+    #[test]
+    fn empty_glyphs_between_composites_keep_their_place() {
+        let square = rect(0, 0, 100, 100);
+        let bar = rect(200, 0, 300, 50);
+        let glyphs = [
+            simple_glyph(&[&square]),
+            Vec::new(),
+            Vec::new(),
+            composite_glyph(&[(0, 50, 0), (4, 0, 100)]),
+            simple_glyph(&[&bar]),
+        ];
+
+        for long_loca in [true, false] {
+            let parsed = parse(&glyphs, long_loca);
+            assert_eq!(parsed.len(), 5);
+
+            assert_eq!(points(&parsed[0]), square);
+            for empty in &parsed[1..3] {
+                assert_eq!(empty.num_contours, 0);
+                assert!(empty.points.is_empty() && empty.components.is_empty());
             }
 
-            for _ in 0..repeat {
-                flags_vec.push(flags & !Flags::REPEAT);
+            let composite = &parsed[3];
+            let components: Vec<_> = composite
+                .components
+                .iter()
+                .map(|c| (c.glyph_id, c.arguments))
+                .collect();
+            assert_eq!(components, [(0, (50, 0)), (4, (0, 100))]);
+            assert_eq!(composite.x_bounds, (50..=300).into());
+            assert_eq!(composite.y_bounds, (0..=150).into());
+
+            assert_eq!(points(&parsed[4]), bar);
+        }
+    }
+
+    #[test]
+    fn quadratics_elevate_to_cubics() {
+        let (start, control, end) = ((0, 0), (300, 600), (600, 0));
+        let arch = simple_glyph(&[&[
+            (start.0, start.1, true),
+            (control.0, control.1, false),
+            (end.0, end.1, true),
+        ]]);
+        let bytes = crate::testing::font(&[(Vec::new(), 500), (arch, 600)], &[]);
+        let font = crate::parse_font_bytes(&bytes).expect("Font should parse");
+        let path = font
+            .glyph_to_cubic_path(1)
+            .expect("Glyph should have an outline");
+
+        // Each control point is two thirds of the way from an end to the
+        // quadratic's control point
+        let elevate = |from: (i16, i16)| {
+            let (from, control) = (
+                (f32::from(from.0), f32::from(from.1)),
+                (f32::from(control.0), f32::from(control.1)),
+            );
+            (
+                from.0 + 2.0 / 3.0 * (control.0 - from.0),
+                from.1 + 2.0 / 3.0 * (control.1 - from.1),
+            )
+        };
+        let close = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+
+        let [PathCmd::MoveTo(moved), PathCmd::CurveTo(c1, c2, curve_end), PathCmd::LineTo(line_end), PathCmd::Close] =
+            path.as_slice()
+        else {
+            panic!("Unexpected path {path:?}");
+        };
+        assert_eq!((*moved, *curve_end, *line_end), ((0.0, 0.0), (600.0, 0.0), (0.0, 0.0)));
+        assert!(close(*c1, elevate(start)), "{c1:?}");
+        assert!(close(*c2, elevate(end)), "{c2:?}");
+        assert!(close(*c1, (200.0, 400.0)) && close(*c2, (400.0, 400.0)));
+    }
+
+    #[test]
+    fn implied_points_are_made_explicit() {
+        let glyph = simple_glyph(&[
+            &[(0, 0, true), (100, 100, false), (200, 100, false), (300, 0, true)],
+            // Starts off-curve, so starts over from its last point
+            &[(500, 0, false), (600, 100, true), (500, 200, true)],
+        ]);
+        let parsed = parse(&[glyph], true);
+
+        let canonical = parsed[0].reconstruct_implied_points(alloc::alloc::Global);
+        let contours: Vec<Vec<_>> = canonical
+            .contours()
+            .map(Iterator::collect)
+            .collect();
+        assert_eq!(contours, [
+            vec![
+                (0, 0, true),
+                (100, 100, false),
+                (150, 100, true),
+                (200, 100, false),
+                (300, 0, true)
+            ],
+            vec![(500, 200, true), (500, 0, false), (600, 100, true)],
+        ]);
+        assert_eq!(canonical.num_contours, 2);
+    }
+
+    #[test]
+    fn overlap_flag_is_recorded() {
+        let contours: [&[(i16, i16, bool)]; 2] = [&rect(0, 0, 300, 300), &rect(200, 200, 500, 500)];
+        let plain = simple_glyph(&contours);
+        let mut overlapping = plain.clone();
+        // After the header, end points and instruction length
+        overlapping[10 + 2 * contours.len() + 2] |= Flags::OVERLAP_SIMPLE;
+
+        let parsed = parse(&[plain, overlapping], true);
+        assert!(!parsed[0].overlapping);
+        assert!(parsed[1].overlapping);
+        assert_eq!(points(&parsed[0]), points(&parsed[1]));
+    }
+
+    #[test]
+    fn flattened_segments_close_each_contour() {
+        let glyph = simple_glyph(&[
+            &[
+                (300, 500, true),
+                (500, 500, false),
+                (500, 300, true),
+                (500, 100, false),
+                (300, 100, true),
+                (100, 100, false),
+                (100, 300, true),
+                (100, 500, false),
+            ],
+            &rect(600, 0, 800, 400),
+        ]);
+        let parsed = parse(&[glyph], true);
+
+        let mut loops = Vec::new();
+        let mut segments = parsed[0].flattened_segments(0.5, 0.1).peekable();
+        while let Some((start, mut end)) = segments.next() {
+            let mut count = 1;
+            // Chains until the next segment jumps to another contour
+            while let Some(&(next_start, next_end)) = segments.peek() {
+                if next_start != end {
+                    break;
+                }
+                end = next_end;
+                count += 1;
+                segments.next();
             }
+            assert_eq!(end, start, "Contour should end where it starts");
+            loops.push(count);
         }
-        flags_vec.shrink_to_fit();
 
-        // in the quest for lower LOC count
-        let x_coords = read_coords!(allocator reader flags_vec X);
-        let y_coords = read_coords!(allocator reader flags_vec Y);
+        assert_eq!(loops.len(), 2);
+        // Curves split into more lines than their points
+        assert!(loops[0] > 8);
+        assert_eq!(loops[1], 4);
+    }
 
-        let mut points = CoreVec::with_capacity_in(num_points, allocator);
-        points.extend(
-            itertools::izip!(flags_vec, x_coords, y_coords)
-                .map(|(f, x, y)| (x, y, f & Flags::ON_CURVE != 0)),
-        );
+    #[test]
+    fn instructions_are_kept_when_asked() {
+        // PUSHB[0] 1, then SVTCA[1]
+        let bytecode = [0xb0, 0x01, 0x01];
+        let mut hinted = simple_glyph(&[&rect(100, 0, 500, 400)]);
+        // Replaces the empty instructions after the header and end point
+        hinted.splice(12..14, [&be!(3u16)[..], &bytecode].concat());
+        let bytes = font(&[(Vec::new(), 500), (hinted, 600)], &[('A', 1)]);
 
-        glyphs.push(Glyph {
-            num_contours,
-            x_bounds,
-            y_bounds,
-            end_pts,
-            points,
-        });
+        let parse = |keep_instructions| {
+            let options = FontOptions {
+                keep_instructions,
+                ..FontOptions::default()
+            };
+            crate::open_font_with_options(alloc::alloc::Global, &mut SliceReader::new(&bytes), &options)
+                .expect("Font should parse")
+        };
+
+        let kept = parse(true);
+        let glyph = kept.glyph(1).expect("Glyph should exist");
+        assert_eq!(glyph.instructions(), bytecode);
+        assert_eq!(points(glyph), rect(100, 0, 500, 400));
+
+        let dropped = parse(false);
+        let glyph = dropped.glyph(1).expect("Glyph should exist");
+        assert!(glyph.instructions().is_empty());
+        assert_eq!(points(glyph), rect(100, 0, 500, 400));
     }
 
-    glyphs.shrink_to_fit();
-    Ok(glyphs)
+    #[test]
+    fn composite_bounds_are_the_union_of_their_components() {
+        let glyphs = [
+            (Vec::new(), 500),
+            (simple_glyph(&[&rect(100, 0, 500, 400)]), 600),
+            (simple_glyph(&[&rect(0, 0, 100, 100)]), 100),
+            (composite_glyph(&[(1, 0, 0), (2, 600, 700)]), 800),
+            // Nests the composite above, moved down and left
+            (composite_glyph(&[(3, -50, -100), (2, 0, 0)]), 800),
+        ];
+        let font = crate::parse_font_bytes(&font(&glyphs, &[])).expect("Font should parse");
+        let bounds = |glyph_id| font.glyph(glyph_id).expect("Glyph should exist").bounds();
+
+        assert_eq!(bounds(1), (100, 0, 500, 400));
+        assert_eq!(bounds(3), (100, 0, 700, 800));
+        assert_eq!(bounds(4), (0, -100, 650, 700));
+        // As stored in `head`, which the test font sets to the em square
+        assert_eq!(font.bbox(), (0, 0, 1000, 1000));
+    }
+
+    #[test]
+    fn self_referencing_composites_parse_quickly() {
+        // Every level of following these naively would multiply the work by
+        // 1000
+        let mut components = vec![(1, 0, 0); 1000];
+        components.push((0, 10, 20));
+        let glyphs = [
+            simple_glyph(&[&rect(0, 0, 100, 100)]),
+            composite_glyph(&components),
+            composite_glyph(&[(3, 0, 0), (0, -50, 0)]),
+            composite_glyph(&[(2, 0, 0), (0, 0, -50)]),
+        ];
+
+        let parsed = parse(&glyphs, true);
+        assert_eq!(parsed[1].bounds(), (10, 20, 110, 120));
+        assert_eq!(parsed[2].bounds(), (-50, -50, 100, 100));
+        assert_eq!(parsed[3].bounds(), (-50, -50, 100, 100));
+    }
 }
@@ -9,6 +9,7 @@ use crate::{
         CoreRead,
         ValidType,
     },
+    FontOptions,
     ParseError,
 };
 
@@ -26,13 +27,21 @@ impl Style {
     const UNDERLINE: u16 = 1 << 2;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Type<A: core::alloc::Allocator> {
     pub units_per_em:        u16,
     pub smallest_px_size:    u16,
     pub style:               u16,
     pub long_offset:         bool,
     pub checksum_adjustment: u32,
+    /// Union of every glyph's bounds, in font units
+    pub x_min:               i16,
+    pub y_min:               i16,
+    pub x_max:               i16,
+    pub y_max:               i16,
+    /// Deprecated, `0` for mixed directions, `1`/`2` for left to right only
+    /// (`2` with neutrals) and `-1`/`-2` for right to left
+    pub font_direction_hint: i16,
 
     _phantom: PhantomData<A>,
 }
@@ -40,28 +49,22 @@ pub struct Type<A: core::alloc::Allocator> {
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
     _allocator: A,
+    _options: &FontOptions,
     _prev_tables: &[Table<A>],
     reader: &mut R,
 ) -> Result<Type<A>, ParseError<R::IoError>> {
     let major_version: u16 = reader.read_int()?;
     let minor_version: u16 = reader.read_int()?;
 
-    if major_version != 1 && minor_version != 0 {
-        return Err(ParseError::InvalidVersion {
-            location: "head",
-            version:  (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+    if major_version != 1 || minor_version != 0 {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"head",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0001_0000],
         });
     }
 
-    let mut font_revision = [0u8; 4];
-    let read = reader.read(&mut font_revision)?;
-    if read != font_revision.len() {
-        return Err(ParseError::UnexpectedEop {
-            location: "head::fontRevision",
-            needed:   font_revision.len() - read,
-        });
-    }
-    let font_revision = fixed::types::I16F16::from_be_bytes(font_revision);
+    let font_revision = fixed::types::I16F16::from_be_bytes(reader.read_array()?);
     tracing::event!(tracing::Level::DEBUG, "Font Revision: {font_revision}");
 
     let checksum_adjustment: u32 = reader.read_int()?;
@@ -100,16 +103,15 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
         ValidType::Ldt(modified_time)
     );
 
-    let _x_min: i16 = reader.read_int()?;
-    let _y_min: i16 = reader.read_int()?;
-    let _x_max: i16 = reader.read_int()?;
-    let _y_max: i16 = reader.read_int()?;
+    let x_min: i16 = reader.read_int()?;
+    let y_min: i16 = reader.read_int()?;
+    let x_max: i16 = reader.read_int()?;
+    let y_max: i16 = reader.read_int()?;
 
     let style: u16 = reader.read_int()?;
     let smallest_px_size: u16 = reader.read_int()?;
 
-    // Deprecated
-    let _font_dir_hint: i16 = reader.read_int()?;
+    let font_direction_hint: i16 = reader.read_int()?;
 
     let long_offset: u16 = reader.read_int()?;
     if long_offset > 1 {
@@ -134,7 +136,46 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
         style,
         smallest_px_size,
         checksum_adjustment,
+        x_min,
+        y_min,
+        x_max,
+        y_max,
+        font_direction_hint,
         long_offset: long_offset == 1,
         _phantom: PhantomData {},
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::head,
+        types::SliceReader,
+    };
+
+    fn parse(table: &[u8]) -> Result<Type<alloc::alloc::Global>, ParseError<core::convert::Infallible>> {
+        parse_table(alloc::alloc::Global, &FontOptions::default(), &[], &mut SliceReader::new(table))
+    }
+
+    #[test]
+    fn only_version_1_0_is_supported() {
+        let table = head(1000, 2, true);
+        let parsed = parse(&table).expect("head should parse");
+        assert_eq!(parsed.units_per_em, 1000);
+        assert!(parsed.long_offset);
+
+        for (version, bytes) in [(0x0002_0000, [0, 2, 0, 0]), (0x0001_0001, [0, 1, 0, 1])] {
+            let mut table = table.clone();
+            table[..4].copy_from_slice(&bytes);
+            assert!(
+                matches!(
+                    parse(&table),
+                    Err(ParseError::UnsupportedVersion { tag, version: v, .. })
+                        if tag == *b"head" && v == version
+                ),
+                "{version:#x}"
+            );
+        }
+    }
+}
@@ -6,20 +6,28 @@ use core::marker::PhantomData;
 use super::Table;
 use crate::{
     types::CoreRead,
+    FontOptions,
     ParseError,
 };
 
 pub type ParsedType<A> = Type<A>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CaretSlope {
     Vertical,
     Horizontal,
     Specific { rise: i16, run: i16 },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Type<A: core::alloc::Allocator> {
+    /// Distance from the baseline to the top of the line, in font units
+    pub ascender:     i16,
+    /// Distance from the baseline to the bottom of the line, negative below
+    /// it, in font units
+    pub descender:    i16,
+    /// Extra spacing between lines, in font units
+    pub line_gap:     i16,
     pub max_advance:  u16,
     pub carat_slope:  CaretSlope,
     pub carat_offset: i16,
@@ -31,6 +39,7 @@ pub struct Type<A: core::alloc::Allocator> {
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
     _allocator: A,
+    _options: &FontOptions,
     _prev_tables: &[Table<A>],
     reader: &mut R,
 ) -> Result<Type<A>, ParseError<R::IoError>> {
@@ -38,15 +47,16 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
     let minor_version: u16 = reader.read_int()?;
 
     if major_version != 1 || minor_version != 0 {
-        return Err(ParseError::InvalidVersion {
-            location: "hhea",
-            version:  (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"hhea",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0001_0000],
         });
     }
 
-    let _ascender: i16 = reader.read_int()?;
-    let _descender: i16 = reader.read_int()?;
-    let _linegap: i16 = reader.read_int()?;
+    let ascender: i16 = reader.read_int()?;
+    let descender: i16 = reader.read_int()?;
+    let line_gap: i16 = reader.read_int()?;
 
     let max_advance: u16 = reader.read_int()?;
 
@@ -73,13 +83,16 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
     if data_format != 0 {
         return Err(ParseError::InvalidVersion {
             location: "hhea",
-            version:  u32::try_from(data_format).expect("i16 -> u32 cast failure"),
+            version:  u32::from(data_format.cast_unsigned()),
         });
     }
 
     let num_hmetric: u16 = reader.read_int()?;
 
     Ok(Type {
+        ascender,
+        descender,
+        line_gap,
         max_advance,
         carat_slope,
         carat_offset,
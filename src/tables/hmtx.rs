@@ -7,20 +7,22 @@ use crate::{
         CoreRead,
         CoreVec,
     },
+    FontOptions,
     ParseError,
 };
 
 pub type ParsedType<A> = CoreVec<Type, A>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Type {
-    advance:           u16,
-    left_side_bearing: i16,
+    pub advance:           u16,
+    pub left_side_bearing: i16,
 }
 
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
     allocator: A,
+    _options: &FontOptions,
     prev_tables: &[Table<A>],
     reader: &mut R,
 ) -> Result<ParsedType<A>, ParseError<R::IoError>> {
@@ -38,12 +40,7 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
         });
     };
 
-    let num_glyphs = *match maxp {
-        super::maxp::Type::Ver05 { num_glyphs } | super::maxp::Type::Ver10 { num_glyphs, .. } => {
-            num_glyphs
-        },
-        super::maxp::Type::_Phantom(_) => unreachable!(),
-    } as usize;
+    let num_glyphs = usize::from(maxp.num_glyphs());
 
     let mut metrics = CoreVec::with_capacity_in(num_glyphs, allocator);
 
@@ -56,11 +53,13 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
         });
     }
 
-    let advance = metrics
-        .last()
-        .expect("No horizontal metrics in hmtx")
-        .advance;
-    for _ in 0..num_glyphs - usize::from(hhea.num_hmetric) {
+    // numberOfHMetrics must be at least 1, but broken fonts may ship 0
+    let advance = metrics.last().map_or(0, |m| m.advance);
+    if hhea.num_hmetric == 0 {
+        tracing::event!(tracing::Level::WARN, "No horizontal metrics in hmtx");
+    }
+
+    for _ in 0..num_glyphs.saturating_sub(usize::from(hhea.num_hmetric)) {
         let left_side_bearing: i16 = reader.read_int()?;
         metrics.push(Type {
             advance,
@@ -70,3 +69,53 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
 
     Ok(metrics)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            hhea,
+            hmtx,
+            maxp,
+        },
+        types::SliceReader,
+    };
+
+    /// `hmtx` of `num_glyphs` glyphs, `num_hmetrics` of them with their own
+    /// advance
+    fn parse(
+        num_glyphs: u16,
+        num_hmetrics: u16,
+        table: &[u8],
+    ) -> Result<ParsedType<alloc::alloc::Global>, ParseError<core::convert::Infallible>> {
+        let options = FontOptions::default();
+        let mut prev_tables = Vec::new();
+        for (tag, bytes) in [(*b"maxp", maxp(num_glyphs)), (*b"hhea", hhea(800, -200, num_hmetrics))] {
+            let table = crate::tables::parse_table(
+                alloc::alloc::Global,
+                &options,
+                &prev_tables,
+                tag,
+                &mut SliceReader::new(&bytes),
+            )?;
+            prev_tables.push(table);
+        }
+
+        parse_table(alloc::alloc::Global, &options, &prev_tables, &mut SliceReader::new(table))
+    }
+
+    #[test]
+    fn bearings_without_metrics_have_no_advance() {
+        let metrics = parse(2, 0, &hmtx(&[], &[10, -20])).expect("hmtx should parse");
+        let metrics: Vec<_> = metrics.iter().map(|m| (m.advance, m.left_side_bearing)).collect();
+        assert_eq!(metrics, [(0, 10), (0, -20)]);
+    }
+
+    #[test]
+    fn trailing_bearings_share_the_last_advance() {
+        let metrics = parse(3, 1, &hmtx(&[(500, 5)], &[10, -20])).expect("hmtx should parse");
+        let metrics: Vec<_> = metrics.iter().map(|m| (m.advance, m.left_side_bearing)).collect();
+        assert_eq!(metrics, [(500, 5), (500, 10), (500, -20)]);
+    }
+}
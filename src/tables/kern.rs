@@ -0,0 +1,161 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreVec,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+pub struct Coverage;
+impl Coverage {
+    pub const CROSS_STREAM: u16 = 1 << 2;
+    pub const HORIZONTAL: u16 = 1 << 0;
+    // Values are minimums rather than adjustments
+    pub const MINIMUM: u16 = 1 << 1;
+    // Replaces the value accumulated from earlier subtables
+    pub const OVERRIDE: u16 = 1 << 3;
+}
+
+/// Format 0 subtable, adjusting the space between pairs of glyphs
+#[derive(Debug, Clone)]
+pub struct Subtable<A: core::alloc::Allocator> {
+    pub coverage: u16,
+    /// ((left, right), adjustment in font units), sorted by glyph pair
+    pub pairs:    CoreVec<((u16, u16), i16), A>,
+}
+
+/// Legacy kerning, only keeping the horizontal format 0 subtables
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    pub subtables: CoreVec<Subtable<A>, A>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    /// Adjustment to the advance of `left` when followed by `right`, in font
+    /// units
+    pub fn kern_pair(
+        &self,
+        left: u16,
+        right: u16,
+    ) -> i16 {
+        self.subtables.iter().fold(0, |total, subtable| {
+            let Ok(idx) = subtable
+                .pairs
+                .binary_search_by_key(&(left, right), |(pair, _)| *pair)
+            else {
+                return total;
+            };
+
+            let value = subtable.pairs[idx].1;
+            if subtable.coverage & Coverage::OVERRIDE != 0 {
+                value
+            } else {
+                total.saturating_add(value)
+            }
+        })
+    }
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    // Apple's version 1.0 starts with a 32-bit version instead
+    let version: u16 = reader.read_int()?;
+    if version != 0 {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"kern",
+            version:   u32::from(version),
+            supported: &[0],
+        });
+    }
+
+    let num_tables: u16 = reader.read_int()?;
+    let mut subtables = CoreVec::new_in(allocator);
+    for _ in 0..num_tables {
+        let _version: u16 = reader.read_int()?;
+        let length = usize::from(reader.read_int::<u16>()?);
+        let coverage: u16 = reader.read_int()?;
+        let format = coverage >> 8;
+
+        let usable = format == 0
+            && coverage & Coverage::HORIZONTAL != 0
+            && coverage & (Coverage::MINIMUM | Coverage::CROSS_STREAM) == 0;
+        if !usable {
+            tracing::event!(
+                tracing::Level::DEBUG,
+                "Skipping kern subtable, format {format} coverage {coverage:#06x}"
+            );
+            reader.skip(length.saturating_sub(6))?;
+            continue;
+        }
+
+        let num_pairs: u16 = reader.read_int()?;
+        // searchRange, entrySelector and rangeShift
+        let _search: [u8; 6] = reader.read_array()?;
+
+        let mut pairs = CoreVec::with_capacity_in(usize::from(num_pairs), allocator);
+        for _ in 0..num_pairs {
+            let left: u16 = reader.read_int()?;
+            let right: u16 = reader.read_int()?;
+            pairs.push(((left, right), reader.read_int()?));
+        }
+        // Binary searches rely on the order, which some fonts get wrong
+        if !pairs.is_sorted_by_key(|(pair, _)| *pair) {
+            tracing::event!(tracing::Level::WARN, "kern pairs aren't sorted");
+            pairs.sort_by_key(|(pair, _)| *pair);
+        }
+
+        // Large subtables overflow `length`, so the pairs are trusted instead
+        let read = 14 + usize::from(num_pairs) * 6;
+        if length > read {
+            reader.skip(length - read)?;
+        }
+
+        subtables.push(Subtable { coverage, pairs });
+    }
+
+    Ok(Type { subtables })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::kern,
+        types::SliceReader,
+    };
+
+    fn parse(table: &[u8]) -> Type<alloc::alloc::Global> {
+        parse_table(alloc::alloc::Global, &FontOptions::default(), &[], &mut SliceReader::new(table))
+            .expect("kern should parse")
+    }
+
+    #[test]
+    fn pairs_are_found_even_when_stored_unsorted() {
+        let table = kern(&[((5, 2), -40), ((1, 2), -80), ((1, 9), 25)]);
+        let parsed = parse(&table);
+        assert_eq!(parsed.kern_pair(1, 2), -80);
+        assert_eq!(parsed.kern_pair(1, 9), 25);
+        assert_eq!(parsed.kern_pair(5, 2), -40);
+        // Order matters within a pair
+        assert_eq!(parsed.kern_pair(2, 1), 0);
+        assert_eq!(parsed.kern_pair(5, 9), 0);
+
+        // Vertical kerning isn't applied to horizontal text
+        let mut vertical = table;
+        vertical[9] &= !(Coverage::HORIZONTAL as u8);
+        assert!(parse(&vertical).subtables.is_empty());
+        assert_eq!(parse(&vertical).kern_pair(1, 2), 0);
+    }
+}
@@ -8,12 +8,13 @@ use crate::{
         CoreVec,
         ValidType,
     },
+    FontOptions,
     ParseError,
 };
 
 pub type ParsedType<A> = Type<A>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Type<A: core::alloc::Allocator> {
     offsets: CoreVec<u32, A>,
 }
@@ -35,6 +36,7 @@ impl<A: core::alloc::Allocator> Type<A> {
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
     allocator: A,
+    _options: &FontOptions,
     prev_tables: &[Table<A>],
     reader: &mut R,
 ) -> Result<Type<A>, ParseError<R::IoError>> {
@@ -52,52 +54,103 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
         });
     };
 
-    let num_glyphs = *match maxp {
-        super::maxp::Type::Ver05 { num_glyphs } | super::maxp::Type::Ver10 { num_glyphs, .. } => {
-            num_glyphs
-        },
-        super::maxp::Type::_Phantom(_) => unreachable!(),
-    } as usize
-        + 1;
+    let num_glyphs = usize::from(maxp.num_glyphs()) + 1;
 
     let mut offsets = CoreVec::with_capacity_in(num_glyphs, allocator);
     offsets.resize(num_glyphs, 0);
 
     let mut prev = u32::MIN;
-    if head.long_offset {
-        // u32, offset
-        for offset in &mut offsets {
-            *offset = reader.read_int()?;
-
-            // loca[n + 1] >= loca[n]
-            if *offset < prev {
-                return Err(ParseError::Parsing {
-                    variable: "loca",
-                    expected: ValidType::U32(prev + 1),
-                    parsed:   ValidType::U32(*offset),
-                });
-            }
-            prev = *offset;
-        }
-    } else {
-        // u16, offset / 2
-        for offset in &mut offsets {
-            let half: u16 = reader.read_int()?;
-            *offset = u32::from(half) * 2;
-
-            // loca[n + 1] >= loca[n]
-            if *offset < prev {
-                return Err(ParseError::Parsing {
-                    variable: "loca",
-                    expected: ValidType::U32(prev + 1),
-                    parsed:   ValidType::U32(*offset),
-                });
-            }
-            prev = *offset;
+    for offset in &mut offsets {
+        *offset = if head.long_offset {
+            reader.read_int()?
+        } else {
+            // Stored halved, doubled as u32 so offsets past u16::MAX fit
+            u32::from(reader.read_int::<u16>()?) * 2
+        };
+
+        // loca[n + 1] >= loca[n], equal for empty glyphs such as trailing
+        // spaces
+        if *offset < prev {
+            return Err(ParseError::Parsing {
+                variable: "loca",
+                expected: ValidType::U32(prev),
+                parsed:   ValidType::U32(*offset),
+            });
         }
+        prev = *offset;
     }
 
     tracing::event!(tracing::Level::DEBUG, "NumGlyphs {}", offsets.len() - 1);
 
     Ok(Type { offsets })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            be,
+            glyf_loca,
+            head,
+            maxp,
+            rect,
+            simple_glyph,
+        },
+        types::SliceReader,
+    };
+
+    fn parse(
+        num_glyphs: u16,
+        long_loca: bool,
+        table: &[u8],
+    ) -> Result<Type<alloc::alloc::Global>, ParseError<core::convert::Infallible>> {
+        let options = FontOptions::default();
+        let mut prev_tables = Vec::new();
+        for (tag, bytes) in [(*b"head", head(1000, 2, long_loca)), (*b"maxp", maxp(num_glyphs))] {
+            let table = crate::tables::parse_table(
+                alloc::alloc::Global,
+                &options,
+                &prev_tables,
+                tag,
+                &mut SliceReader::new(&bytes),
+            )?;
+            prev_tables.push(table);
+        }
+
+        parse_table(alloc::alloc::Global, &options, &prev_tables, &mut SliceReader::new(table))
+    }
+
+    #[test]
+    fn trailing_empty_glyphs_have_no_length() {
+        let square = simple_glyph(&[&rect(0, 0, 100, 100)]);
+        let len = u32::try_from(square.len().next_multiple_of(2)).expect("Glyph too long");
+
+        for long_loca in [true, false] {
+            let (_, loca) = glyf_loca(&[square.clone(), Vec::new(), Vec::new()], long_loca);
+            let parsed = parse(3, long_loca, &loca).expect("loca should parse");
+            assert_eq!(parsed.len(), 3);
+            assert_eq!(parsed.index(0), (0, len));
+            assert_eq!(parsed.index(1), (len, 0));
+            assert_eq!(parsed.index(2), (len, 0));
+        }
+    }
+
+    #[test]
+    fn short_offsets_double_past_u16() {
+        let loca = be!(0u16, 0x8000u16, u16::MAX, u16::MAX);
+        let parsed = parse(3, false, &loca).expect("loca should parse");
+        assert_eq!(parsed.index(0), (0, 0x1_0000));
+        assert_eq!(parsed.index(1), (0x1_0000, 0xfffe));
+        assert_eq!(parsed.index(2), (0x1_fffe, 0));
+
+        let decreasing = be!(0u16, 4u16, 2u16);
+        assert!(matches!(
+            parse(2, false, &decreasing),
+            Err(ParseError::Parsing {
+                variable: "loca",
+                ..
+            })
+        ));
+    }
+}
@@ -6,51 +6,204 @@ use core::marker::PhantomData;
 use super::Table;
 use crate::{
     types::CoreRead,
+    FontOptions,
     ParseError,
 };
 
 pub type ParsedType<A> = Type<A>;
 
-#[derive(Debug)]
+/// Upper bounds over every glyph, for sizing buffers up front. Only present
+/// in TrueType fonts
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// In a simple glyph
+    pub max_points:               u16,
+    /// In a simple glyph
+    pub max_contours:             u16,
+    pub max_composite_points:     u16,
+    pub max_composite_contours:   u16,
+    /// 1 if instructions don't use the twilight zone, otherwise 2
+    pub max_zones:                u16,
+    pub max_twilight_points:      u16,
+    pub max_storage:              u16,
+    pub max_function_defs:        u16,
+    pub max_instruction_defs:     u16,
+    pub max_stack_elements:       u16,
+    /// In bytes, over glyph programs only
+    pub max_size_of_instructions: u16,
+    /// Components referenced at the top level of a composite glyph
+    pub max_component_elements:   u16,
+    /// Levels of recursion, 1 for composites of simple glyphs
+    pub max_component_depth:      u16,
+}
+
+#[derive(Debug, Clone)]
 pub enum Type<A: core::alloc::Allocator> {
     Ver05 {
         num_glyphs: u16,
     },
     Ver10 {
         num_glyphs: u16,
+        limits:     Limits,
     },
 
     #[doc(hidden)]
     _Phantom(PhantomData<A>),
 }
 
+impl<A: core::alloc::Allocator> Type<A> {
+    #[must_use]
+    pub const fn num_glyphs(&self) -> u16 {
+        match self {
+            Self::Ver05 { num_glyphs } | Self::Ver10 { num_glyphs, .. } => *num_glyphs,
+            Self::_Phantom(_) => unreachable!(),
+        }
+    }
+
+    /// `None` for version 0.5, as used by CFF fonts
+    #[must_use]
+    pub const fn limits(&self) -> Option<&Limits> {
+        match self {
+            Self::Ver10 { limits, .. } => Some(limits),
+            Self::Ver05 { .. } | Self::_Phantom(_) => None,
+        }
+    }
+}
+
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
     _allocator: A,
+    options: &FontOptions,
     _prev_tables: &[Table<A>],
     reader: &mut R,
 ) -> Result<Type<A>, ParseError<R::IoError>> {
     // Must be at least 6 bytes (v16d16 + u16)
     let packed_ver: u32 = reader.read_int()?;
 
-    match packed_ver {
+    let table = match packed_ver {
         0x0000_5000 => {
             // Version 0.5
             tracing::event!(tracing::Level::TRACE, "Version 0.5");
-            Ok(Type::Ver05 {
+            Type::Ver05 {
                 num_glyphs: reader.read_int()?,
-            })
+            }
         },
         0x0001_0000 => {
             // Version 1.0
             tracing::event!(tracing::Level::TRACE, "Version 1.0");
-            Ok(Type::Ver10 {
+            Type::Ver10 {
                 num_glyphs: reader.read_int()?,
+                limits:     Limits {
+                    max_points:               reader.read_int()?,
+                    max_contours:             reader.read_int()?,
+                    max_composite_points:     reader.read_int()?,
+                    max_composite_contours:   reader.read_int()?,
+                    max_zones:                reader.read_int()?,
+                    max_twilight_points:      reader.read_int()?,
+                    max_storage:              reader.read_int()?,
+                    max_function_defs:        reader.read_int()?,
+                    max_instruction_defs:     reader.read_int()?,
+                    max_stack_elements:       reader.read_int()?,
+                    max_size_of_instructions: reader.read_int()?,
+                    max_component_elements:   reader.read_int()?,
+                    max_component_depth:      reader.read_int()?,
+                },
+            }
+        },
+        _ => {
+            return Err(ParseError::UnsupportedVersion {
+                tag:       *b"maxp",
+                version:   packed_ver,
+                supported: &[0x0000_5000, 0x0001_0000],
             })
         },
-        _ => Err(ParseError::InvalidVersion {
-            location: "maxp",
-            version:  packed_ver,
-        }),
+    };
+
+    // Checked here as `loca`, `hmtx` and `glyf` all allocate per glyph
+    if let Some(limit) = options
+        .max_glyphs
+        .filter(|limit| usize::from(table.num_glyphs()) > *limit)
+    {
+        return Err(ParseError::BudgetExceeded {
+            budget: "max_glyphs",
+            limit,
+        });
+    }
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{
+            be,
+            maxp,
+        },
+        types::SliceReader,
+    };
+
+    fn parse(table: &[u8]) -> Result<Type<alloc::alloc::Global>, ParseError<core::convert::Infallible>> {
+        parse_table(alloc::alloc::Global, &FontOptions::default(), &[], &mut SliceReader::new(table))
+    }
+
+    #[test]
+    fn version_1_reads_every_limit() {
+        let mut table = be!(0x0001_0000u32, 42u16);
+        for limit in 1..=13u16 {
+            table.extend(be!(limit * 10));
+        }
+
+        let parsed = parse(&table).expect("maxp should parse");
+        assert_eq!(parsed.num_glyphs(), 42);
+        let limits = parsed.limits().expect("Version 1.0 should have limits");
+        assert_eq!(
+            [
+                limits.max_points,
+                limits.max_contours,
+                limits.max_composite_points,
+                limits.max_composite_contours,
+                limits.max_zones,
+                limits.max_twilight_points,
+                limits.max_storage,
+                limits.max_function_defs,
+                limits.max_instruction_defs,
+                limits.max_stack_elements,
+                limits.max_size_of_instructions,
+                limits.max_component_elements,
+                limits.max_component_depth,
+            ],
+            [10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 110, 120, 130]
+        );
+
+        // Missing the last limit
+        assert!(parse(&table[..table.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn version_2_is_unsupported() {
+        let mut table = be!(0x0002_0000u32, 42u16);
+        table.extend([0; 26]);
+
+        match parse(&table) {
+            Err(ParseError::UnsupportedVersion {
+                tag,
+                version,
+                supported,
+            }) => {
+                assert_eq!(tag, *b"maxp");
+                assert_eq!(version, 0x0002_0000);
+                assert!(supported.contains(&0x0000_5000) && supported.contains(&0x0001_0000));
+            },
+            other => panic!("Expected UnsupportedVersion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn version_0_5_has_no_limits() {
+        let parsed = parse(&maxp(7)).expect("maxp should parse");
+        assert_eq!(parsed.num_glyphs(), 7);
+        assert!(parsed.limits().is_none());
     }
 }
@@ -2,13 +2,28 @@
 // SPDX-License-Identifier: LGPL-2.1-only
 
 macro_rules! create_table {
-    {$($tag:ident),* $(,)*} => {
+    // Tables with uppercase tags spell them out
+    (@tag $tag:ident $raw:literal) => { *$raw };
+    (@tag $tag:ident) => {
+        paste::paste! {{
+            const BYTES: &[u8] = stringify!([<$tag:lower>]).as_bytes();
+            let mut result: [u8; 4] = [0u8; 4];
+            let mut idx = 0;
+            while idx < BYTES.len() {
+                result[idx] = BYTES[idx];
+                idx += 1;
+            }
+            result
+        }}
+    };
+
+    {$($tag:ident $(= $raw:literal)?),* $(,)*} => {
         paste::paste! {
             $(
                 pub mod [<$tag:lower>];
             )*
 
-            #[derive(Debug)]
+            #[derive(Debug, Clone)]
             pub enum Table<A: core::alloc::Allocator + core::fmt::Debug + 'static> {
                 $(
                     [<$tag:camel>]([<$tag:lower>]::ParsedType<A>),
@@ -17,27 +32,19 @@ macro_rules! create_table {
 
             pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug + 'static, R: crate::types::CoreRead>(
                 allocator: A,
+                options: &crate::FontOptions,
                 prev_tables: &[Table<A>],
                 tag: [u8; 4],
                 reader: &mut R,
             ) -> Result<Table<A>, crate::ParseError<R::IoError>> {
                 $(
-                    const [<$tag:upper>]: [u8; stringify!([<$tag:lower>]).len()] = {
-                        const BYTES: &[u8] = stringify!([<$tag:lower>]).as_bytes();
-                        let mut result: [u8; BYTES.len()] = [0u8; BYTES.len()];
-                        let mut idx = 0;
-                        while idx < BYTES.len() {
-                            result[idx] = BYTES[idx];
-                            idx += 1;
-                        }
-                        result
-                    };
+                    const [<$tag:upper>]: [u8; 4] = create_table!(@tag $tag $($raw)?);
                 )*
 
 
                     match tag {
                         $(
-                            [<$tag:upper>] => Ok(Table::[<$tag:camel>]([<$tag:lower>]::parse_table(allocator, prev_tables, reader)?)),
+                            [<$tag:upper>] => Ok(Table::[<$tag:camel>]([<$tag:lower>]::parse_table(allocator, options, prev_tables, reader)?)),
                         )*
                         _ => Err(crate::ParseError::InvalidTag(tag))
                     }
@@ -48,5 +55,23 @@ macro_rules! create_table {
 }
 
 create_table! {
-    glyf, maxp, loca, head, name, hhea, hmtx
+    glyf, maxp, loca, head, name, hhea, hmtx, pclt = b"PCLT",
+    cblc = b"CBLC", cbdt = b"CBDT", cmap, os2 = b"OS/2",
+    cff = b"CFF ", base = b"BASE", post, kern,
+}
+
+/// Tables `tag`'s parser reads from `prev_tables`, directly or through one of
+/// them, so whose changes change what it parses to
+pub const fn dependencies(tag: [u8; 4]) -> &'static [[u8; 4]] {
+    const HEAD: [u8; 4] = *b"head";
+    const MAXP: [u8; 4] = *b"maxp";
+    const HHEA: [u8; 4] = *b"hhea";
+    const LOCA: [u8; 4] = *b"loca";
+    match &tag {
+        b"loca" => &[HEAD, MAXP],
+        b"glyf" => &[LOCA, HEAD, MAXP],
+        b"hmtx" => &[HHEA, MAXP],
+        b"CBLC" => &[MAXP],
+        _ => &[],
+    }
 }
@@ -6,18 +6,19 @@ use core::str;
 use super::Table;
 use crate::{
     types::{
-        //CoreBox,
         CoreBox,
         CoreRead,
         CoreVec,
         TrackingReader,
+        ValidType,
     },
+    FontOptions,
     ParseError,
 };
 
 pub type ParsedType<A> = Type<A>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RecordType {
     Copyright,
     Family,
@@ -76,97 +77,127 @@ impl From<u16> for RecordType {
             23 => Self::LightPalette,
             24 => Self::DarkPalette,
             25 => Self::PostScriptVariations,
-            15 | 26..256 => Self::_Reserved,
             256..=32767 => Self::FontSpecific(value),
-            _ => unreachable!("Invalid Name ID"),
+            _ => Self::_Reserved,
         }
     }
 }
 
+/// Characters of bytes 0x80 to 0xff in the Mac OS Roman encoding
+const MAC_ROMAN: [char; 128] = [
+    '\u{00c4}', '\u{00c5}', '\u{00c7}', '\u{00c9}', '\u{00d1}', '\u{00d6}', '\u{00dc}', '\u{00e1}',
+    '\u{00e0}', '\u{00e2}', '\u{00e4}', '\u{00e3}', '\u{00e5}', '\u{00e7}', '\u{00e9}', '\u{00e8}',
+    '\u{00ea}', '\u{00eb}', '\u{00ed}', '\u{00ec}', '\u{00ee}', '\u{00ef}', '\u{00f1}', '\u{00f3}',
+    '\u{00f2}', '\u{00f4}', '\u{00f6}', '\u{00f5}', '\u{00fa}', '\u{00f9}', '\u{00fb}', '\u{00fc}',
+    '\u{2020}', '\u{00b0}', '\u{00a2}', '\u{00a3}', '\u{00a7}', '\u{2022}', '\u{00b6}', '\u{00df}',
+    '\u{00ae}', '\u{00a9}', '\u{2122}', '\u{00b4}', '\u{00a8}', '\u{2260}', '\u{00c6}', '\u{00d8}',
+    '\u{221e}', '\u{00b1}', '\u{2264}', '\u{2265}', '\u{00a5}', '\u{00b5}', '\u{2202}', '\u{2211}',
+    '\u{220f}', '\u{03c0}', '\u{222b}', '\u{00aa}', '\u{00ba}', '\u{03a9}', '\u{00e6}', '\u{00f8}',
+    '\u{00bf}', '\u{00a1}', '\u{00ac}', '\u{221a}', '\u{0192}', '\u{2248}', '\u{2206}', '\u{00ab}',
+    '\u{00bb}', '\u{2026}', '\u{00a0}', '\u{00c0}', '\u{00c3}', '\u{00d5}', '\u{0152}', '\u{0153}',
+    '\u{2013}', '\u{2014}', '\u{201c}', '\u{201d}', '\u{2018}', '\u{2019}', '\u{00f7}', '\u{25ca}',
+    '\u{00ff}', '\u{0178}', '\u{2044}', '\u{20ac}', '\u{2039}', '\u{203a}', '\u{fb01}', '\u{fb02}',
+    '\u{2021}', '\u{00b7}', '\u{201a}', '\u{201e}', '\u{2030}', '\u{00c2}', '\u{00ca}', '\u{00c1}',
+    '\u{00cb}', '\u{00c8}', '\u{00cd}', '\u{00ce}', '\u{00cf}', '\u{00cc}', '\u{00d3}', '\u{00d4}',
+    '\u{f8ff}', '\u{00d2}', '\u{00da}', '\u{00db}', '\u{00d9}', '\u{0131}', '\u{02c6}', '\u{02dc}',
+    '\u{00af}', '\u{02d8}', '\u{02d9}', '\u{02da}', '\u{00b8}', '\u{02dd}', '\u{02db}', '\u{02c7}',
+];
+
 #[derive(Debug)]
 pub struct Record<A: core::alloc::Allocator> {
     pub name:   RecordType,
     pub string: CoreBox<str, A>,
 }
 
+// `Box<str, A>` is only `Clone` in the global allocator
+impl<A: core::alloc::Allocator + Clone> Clone for Record<A> {
+    fn clone(&self) -> Self {
+        let allocator = CoreBox::allocator(&self.string).clone();
+        let bytes = self.string.as_bytes().to_vec_in(allocator);
+        let (ptr, alloc) = CoreBox::into_raw_with_allocator(bytes.into_boxed_slice());
+
+        Self {
+            name:   self.name,
+            // Copied from a `str`, so is valid UTF-8
+            string: unsafe { CoreBox::from_raw_in(ptr as *mut str, alloc) },
+        }
+    }
+}
+
 impl<A: core::alloc::Allocator + Copy> Record<A> {
-    /// WARNING: Will destructively modify `bytes`
-    /// # Panics
-    /// - If specified `encoding_id` is utf16 and bytes isn't u16 alligned
+    /// Caller must guarantee `bytes` is valid UTF-8
+    unsafe fn into_boxed_str(bytes: CoreVec<u8, A>) -> CoreBox<str, A> {
+        // literally just from_boxed_utf8_unchecked
+        let (ptr, alloc) = CoreBox::into_raw_with_allocator(bytes.into_boxed_slice());
+        CoreBox::from_raw_in(ptr as *mut str, alloc)
+    }
+
+    /// A trailing odd byte is ignored
     fn from_utf16(
         allocator: A,
-        bytes: &mut [u8],
+        bytes: &[u8],
     ) -> CoreBox<str, A> {
-        let nibbles = unsafe {
-            let mut arr = CoreBox::new_uninit_slice_in(bytes.len(), allocator);
-            for (v, s) in bytes.array_chunks().zip(&mut arr) {
-                s.write(u16::from_be_bytes(*v));
-            }
-            arr.assume_init()
-        };
-
-        // Feel like this can be done slightly more clean
-        let char_iter = char::decode_utf16(nibbles.iter().copied())
+        let (nibbles, _) = bytes.as_chunks::<2>();
+        let char_iter = char::decode_utf16(nibbles.iter().map(|v| u16::from_be_bytes(*v)))
             .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER));
-        let bytes = char_iter
-            .clone()
-            .fold(0usize, |size, c| size + c.len_utf8());
-
-        let mut utf8_slices =
-            unsafe { CoreBox::new_uninit_slice_in(bytes, allocator).assume_init() };
-        let _ = char_iter.fold(0usize, |idx, c| {
-            c.encode_utf8(&mut utf8_slices[idx..]);
-            idx + c.len_utf8()
-        });
 
-        // literally just from_boxed_utf8_unchecked
-        unsafe {
-            let (ptr, alloc) = CoreBox::into_raw_with_allocator(utf8_slices); // should just be `allocator`
-            CoreBox::from_raw_in(ptr as *mut str, alloc)
+        let mut utf8 =
+            CoreVec::with_capacity_in(char_iter.clone().map(char::len_utf8).sum(), allocator);
+        for c in char_iter {
+            utf8.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
         }
+
+        unsafe { Self::into_boxed_str(utf8) }
     }
 
-    fn from_utf8(
+    /// Every byte maps to a character, the lower half being ASCII
+    fn from_mac_roman(
         allocator: A,
-        bytes: &mut [u8],
+        bytes: &[u8],
     ) -> CoreBox<str, A> {
-        let utf8_slices = unsafe {
-            let mut arr = CoreBox::new_uninit_slice_in(bytes.len(), allocator);
-            for (v, s) in bytes.iter().zip(&mut arr) {
-                s.write(v);
-            }
-            arr.assume_init()
-        };
+        let char_iter = bytes.iter().map(|b| match b {
+            0..0x80 => char::from(*b),
+            _ => MAC_ROMAN[usize::from(b - 0x80)],
+        });
 
-        // literally just from_boxed_utf8_unchecked
-        unsafe {
-            let (ptr, alloc) = CoreBox::into_raw_with_allocator(utf8_slices); // should just be `allocator`
-            CoreBox::from_raw_in(ptr as *mut str, alloc)
+        let mut utf8 =
+            CoreVec::with_capacity_in(char_iter.clone().map(char::len_utf8).sum(), allocator);
+        for c in char_iter {
+            utf8.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
         }
+
+        unsafe { Self::into_boxed_str(utf8) }
     }
 
+    /// `None` for encodings that can't be decoded, which only affects the
+    /// record's string
     pub fn from_bytes(
         allocator: A,
         platform_id: u16,
         encoding_id: u16,
         language_id: u16,
         name: RecordType,
-        bytes: &mut [u8],
-    ) -> Self {
-        Self {
-            name,
-            string: match (platform_id, encoding_id, language_id) {
-                // Unicode
-                // TODO: Verify BMP types
-                (0, 3..4, _) | (3, 1 | 10, _) => Self::from_utf16(allocator, bytes),
-                (1, 0, _) => Self::from_utf8(allocator, bytes),
-                (a, b, c) => panic!("Unrecognised record! {a} {b} {c} {bytes:?}"),
+        bytes: &[u8],
+    ) -> Option<Self> {
+        let string = match (platform_id, encoding_id, language_id) {
+            // Unicode, and Windows Symbol which is stored the same way
+            (0, _, _) | (3, 0 | 1 | 10, _) => Self::from_utf16(allocator, bytes),
+            (1, 0, _) => Self::from_mac_roman(allocator, bytes),
+            (platform_id, encoding_id, language_id) => {
+                tracing::event!(
+                    tracing::Level::DEBUG,
+                    "Skipping name record in encoding ({platform_id}, {encoding_id}, \
+                     {language_id})"
+                );
+                return None;
             },
-        }
+        };
+
+        Some(Self { name, string })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Type<A: core::alloc::Allocator> {
     pub records: CoreVec<Record<A>, A>,
 }
@@ -174,6 +205,7 @@ pub struct Type<A: core::alloc::Allocator> {
 #[tracing::instrument(skip_all, level = "trace")]
 pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
     allocator: A,
+    _options: &FontOptions,
     _prev_tables: &[Table<A>],
     reader_actual: &mut R,
 ) -> Result<Type<A>, ParseError<R::IoError>> {
@@ -215,6 +247,13 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
     }
 
     let current_index = reader.finish();
+    if (storage_offset as usize) < current_index {
+        return Err(ParseError::Parsing {
+            variable: "name::storageOffset",
+            expected: ValidType::USize(current_index),
+            parsed:   ValidType::U16(storage_offset),
+        });
+    }
     if storage_offset as usize != current_index {
         tracing::event!(
             tracing::Level::WARN,
@@ -225,8 +264,8 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
         reader_actual.skip(storage_offset as usize - current_index)?;
     }
 
-    let mut storage_area =
-        unsafe { CoreBox::new_uninit_slice_in(storage_area_length, allocator).assume_init() };
+    let mut storage_area = CoreVec::with_capacity_in(storage_area_length, allocator);
+    storage_area.resize(storage_area_length, 0u8);
     let read = reader_actual.read(&mut storage_area)?;
     if read < storage_area_length {
         return Err(ParseError::UnexpectedEop {
@@ -237,13 +276,13 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
 
     let mut records = CoreVec::with_capacity_in(num_records, allocator);
     for (platform_id, encoding_id, language_id, name_id, begin, end) in records_info {
-        records.push(Record::from_bytes(
+        records.extend(Record::from_bytes(
             allocator,
             platform_id,
             encoding_id,
             language_id,
             name_id.into(),
-            &mut storage_area[begin..end],
+            &storage_area[begin..end],
         ));
     }
 
@@ -251,3 +290,57 @@ pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreR
 
     Ok(Type { records })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::name,
+        types::SliceReader,
+    };
+
+    #[test]
+    fn mac_roman_records_are_decoded() {
+        let utf16: Vec<_> = "Café".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let table = name(&[
+            (1, 0, 0, 1, b"Caf\x8e \xa5"),
+            // Mac Japanese isn't supported
+            (1, 1, 11, 2, b"\x82\xa0"),
+            (3, 1, 0x409, 4, &utf16),
+        ]);
+
+        let parsed = parse_table(
+            alloc::alloc::Global,
+            &FontOptions::default(),
+            &[],
+            &mut SliceReader::new(&table),
+        )
+        .expect("name should parse");
+        let records: Vec<_> = parsed
+            .records
+            .iter()
+            .map(|record| (&record.name, &*record.string))
+            .collect();
+        assert_eq!(records, [
+            (&RecordType::Family, "Café \u{2022}"),
+            (&RecordType::Full, "Café"),
+        ]);
+    }
+
+    #[test]
+    fn utf16_is_big_endian_on_any_host() {
+        let decode = |bytes: &[u8]| Record::from_utf16(alloc::alloc::Global, bytes);
+
+        // "Aé😀" written out in big-endian, the last as a surrogate pair
+        let big_endian = [0x00, 0x41, 0x00, 0xe9, 0xd8, 0x3d, 0xde, 0x00];
+        assert_eq!(&*decode(&big_endian), "Aé😀");
+        // The same units little-endian are different characters, so a host
+        // order read would show up here
+        let little_endian = [0x41, 0x00, 0xe9, 0x00];
+        assert_eq!(&*decode(&little_endian), "\u{4100}\u{e900}");
+
+        // A trailing odd byte is dropped, and a lone surrogate replaced
+        assert_eq!(&*decode(&[0x00, 0x41, 0x00]), "A");
+        assert_eq!(&*decode(&[0xd8, 0x3d, 0x00, 0x41]), "\u{fffd}A");
+    }
+}
@@ -0,0 +1,322 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use core::marker::PhantomData;
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreReadError,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Vertical metrics, in font units
+#[derive(Debug, Clone, Copy)]
+pub struct VerticalMetrics {
+    pub typo_ascender:  i16,
+    pub typo_descender: i16,
+    pub typo_line_gap:  i16,
+    pub win_ascent:     u16,
+    pub win_descent:    u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    pub version:            u16,
+    pub avg_char_width:     i16,
+    /// 100 (thin) to 900 (black), 400 being regular
+    pub weight_class:       u16,
+    /// 1 (ultra-condensed) to 9 (ultra-expanded), 5 being normal
+    pub width_class:        u16,
+    /// Embedding permissions
+    pub fs_type:            u16,
+    pub strikeout_size:     i16,
+    pub strikeout_position: i16,
+    pub family_class:       i16,
+    pub panose:             [u8; 10],
+    /// Bits of `ulUnicodeRange1` to `ulUnicodeRange4`
+    pub unicode_range:      [u32; 4],
+    pub vendor_id:          [u8; 4],
+    pub fs_selection:       u16,
+    pub first_char_index:   u16,
+    pub last_char_index:    u16,
+
+    // Missing from tables that end before the version's fields, as
+    // older Apple fonts do
+    pub vertical_metrics:   Option<VerticalMetrics>,
+    /// Version 1+
+    pub code_page_range:    Option<[u32; 2]>,
+    /// Version 2+
+    pub x_height:           Option<i16>,
+    /// Version 2+
+    pub cap_height:         Option<i16>,
+    /// Version 5+, (lower, upper) in twentieths of a point
+    pub optical_point_size: Option<(u16, u16)>,
+
+    _phantom: PhantomData<A>,
+}
+
+/// Unicode block and the `ulUnicodeRange` bit covering it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnicodeBlock {
+    pub name:   &'static str,
+    pub first:  u32,
+    pub last:   u32,
+    /// Counting from bit 0 of `ulUnicodeRange1` to bit 31 of `ulUnicodeRange4`
+    pub bit:    u8,
+    /// OpenType tag of the script written with the block, `None` for symbols
+    /// and punctuation shared between scripts
+    pub script: Option<[u8; 4]>,
+}
+
+const fn block(
+    bit: u8,
+    first: u32,
+    last: u32,
+    name: &'static str,
+    script: Option<[u8; 4]>,
+) -> UnicodeBlock {
+    UnicodeBlock {
+        name,
+        first,
+        last,
+        bit,
+        script,
+    }
+}
+
+/// Blocks given a `ulUnicodeRange` bit, in code point order. Covers bits 0 to
+/// 84 bar the surrogates of bit 57, with some bits spanning several blocks
+pub const UNICODE_BLOCKS: &[UnicodeBlock] = &[
+    block(0, 0x0000, 0x007f, "Basic Latin", Some(*b"latn")),
+    block(1, 0x0080, 0x00ff, "Latin-1 Supplement", Some(*b"latn")),
+    block(2, 0x0100, 0x017f, "Latin Extended-A", Some(*b"latn")),
+    block(3, 0x0180, 0x024f, "Latin Extended-B", Some(*b"latn")),
+    block(4, 0x0250, 0x02af, "IPA Extensions", Some(*b"latn")),
+    block(5, 0x02b0, 0x02ff, "Spacing Modifier Letters", None),
+    block(6, 0x0300, 0x036f, "Combining Diacritical Marks", None),
+    block(7, 0x0370, 0x03ff, "Greek and Coptic", Some(*b"grek")),
+    block(9, 0x0400, 0x04ff, "Cyrillic", Some(*b"cyrl")),
+    block(10, 0x0530, 0x058f, "Armenian", Some(*b"armn")),
+    block(11, 0x0590, 0x05ff, "Hebrew", Some(*b"hebr")),
+    block(13, 0x0600, 0x06ff, "Arabic", Some(*b"arab")),
+    block(71, 0x0700, 0x074f, "Syriac", Some(*b"syrc")),
+    block(72, 0x0780, 0x07bf, "Thaana", Some(*b"thaa")),
+    block(14, 0x07c0, 0x07ff, "NKo", Some(*b"nko ")),
+    block(15, 0x0900, 0x097f, "Devanagari", Some(*b"deva")),
+    block(16, 0x0980, 0x09ff, "Bengali", Some(*b"beng")),
+    block(17, 0x0a00, 0x0a7f, "Gurmukhi", Some(*b"guru")),
+    block(18, 0x0a80, 0x0aff, "Gujarati", Some(*b"gujr")),
+    block(19, 0x0b00, 0x0b7f, "Oriya", Some(*b"orya")),
+    block(20, 0x0b80, 0x0bff, "Tamil", Some(*b"taml")),
+    block(21, 0x0c00, 0x0c7f, "Telugu", Some(*b"telu")),
+    block(22, 0x0c80, 0x0cff, "Kannada", Some(*b"knda")),
+    block(23, 0x0d00, 0x0d7f, "Malayalam", Some(*b"mlym")),
+    block(73, 0x0d80, 0x0dff, "Sinhala", Some(*b"sinh")),
+    block(24, 0x0e00, 0x0e7f, "Thai", Some(*b"thai")),
+    block(25, 0x0e80, 0x0eff, "Lao", Some(*b"lao ")),
+    block(70, 0x0f00, 0x0fff, "Tibetan", Some(*b"tibt")),
+    block(74, 0x1000, 0x109f, "Myanmar", Some(*b"mymr")),
+    block(26, 0x10a0, 0x10ff, "Georgian", Some(*b"geor")),
+    block(28, 0x1100, 0x11ff, "Hangul Jamo", Some(*b"hang")),
+    block(75, 0x1200, 0x137f, "Ethiopic", Some(*b"ethi")),
+    block(76, 0x13a0, 0x13ff, "Cherokee", Some(*b"cher")),
+    block(77, 0x1400, 0x167f, "Unified Canadian Aboriginal Syllabics", Some(*b"cans")),
+    block(78, 0x1680, 0x169f, "Ogham", Some(*b"ogam")),
+    block(79, 0x16a0, 0x16ff, "Runic", Some(*b"runr")),
+    block(84, 0x1700, 0x171f, "Tagalog", Some(*b"tglg")),
+    block(80, 0x1780, 0x17ff, "Khmer", Some(*b"khmr")),
+    block(81, 0x1800, 0x18af, "Mongolian", Some(*b"mong")),
+    block(27, 0x1b00, 0x1b7f, "Balinese", Some(*b"bali")),
+    block(29, 0x1e00, 0x1eff, "Latin Extended Additional", Some(*b"latn")),
+    block(30, 0x1f00, 0x1fff, "Greek Extended", Some(*b"grek")),
+    block(31, 0x2000, 0x206f, "General Punctuation", None),
+    block(32, 0x2070, 0x209f, "Superscripts and Subscripts", None),
+    block(33, 0x20a0, 0x20cf, "Currency Symbols", None),
+    block(34, 0x20d0, 0x20ff, "Combining Diacritical Marks for Symbols", None),
+    block(35, 0x2100, 0x214f, "Letterlike Symbols", None),
+    block(36, 0x2150, 0x218f, "Number Forms", None),
+    block(37, 0x2190, 0x21ff, "Arrows", None),
+    block(38, 0x2200, 0x22ff, "Mathematical Operators", None),
+    block(39, 0x2300, 0x23ff, "Miscellaneous Technical", None),
+    block(40, 0x2400, 0x243f, "Control Pictures", None),
+    block(41, 0x2440, 0x245f, "Optical Character Recognition", None),
+    block(42, 0x2460, 0x24ff, "Enclosed Alphanumerics", None),
+    block(43, 0x2500, 0x257f, "Box Drawing", None),
+    block(44, 0x2580, 0x259f, "Block Elements", None),
+    block(45, 0x25a0, 0x25ff, "Geometric Shapes", None),
+    block(46, 0x2600, 0x26ff, "Miscellaneous Symbols", None),
+    block(47, 0x2700, 0x27bf, "Dingbats", None),
+    block(82, 0x2800, 0x28ff, "Braille Patterns", Some(*b"brai")),
+    block(8, 0x2c80, 0x2cff, "Coptic", Some(*b"copt")),
+    block(48, 0x3000, 0x303f, "CJK Symbols and Punctuation", None),
+    block(49, 0x3040, 0x309f, "Hiragana", Some(*b"kana")),
+    block(50, 0x30a0, 0x30ff, "Katakana", Some(*b"kana")),
+    block(51, 0x3100, 0x312f, "Bopomofo", Some(*b"bopo")),
+    block(52, 0x3130, 0x318f, "Hangul Compatibility Jamo", Some(*b"hang")),
+    block(61, 0x31c0, 0x31ef, "CJK Strokes", Some(*b"hani")),
+    block(54, 0x3200, 0x32ff, "Enclosed CJK Letters and Months", None),
+    block(55, 0x3300, 0x33ff, "CJK Compatibility", None),
+    block(59, 0x3400, 0x4dbf, "CJK Unified Ideographs Extension A", Some(*b"hani")),
+    block(59, 0x4e00, 0x9fff, "CJK Unified Ideographs", Some(*b"hani")),
+    block(83, 0xa000, 0xa48f, "Yi Syllables", Some(*b"yi  ")),
+    block(12, 0xa500, 0xa63f, "Vai", Some(*b"vai ")),
+    block(53, 0xa840, 0xa87f, "Phags-pa", Some(*b"phag")),
+    block(56, 0xac00, 0xd7af, "Hangul Syllables", Some(*b"hang")),
+    block(60, 0xe000, 0xf8ff, "Private Use Area", None),
+    block(61, 0xf900, 0xfaff, "CJK Compatibility Ideographs", Some(*b"hani")),
+    block(62, 0xfb00, 0xfb4f, "Alphabetic Presentation Forms", None),
+    block(63, 0xfb50, 0xfdff, "Arabic Presentation Forms-A", Some(*b"arab")),
+    block(65, 0xfe10, 0xfe1f, "Vertical Forms", None),
+    block(64, 0xfe20, 0xfe2f, "Combining Half Marks", None),
+    block(65, 0xfe30, 0xfe4f, "CJK Compatibility Forms", None),
+    block(66, 0xfe50, 0xfe6f, "Small Form Variants", None),
+    block(67, 0xfe70, 0xfeff, "Arabic Presentation Forms-B", Some(*b"arab")),
+    block(68, 0xff00, 0xffef, "Halfwidth and Fullwidth Forms", None),
+    block(69, 0xfff0, 0xffff, "Specials", None),
+    block(58, 0x10900, 0x1091f, "Phoenician", Some(*b"phnx")),
+    block(59, 0x20000, 0x2a6df, "CJK Unified Ideographs Extension B", Some(*b"hani")),
+];
+
+impl<A: core::alloc::Allocator> Type<A> {
+    /// Whether `block`'s bit is set in `ulUnicodeRange1` to `ulUnicodeRange4`
+    #[must_use]
+    pub fn covers(
+        &self,
+        block: &UnicodeBlock,
+    ) -> bool {
+        self.unicode_range
+            .get(usize::from(block.bit / 32))
+            .is_some_and(|range| range & (1 << (block.bit % 32)) != 0)
+    }
+}
+
+/// Reads the next group of fields, or `None` if the table ended before them
+fn read_group<const N: usize, R: CoreRead>(
+    reader: &mut R
+) -> Result<Option<[u8; N]>, ParseError<R::IoError>> {
+    match reader.read_array() {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(CoreReadError::UnexpectedEnd(needed)) => {
+            tracing::event!(
+                tracing::Level::WARN,
+                "OS/2 ended {needed} bytes short of its version's fields"
+            );
+            Ok(None)
+        },
+        Err(error) => Err(error.into()),
+    }
+}
+
+const fn be_u16(
+    bytes: &[u8],
+    at: usize,
+) -> u16 {
+    u16::from_be_bytes([bytes[at], bytes[at + 1]])
+}
+
+const fn be_i16(
+    bytes: &[u8],
+    at: usize,
+) -> i16 {
+    i16::from_be_bytes([bytes[at], bytes[at + 1]])
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    _allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    let version: u16 = reader.read_int()?;
+    if version > 5 {
+        // Later versions only append fields
+        tracing::event!(
+            tracing::Level::DEBUG,
+            "OS/2 version {version}, reading as version 5"
+        );
+    }
+
+    let avg_char_width: i16 = reader.read_int()?;
+    let weight_class: u16 = reader.read_int()?;
+    let width_class: u16 = reader.read_int()?;
+    let fs_type: u16 = reader.read_int()?;
+
+    // Sub and superscript sizes and offsets
+    let _script_metrics: [u8; 16] = reader.read_array()?;
+
+    let strikeout_size: i16 = reader.read_int()?;
+    let strikeout_position: i16 = reader.read_int()?;
+    let family_class: i16 = reader.read_int()?;
+    let panose: [u8; 10] = reader.read_array()?;
+    let unicode_range = [
+        reader.read_int()?,
+        reader.read_int()?,
+        reader.read_int()?,
+        reader.read_int()?,
+    ];
+    let vendor_id: [u8; 4] = reader.read_array()?;
+    let fs_selection: u16 = reader.read_int()?;
+    let first_char_index: u16 = reader.read_int()?;
+    let last_char_index: u16 = reader.read_int()?;
+
+    let vertical_metrics = read_group::<10, _>(reader)?.map(|b| VerticalMetrics {
+        typo_ascender:  be_i16(&b, 0),
+        typo_descender: be_i16(&b, 2),
+        typo_line_gap:  be_i16(&b, 4),
+        win_ascent:     be_u16(&b, 6),
+        win_descent:    be_u16(&b, 8),
+    });
+
+    let code_page_range = match version {
+        1.. if vertical_metrics.is_some() => read_group::<8, _>(reader)?.map(|b| {
+            [
+                u32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+                u32::from_be_bytes([b[4], b[5], b[6], b[7]]),
+            ]
+        }),
+        _ => None,
+    };
+
+    // usDefaultChar, usBreakChar and usMaxContext follow the heights
+    let heights = match version {
+        2.. if code_page_range.is_some() => read_group::<10, _>(reader)?,
+        _ => None,
+    };
+
+    let optical_point_size = match version {
+        5.. if heights.is_some() => {
+            read_group::<4, _>(reader)?.map(|b| (be_u16(&b, 0), be_u16(&b, 2)))
+        },
+        _ => None,
+    };
+
+    Ok(Type {
+        version,
+        avg_char_width,
+        weight_class,
+        width_class,
+        fs_type,
+        strikeout_size,
+        strikeout_position,
+        family_class,
+        panose,
+        unicode_range,
+        vendor_id,
+        fs_selection,
+        first_char_index,
+        last_char_index,
+        vertical_metrics,
+        code_page_range,
+        x_height: heights.map(|b| be_i16(&b, 0)),
+        cap_height: heights.map(|b| be_i16(&b, 2)),
+        optical_point_size,
+        _phantom: PhantomData,
+    })
+}
@@ -0,0 +1,129 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use core::marker::PhantomData;
+
+use super::Table;
+use crate::{
+    types::CoreRead,
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Typeface information for PCL 5 printers
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    pub font_number:          u32,
+    /// Width of the space glyph, in font units
+    pub pitch:                u16,
+    pub x_height:             u16,
+    pub style:                u16,
+    pub type_family:          u16,
+    pub cap_height:           u16,
+    pub symbol_set:           u16,
+    /// Space padded ASCII
+    pub typeface:             [u8; 16],
+    pub character_complement: [u8; 8],
+    pub file_name:            [u8; 6],
+    pub stroke_weight:        i8,
+    pub width_type:           i8,
+    pub serif_style:          u8,
+
+    _phantom: PhantomData<A>,
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    _allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    let major_version: u16 = reader.read_int()?;
+    let minor_version: u16 = reader.read_int()?;
+
+    if major_version != 1 {
+        return Err(ParseError::UnsupportedVersion {
+            tag:       *b"PCLT",
+            version:   (u32::from(major_version) << u16::BITS) | u32::from(minor_version),
+            supported: &[0x0001_0000],
+        });
+    }
+
+    let table = Type {
+        font_number:          reader.read_int()?,
+        pitch:                reader.read_int()?,
+        x_height:             reader.read_int()?,
+        style:                reader.read_int()?,
+        type_family:          reader.read_int()?,
+        cap_height:           reader.read_int()?,
+        symbol_set:           reader.read_int()?,
+        typeface:             reader.read_array()?,
+        character_complement: reader.read_array()?,
+        file_name:            reader.read_array()?,
+        stroke_weight:        reader.read_int()?,
+        width_type:           reader.read_int()?,
+        serif_style:          reader.read_int()?,
+
+        _phantom: PhantomData,
+    };
+
+    // reserved
+    let _: u8 = reader.read_int()?;
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testing::{
+            be,
+            head,
+            maxp,
+            sfnt,
+        },
+        FontTrait,
+    };
+
+    /// Version 1.0 `PCLT` with the given pitch and x-height
+    fn pclt(
+        pitch: u16,
+        x_height: u16,
+    ) -> Vec<u8> {
+        let mut bytes = be!(0x0001_0000_u32, 42u32, pitch, x_height, 0u16, 0u16, 700u16, 0u16);
+        bytes.extend(b"Test            ");
+        bytes.extend([0; 8]);
+        bytes.extend(b"TEST00");
+        bytes.extend(be!(3i8, 0i8, 0u8, 0u8));
+        bytes
+    }
+
+    #[test]
+    fn pitch_and_x_height_are_read() {
+        let font = sfnt(&[
+            (*b"head", &head(1000, 0, true)),
+            (*b"maxp", &maxp(1)),
+            (*b"PCLT", &pclt(250, 480)),
+        ]);
+
+        let font = crate::parse_font_bytes(&font).expect("Font should parse");
+        let table = font.pclt().expect("PCLT should parse");
+        assert_eq!((table.pitch, table.x_height), (250, 480));
+        assert_eq!((table.font_number, table.cap_height), (42, 700));
+        assert_eq!(&table.typeface, b"Test            ");
+        assert_eq!(table.stroke_weight, 3);
+
+        // Missing its reserved byte
+        let mut short = pclt(250, 480);
+        short.pop();
+        let font = sfnt(&[
+            (*b"head", &head(1000, 0, true)),
+            (*b"maxp", &maxp(1)),
+            (*b"PCLT", &short),
+        ]);
+        assert!(crate::parse_font_bytes(&font).is_err());
+    }
+}
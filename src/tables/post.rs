@@ -0,0 +1,193 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+use super::Table;
+use crate::{
+    types::{
+        CoreRead,
+        CoreVec,
+    },
+    FontOptions,
+    ParseError,
+};
+
+pub type ParsedType<A> = Type<A>;
+
+/// Names of the standard Macintosh glyph order, which version 1.0 fonts follow
+/// and version 2.0 fonts index into for their first 258 names
+const MAC_GLYPH_NAMES: [&str; 258] = [
+    ".notdef", ".null", "nonmarkingreturn", "space", "exclam", "quotedbl", "numbersign", "dollar",
+    "percent", "ampersand", "quotesingle", "parenleft", "parenright", "asterisk", "plus", "comma",
+    "hyphen", "period", "slash", "zero", "one", "two", "three", "four", "five", "six", "seven",
+    "eight", "nine", "colon", "semicolon", "less", "equal", "greater", "question", "at", "A", "B",
+    "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U",
+    "V", "W", "X", "Y", "Z", "bracketleft", "backslash", "bracketright", "asciicircum",
+    "underscore", "grave", "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n",
+    "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z", "braceleft", "bar", "braceright",
+    "asciitilde", "Adieresis", "Aring", "Ccedilla", "Eacute", "Ntilde", "Odieresis", "Udieresis",
+    "aacute", "agrave", "acircumflex", "adieresis", "atilde", "aring", "ccedilla", "eacute",
+    "egrave", "ecircumflex", "edieresis", "iacute", "igrave", "icircumflex", "idieresis", "ntilde",
+    "oacute", "ograve", "ocircumflex", "odieresis", "otilde", "uacute", "ugrave", "ucircumflex",
+    "udieresis", "dagger", "degree", "cent", "sterling", "section", "bullet", "paragraph",
+    "germandbls", "registered", "copyright", "trademark", "acute", "dieresis", "notequal", "AE",
+    "Oslash", "infinity", "plusminus", "lessequal", "greaterequal", "yen", "mu", "partialdiff",
+    "summation", "product", "pi", "integral", "ordfeminine", "ordmasculine", "Omega", "ae",
+    "oslash", "questiondown", "exclamdown", "logicalnot", "radical", "florin", "approxequal",
+    "Delta", "guillemotleft", "guillemotright", "ellipsis", "nonbreakingspace", "Agrave", "Atilde",
+    "Otilde", "OE", "oe", "endash", "emdash", "quotedblleft", "quotedblright", "quoteleft",
+    "quoteright", "divide", "lozenge", "ydieresis", "Ydieresis", "fraction", "currency",
+    "guilsinglleft", "guilsinglright", "fi", "fl", "daggerdbl", "periodcentered", "quotesinglbase",
+    "quotedblbase", "perthousand", "Acircumflex", "Ecircumflex", "Aacute", "Edieresis", "Egrave",
+    "Iacute", "Icircumflex", "Idieresis", "Igrave", "Oacute", "Ocircumflex", "apple", "Ograve",
+    "Uacute", "Ucircumflex", "Ugrave", "dotlessi", "circumflex", "tilde", "macron", "breve",
+    "dotaccent", "ring", "cedilla", "hungarumlaut", "ogonek", "caron", "Lslash", "lslash", "Scaron",
+    "scaron", "Zcaron", "zcaron", "brokenbar", "Eth", "eth", "Yacute", "yacute", "Thorn", "thorn",
+    "minus", "multiply", "onesuperior", "twosuperior", "threesuperior", "onehalf", "onequarter",
+    "threequarters", "franc", "Gbreve", "gbreve", "Idotaccent", "Scedilla", "scedilla", "Cacute",
+    "cacute", "Ccaron", "ccaron", "dcroat",
+];
+
+#[derive(Debug, Clone)]
+enum Names<A: core::alloc::Allocator> {
+    /// Version 1.0, glyphs are in the standard Macintosh order
+    Standard,
+    /// Version 2.0
+    Custom {
+        /// Index of each glyph's name, below 258 into [`MAC_GLYPH_NAMES`],
+        /// otherwise into `names`
+        indices: CoreVec<u16, A>,
+        data:    CoreVec<u8, A>,
+        /// Ranges of `data` holding each name
+        names:   CoreVec<core::range::Range<usize>, A>,
+    },
+    /// Version 3.0, or a version whose names aren't supported
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct Type<A: core::alloc::Allocator> {
+    pub version:             u32,
+    /// Degrees counter-clockwise from vertical, negative for forward-leaning
+    /// italics
+    pub italic_angle:        fixed::types::I16F16,
+    /// Top of the underline relative to the baseline, in font units
+    pub underline_position:  i16,
+    pub underline_thickness: i16,
+    pub is_fixed_pitch:      bool,
+
+    names: Names<A>,
+}
+
+impl<A: core::alloc::Allocator> Type<A> {
+    /// PostScript name of `glyph_id`, `None` if the table has no names or the
+    /// glyph's isn't valid UTF-8
+    pub fn glyph_name(
+        &self,
+        glyph_id: u32,
+    ) -> Option<&str> {
+        match &self.names {
+            Names::Standard => MAC_GLYPH_NAMES.get(glyph_id as usize).copied(),
+            Names::Custom {
+                indices,
+                data,
+                names,
+            } => {
+                let index = usize::from(*indices.get(glyph_id as usize)?);
+                match index.checked_sub(MAC_GLYPH_NAMES.len()) {
+                    None => Some(MAC_GLYPH_NAMES[index]),
+                    Some(index) => {
+                        let range = names.get(index)?;
+                        core::str::from_utf8(data.get(range.start..range.end)?).ok()
+                    },
+                }
+            },
+            Names::None => None,
+        }
+    }
+}
+
+/// Reads version 2.0's name indices and the Pascal strings following them
+fn parse_names<A: core::alloc::Allocator + Copy, R: CoreRead>(
+    allocator: A,
+    reader: &mut R,
+) -> Result<Names<A>, ParseError<R::IoError>> {
+    let num_glyphs: u16 = reader.read_int()?;
+    let mut indices = CoreVec::with_capacity_in(usize::from(num_glyphs), allocator);
+    for _ in 0..num_glyphs {
+        indices.push(reader.read_int()?);
+    }
+
+    // Strings run to the end of the table, each prefixed by its length
+    let mut data = CoreVec::new_in(allocator);
+    reader.read_to_vec(&mut data)?;
+
+    let mut names = CoreVec::new_in(allocator);
+    let mut start = 0;
+    while let Some(len) = data.get(start) {
+        let end = start + 1 + usize::from(*len);
+        if end > data.len() {
+            tracing::event!(
+                tracing::Level::WARN,
+                "post name {} runs {} bytes past the table",
+                names.len(),
+                end - data.len()
+            );
+            break;
+        }
+        names.push(core::range::Range {
+            start: start + 1,
+            end,
+        });
+        start = end;
+    }
+
+    Ok(Names::Custom {
+        indices,
+        data,
+        names,
+    })
+}
+
+#[tracing::instrument(skip_all, level = "trace")]
+pub fn parse_table<A: core::alloc::Allocator + Copy + core::fmt::Debug, R: CoreRead>(
+    allocator: A,
+    _options: &FontOptions,
+    _prev_tables: &[Table<A>],
+    reader: &mut R,
+) -> Result<Type<A>, ParseError<R::IoError>> {
+    let version: u32 = reader.read_int()?;
+    let italic_angle = fixed::types::I16F16::from_be_bytes(reader.read_array()?);
+    let underline_position: i16 = reader.read_int()?;
+    let underline_thickness: i16 = reader.read_int()?;
+    let is_fixed_pitch: u32 = reader.read_int()?;
+
+    // Memory usage hints for PostScript printers
+    let _memory_usage: [u8; 16] = reader.read_array()?;
+
+    let names = match version {
+        0x0001_0000 => Names::Standard,
+        0x0002_0000 => parse_names(allocator, reader)?,
+        0x0003_0000 => Names::None,
+        // Deprecated offsets into the standard order
+        0x0002_5000 => {
+            tracing::event!(tracing::Level::DEBUG, "post version 2.5 names are ignored");
+            Names::None
+        },
+        _ => {
+            return Err(ParseError::UnsupportedVersion {
+                tag: *b"post",
+                version,
+                supported: &[0x0001_0000, 0x0002_0000, 0x0002_5000, 0x0003_0000],
+            })
+        },
+    };
+
+    Ok(Type {
+        version,
+        italic_angle,
+        underline_position,
+        underline_thickness,
+        is_fixed_pitch: is_fixed_pitch != 0,
+        names,
+    })
+}
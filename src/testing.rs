@@ -0,0 +1,384 @@
+// Copyright (C) 2024 GLStudios
+// SPDX-License-Identifier: LGPL-2.1-only
+
+//! Builders for the small fonts parsed by the tests, as no font files ship
+//! with the crate
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+
+/// Big-endian bytes of every value, e.g. `be!(1u16, -2i16)`
+macro_rules! be {
+    ($($value:expr),* $(,)?) => {{
+        let mut bytes = Vec::new();
+        $(bytes.extend_from_slice(&$value.to_be_bytes());)*
+        bytes
+    }};
+}
+pub(crate) use be;
+
+/// Sum of `bytes` as big-endian `u32`s, zero-padded to a multiple of 4
+pub fn checksum(bytes: &[u8]) -> u32 {
+    bytes.chunks(4).fold(0, |sum: u32, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// sfnt with `tables` stored in the given order, as tables are parsed in file
+/// order and some need others parsed first. Records are sorted by tag, and the
+/// checksums and `head`'s ChecksumAdjustment filled in
+pub fn sfnt(tables: &[([u8; 4], &[u8])]) -> Vec<u8> {
+    let num_tables = u16::try_from(tables.len()).expect("Too many tables");
+    let entry_selector = num_tables.checked_ilog2().unwrap_or(0);
+    let search_range: u16 = 16 << entry_selector;
+
+    let mut bytes = be!(0x0001_0000u32, num_tables, search_range);
+    bytes.extend(be!(entry_selector as u16, num_tables * 16 - search_range));
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut records = Vec::new();
+    for (tag, table) in tables {
+        records.push((*tag, checksum(table), offset, table.len()));
+        offset += table.len().next_multiple_of(4);
+    }
+    records.sort_by_key(|(tag, ..)| *tag);
+    for (tag, checksum, offset, length) in records {
+        bytes.extend_from_slice(&tag);
+        bytes.extend(be!(checksum, offset as u32, length as u32));
+    }
+
+    let mut head = None;
+    for (tag, table) in tables {
+        if tag == b"head" {
+            head = Some(bytes.len());
+        }
+        bytes.extend_from_slice(table);
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head) = head {
+        let adjustment = 0xb1b0_afba_u32.wrapping_sub(checksum(&bytes));
+        bytes[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    bytes
+}
+
+/// Version 1.0 `head`, with glyphs bounded by (0, 0) and the em square
+pub fn head(
+    units_per_em: u16,
+    direction_hint: i16,
+    long_loca: bool,
+) -> Vec<u8> {
+    let mut bytes = be!(0x0001_0000u32, 0x0001_0000u32, 0u32, 0x5f0f_3cf5_u32, 0u16);
+    bytes.extend(be!(units_per_em, 0i64, 0i64));
+    bytes.extend(be!(0i16, 0i16, units_per_em as i16, units_per_em as i16));
+    bytes.extend(be!(0u16, 8u16, direction_hint, u16::from(long_loca), 0u16));
+    bytes
+}
+
+/// Version 0.5 `maxp`
+pub fn maxp(num_glyphs: u16) -> Vec<u8> {
+    be!(0x0000_5000u32, num_glyphs)
+}
+
+/// `hhea` with an upright caret
+pub fn hhea(
+    ascender: i16,
+    descender: i16,
+    num_hmetrics: u16,
+) -> Vec<u8> {
+    let mut bytes = be!(0x0001_0000u32, ascender, descender, 0i16, 0u16);
+    bytes.extend(be!(0i16, 0i16, 0i16, 1i16, 0i16, 0i16));
+    bytes.extend(be!(0u64, 0i16, num_hmetrics));
+    bytes
+}
+
+/// `hmtx` of `(advance, left side bearing)` metrics, followed by the bearings
+/// of glyphs sharing the last advance
+pub fn hmtx(
+    metrics: &[(u16, i16)],
+    bearings: &[i16],
+) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (advance, bearing) in metrics {
+        bytes.extend(be!(*advance, *bearing));
+    }
+    for bearing in bearings {
+        bytes.extend(be!(*bearing));
+    }
+    bytes
+}
+
+/// Corners of the rectangle running clockwise from (`x_min`, `y_min`), as
+/// TrueType's outer contours do
+pub const fn rect(
+    x_min: i16,
+    y_min: i16,
+    x_max: i16,
+    y_max: i16,
+) -> [(i16, i16, bool); 4] {
+    [
+        (x_min, y_min, true),
+        (x_min, y_max, true),
+        (x_max, y_max, true),
+        (x_max, y_min, true),
+    ]
+}
+
+/// Simple glyph of `(x, y, on_curve)` contours, with every coordinate stored
+/// as a full `i16`
+pub fn simple_glyph(contours: &[&[(i16, i16, bool)]]) -> Vec<u8> {
+    let points = contours.iter().flat_map(|contour| contour.iter());
+    let (x_min, y_min, x_max, y_max) = points.clone().fold(
+        (i16::MAX, i16::MAX, i16::MIN, i16::MIN),
+        |(x_min, y_min, x_max, y_max), (x, y, _)| {
+            (x_min.min(*x), y_min.min(*y), x_max.max(*x), y_max.max(*y))
+        },
+    );
+
+    let mut bytes = be!(contours.len() as i16, x_min, y_min, x_max, y_max);
+    let mut end = 0;
+    for contour in contours {
+        end += contour.len();
+        bytes.extend(be!(end as u16 - 1));
+    }
+    // No instructions
+    bytes.extend(be!(0u16));
+
+    bytes.extend(points.clone().map(|(_, _, on_curve)| u8::from(*on_curve)));
+    let mut prev = (0i16, 0i16);
+    let deltas: Vec<_> = points
+        .map(|(x, y, _)| {
+            let delta = (x - prev.0, y - prev.1);
+            prev = (*x, *y);
+            delta
+        })
+        .collect();
+    for (dx, _) in &deltas {
+        bytes.extend(be!(*dx));
+    }
+    for (_, dy) in &deltas {
+        bytes.extend(be!(*dy));
+    }
+    bytes
+}
+
+/// Composite glyph placing each `(glyph_id, dx, dy)`
+pub fn composite_glyph(components: &[(u16, i16, i16)]) -> Vec<u8> {
+    const ARGS_ARE_WORDS: u16 = 1 << 0;
+    const ARGS_ARE_XY_VALUES: u16 = 1 << 1;
+    const MORE_COMPONENTS: u16 = 1 << 5;
+
+    // Bounds are recomputed from the components when parsed
+    let mut bytes = be!(-1i16, 0i16, 0i16, 0i16, 0i16);
+    for (idx, (glyph_id, dx, dy)) in components.iter().enumerate() {
+        let more = if idx + 1 < components.len() {
+            MORE_COMPONENTS
+        } else {
+            0
+        };
+        bytes.extend(be!(
+            ARGS_ARE_WORDS | ARGS_ARE_XY_VALUES | more,
+            *glyph_id,
+            *dx,
+            *dy
+        ));
+    }
+    bytes
+}
+
+/// `glyf` holding `glyphs`, each padded to an even length, with the `loca`
+/// locating them
+pub fn glyf_loca(
+    glyphs: &[Vec<u8>],
+    long_loca: bool,
+) -> (Vec<u8>, Vec<u8>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::new();
+    let mut push_offset = |offset: usize| {
+        if long_loca {
+            loca.extend(be!(offset as u32));
+        } else {
+            loca.extend(be!((offset / 2) as u16));
+        }
+    };
+
+    for glyph in glyphs {
+        push_offset(glyf.len());
+        glyf.extend_from_slice(glyph);
+        glyf.resize(glyf.len().next_multiple_of(2), 0);
+    }
+    push_offset(glyf.len());
+
+    (glyf, loca)
+}
+
+/// `cmap` of `(platform_id, encoding_id, subtable)` encoding records
+pub fn cmap(subtables: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+    let mut offset = 4 + 8 * subtables.len();
+    let mut bytes = be!(0u16, subtables.len() as u16);
+    for (platform_id, encoding_id, subtable) in subtables {
+        bytes.extend(be!(*platform_id, *encoding_id, offset as u32));
+        offset += subtable.len();
+    }
+    for (_, _, subtable) in subtables {
+        bytes.extend_from_slice(subtable);
+    }
+    bytes
+}
+
+/// Format 4 subtable mapping each character to a glyph id through its own
+/// segment
+pub fn cmap4(mappings: &[(char, u16)]) -> Vec<u8> {
+    let mut mappings = mappings.to_vec();
+    mappings.sort_unstable();
+    let seg_count = mappings.len() as u16 + 1;
+
+    let mut bytes = be!(
+        4u16,
+        16 + 8 * seg_count,
+        0u16,
+        seg_count * 2,
+        0u16,
+        0u16,
+        0u16
+    );
+    for (c, _) in &mappings {
+        bytes.extend(be!(*c as u16));
+    }
+    bytes.extend(be!(0xffff_u16, 0u16));
+    for (c, _) in &mappings {
+        bytes.extend(be!(*c as u16));
+    }
+    bytes.extend(be!(0xffff_u16));
+    for (c, glyph_id) in &mappings {
+        bytes.extend(be!(glyph_id.wrapping_sub(*c as u16)));
+    }
+    bytes.extend(be!(1u16));
+    bytes.extend(vec![0; usize::from(seg_count) * 2]);
+    bytes
+}
+
+/// TrueType font with 1000 units per em of `glyphs`, each with its advance,
+/// and a Unicode `cmap` of `mappings`
+pub fn font(
+    glyphs: &[(Vec<u8>, u16)],
+    mappings: &[(char, u16)],
+) -> Vec<u8> {
+    font_with(glyphs, mappings, &[])
+}
+
+/// [`font`] with `extra` tables stored after the others
+pub fn font_with(
+    glyphs: &[(Vec<u8>, u16)],
+    mappings: &[(char, u16)],
+    extra: &[([u8; 4], &[u8])],
+) -> Vec<u8> {
+    let outlines: Vec<_> = glyphs.iter().map(|(glyph, _)| glyph.clone()).collect();
+    let (glyf, loca) = glyf_loca(&outlines, true);
+    // Bearings match the bounding boxes
+    let metrics: Vec<_> = glyphs
+        .iter()
+        .map(|(glyph, advance)| {
+            let x_min = glyph
+                .get(2..4)
+                .map_or(0, |x| i16::from_be_bytes([x[0], x[1]]));
+            (*advance, x_min)
+        })
+        .collect();
+
+    let (head, maxp, hhea) = (
+        head(1000, 2, true),
+        maxp(glyphs.len() as u16),
+        hhea(800, -200, glyphs.len() as u16),
+    );
+    let (hmtx, cmap) = (hmtx(&metrics, &[]), cmap(&[(3, 1, cmap4(mappings))]));
+    let mut tables = vec![
+        (*b"head", head.as_slice()),
+        (*b"maxp", &maxp),
+        (*b"hhea", &hhea),
+        (*b"hmtx", &hmtx),
+        (*b"loca", &loca),
+        (*b"glyf", &glyf),
+        (*b"cmap", &cmap),
+    ];
+    tables.extend_from_slice(extra);
+    sfnt(&tables)
+}
+
+/// `OS/2` of `version`, cut short after the fields it has. Heights are
+/// (`sxHeight`, `sCapHeight`), only kept from version 2
+pub fn os2(
+    version: u16,
+    unicode_range: [u32; 4],
+    (x_height, cap_height): (i16, i16),
+) -> Vec<u8> {
+    let mut bytes = be!(version, 500i16, 400u16, 5u16, 0u16);
+    bytes.extend([0; 16]);
+    bytes.extend(be!(50i16, 300i16, 0i16));
+    bytes.extend([0; 10]);
+    for range in unicode_range {
+        bytes.extend(be!(range));
+    }
+    bytes.extend(b"NONE");
+    bytes.extend(be!(0x0040_u16, 0x20u16, 0xffff_u16));
+    bytes.extend(be!(800i16, -200i16, 0i16, 800u16, 200u16));
+    if version >= 1 {
+        bytes.extend(be!(1u32, 0u32));
+    }
+    if version >= 2 {
+        bytes.extend(be!(x_height, cap_height, 0u16, 0x20u16, 1u16));
+    }
+    bytes
+}
+
+/// Version 0 `name` of `(platform_id, encoding_id, language_id, name_id,
+/// string)` records
+pub fn name(records: &[(u16, u16, u16, u16, &[u8])]) -> Vec<u8> {
+    let count = u16::try_from(records.len()).expect("Too many records");
+    let mut bytes = be!(0u16, count, 6 + 12 * count);
+    let mut storage = Vec::new();
+    for (platform_id, encoding_id, language_id, name_id, string) in records {
+        let length = u16::try_from(string.len()).expect("String too long");
+        let offset = u16::try_from(storage.len()).expect("Storage too long");
+        bytes.extend(be!(
+            *platform_id,
+            *encoding_id,
+            *language_id,
+            *name_id,
+            length,
+            offset
+        ));
+        storage.extend_from_slice(string);
+    }
+    bytes.extend(storage);
+    bytes
+}
+
+/// Version 0 `kern` of a single horizontal subtable of `pairs`, which must be
+/// sorted
+pub fn kern(pairs: &[((u16, u16), i16)]) -> Vec<u8> {
+    let num_pairs = pairs.len() as u16;
+    let mut bytes = be!(0u16, 1u16, 0u16, 14 + 6 * num_pairs, 0x0001_u16);
+    bytes.extend(be!(num_pairs, 0u16, 0u16, 0u16));
+    for ((left, right), value) in pairs {
+        bytes.extend(be!(*left, *right, *value));
+    }
+    bytes
+}
+
+/// [`font`] with an empty `.notdef` and a 600 unit wide square for 'A' as glyph
+/// 1
+pub fn square_font() -> Vec<u8> {
+    font(
+        &[
+            (Vec::new(), 500),
+            (simple_glyph(&[&rect(100, 0, 500, 400)]), 600),
+        ],
+        &[('A', 1)],
+    )
+}
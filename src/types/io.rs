@@ -18,25 +18,56 @@ pub trait CoreRead {
         buf: &mut [u8],
     ) -> Result<usize, CoreReadError<Self::IoError>>;
 
+    /// Discards up to `skip` bytes, returning how many there were
     fn skip(
         &mut self,
         skip: usize,
     ) -> Result<usize, CoreReadError<Self::IoError>> {
-        let mut buf = [0u8; 1];
+        let mut chunk = [0u8; 512];
 
         let mut total = 0;
         while total != skip {
-            let read = self.read(&mut buf)?;
+            let wanted = chunk.len().min(skip - total);
+            let read = self.read(&mut chunk[..wanted])?;
+            if read == 0 {
+                break;
+            }
             total += read;
+        }
+
+        Ok(total)
+    }
+
+    /// Appends everything left in the reader to `buf`
+    fn read_to_vec<A: core::alloc::Allocator>(
+        &mut self,
+        buf: &mut super::CoreVec<u8, A>,
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        let mut chunk = [0u8; 512];
 
-            if read != buf.len() {
+        let mut total = 0;
+        loop {
+            let read = self.read(&mut chunk)?;
+            if read == 0 {
                 break;
             }
+            buf.extend_from_slice(&chunk[..read]);
+            total += read;
         }
 
         Ok(total)
     }
 
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], CoreReadError<Self::IoError>> {
+        let mut bytes = [0u8; N];
+        let read = self.read(&mut bytes)?;
+        if read == bytes.len() {
+            Ok(bytes)
+        } else {
+            Err(CoreReadError::UnexpectedEnd(bytes.len() - read))
+        }
+    }
+
     fn read_int<T: num_traits::PrimInt + bytemuck::AnyBitPattern>(
         &mut self
     ) -> Result<T, CoreReadError<Self::IoError>>
@@ -65,6 +96,44 @@ impl<T: std::io::Read> CoreRead for T {
     }
 }
 
+pub struct SliceReader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, index: 0 }
+    }
+}
+
+impl CoreRead for SliceReader<'_> {
+    type IoError = core::convert::Infallible;
+
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        let remaining = &self.bytes[self.index..];
+        let read = buf.len().min(remaining.len());
+
+        buf[..read].copy_from_slice(&remaining[..read]);
+        self.index += read;
+
+        Ok(read)
+    }
+
+    fn skip(
+        &mut self,
+        skip: usize,
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        let skipped = skip.min(self.bytes.len() - self.index);
+        self.index += skipped;
+
+        Ok(skipped)
+    }
+}
+
 pub struct TrackingReader<'a, R: CoreRead> {
     reader: &'a mut R,
     index:  usize,
@@ -93,6 +162,54 @@ impl<R: CoreRead> CoreRead for TrackingReader<'_, R> {
     ) -> Result<usize, CoreReadError<Self::IoError>> {
         self.reader.read(buf).inspect(|read| self.index += read)
     }
+
+    fn skip(
+        &mut self,
+        skip: usize,
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        self.reader
+            .skip(skip)
+            .inspect(|skipped| self.index += skipped)
+    }
+}
+
+/// Stops reading after `limit` bytes, keeping table parsers within their
+/// table
+pub struct LimitReader<'a, R: CoreRead> {
+    reader: &'a mut R,
+    limit:  usize,
+}
+
+impl<'a, R: CoreRead> LimitReader<'a, R> {
+    pub const fn new(
+        reader: &'a mut R,
+        limit: usize,
+    ) -> Self {
+        Self { reader, limit }
+    }
+}
+
+impl<R: CoreRead> CoreRead for LimitReader<'_, R> {
+    type IoError = R::IoError;
+
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        let len = buf.len().min(self.limit);
+        self.reader
+            .read(&mut buf[..len])
+            .inspect(|read| self.limit -= read)
+    }
+
+    fn skip(
+        &mut self,
+        skip: usize,
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        self.reader
+            .skip(skip.min(self.limit))
+            .inspect(|skipped| self.limit -= skipped)
+    }
 }
 
 pub struct ChecksumReader<'a, R: CoreRead> {
@@ -161,3 +278,167 @@ impl<R: CoreRead> CoreRead for ChecksumReader<'_, R> {
         })
     }
 }
+
+/// Buffers up to `N` bytes so parsers can look ahead without consuming them
+pub struct PeekReader<'a, R: CoreRead, const N: usize> {
+    reader: &'a mut R,
+
+    buffer: [u8; N],
+    // Peeked bytes not yet consumed are `buffer[start..start + len]`
+    start:  usize,
+    len:    usize,
+}
+
+impl<'a, R: CoreRead, const N: usize> PeekReader<'a, R, N> {
+    pub const fn new(reader: &'a mut R) -> Self {
+        Self {
+            reader,
+            buffer: [0; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Fills `buf` with upcoming bytes without consuming them, returning how
+    /// many were available. At most `N` bytes can be peeked
+    pub fn peek(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<usize, CoreReadError<R::IoError>> {
+        let wanted = buf.len().min(N);
+
+        if self.len < wanted {
+            self.buffer
+                .copy_within(self.start..self.start + self.len, 0);
+            self.start = 0;
+
+            while self.len < wanted {
+                let read = self.reader.read(&mut self.buffer[self.len..wanted])?;
+                if read == 0 {
+                    break;
+                }
+                self.len += read;
+            }
+        }
+
+        let peeked = wanted.min(self.len);
+        buf[..peeked].copy_from_slice(&self.buffer[self.start..self.start + peeked]);
+        Ok(peeked)
+    }
+}
+
+impl<R: CoreRead, const N: usize> CoreRead for PeekReader<'_, R, N> {
+    type IoError = R::IoError;
+
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> Result<usize, CoreReadError<Self::IoError>> {
+        let buffered = buf.len().min(self.len);
+        buf[..buffered].copy_from_slice(&self.buffer[self.start..self.start + buffered]);
+        self.start += buffered;
+        self.len -= buffered;
+
+        if buffered == buf.len() {
+            return Ok(buffered);
+        }
+        self.reader
+            .read(&mut buf[buffered..])
+            .map(|read| buffered + read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_array_fills_the_array() {
+        let mut reader = SliceReader::new(b"glyfloca");
+        assert!(matches!(reader.read_array::<4>(), Ok(tag) if tag == *b"glyf"));
+        assert!(matches!(reader.read_array::<4>(), Ok(tag) if tag == *b"loca"));
+
+        let mut reader = std::io::Cursor::new(b"head");
+        assert!(matches!(reader.read_array::<4>(), Ok(tag) if tag == *b"head"));
+    }
+
+    #[test]
+    fn read_array_rejects_short_reads() {
+        let mut reader = SliceReader::new(b"cm");
+        assert!(matches!(
+            reader.read_array::<4>(),
+            Err(CoreReadError::UnexpectedEnd(2))
+        ));
+
+        let mut reader = std::io::Cursor::new(b"");
+        assert!(matches!(
+            reader.read_array::<4>(),
+            Err(CoreReadError::UnexpectedEnd(4))
+        ));
+    }
+
+    /// Only implements `read`, counting the calls
+    struct CountingReader<'a> {
+        reader: SliceReader<'a>,
+        reads:  usize,
+    }
+
+    impl CoreRead for CountingReader<'_> {
+        type IoError = core::convert::Infallible;
+
+        fn read(
+            &mut self,
+            buf: &mut [u8],
+        ) -> Result<usize, CoreReadError<Self::IoError>> {
+            self.reads += 1;
+            self.reader.read(buf)
+        }
+    }
+
+    #[test]
+    fn skip_crosses_large_gaps_in_chunks() {
+        let mut bytes = vec![0u8; 1 << 20];
+        bytes.extend_from_slice(b"tail");
+
+        let mut reader = CountingReader {
+            reader: SliceReader::new(&bytes),
+            reads:  0,
+        };
+        assert!(matches!(reader.skip(1 << 20), Ok(skipped) if skipped == 1 << 20));
+        assert_eq!(reader.reads, (1 << 20) / 512);
+        assert!(matches!(reader.read_array::<4>(), Ok(tail) if tail == *b"tail"));
+
+        // Stops at the end
+        let mut reader = std::io::Cursor::new(&bytes);
+        assert!(matches!(reader.skip(usize::MAX), Ok(skipped) if skipped == bytes.len()));
+
+        let mut reader = SliceReader::new(&bytes);
+        assert!(matches!(reader.skip(1 << 20), Ok(skipped) if skipped == 1 << 20));
+        assert!(matches!(reader.read_array::<4>(), Ok(tail) if tail == *b"tail"));
+        assert!(matches!(reader.skip(1), Ok(0)));
+    }
+
+    #[test]
+    fn peeked_bytes_are_read_again() {
+        let mut source = SliceReader::new(b"headglyfloca");
+        let mut reader = PeekReader::<_, 8>::new(&mut source);
+
+        let mut peeked = [0; 4];
+        assert!(matches!(reader.peek(&mut peeked), Ok(4)));
+        assert_eq!(&peeked, b"head");
+        assert!(matches!(reader.read_array::<4>(), Ok(tag) if tag == *b"head"));
+
+        // Reads past the peeked bytes into the underlying reader
+        let mut peeked = [0; 2];
+        assert!(matches!(reader.peek(&mut peeked), Ok(2)));
+        assert_eq!(&peeked, b"gl");
+        assert!(matches!(reader.read_array::<6>(), Ok(tag) if tag == *b"glyflo"));
+
+        // Only `N` bytes, and no more than remain, can be peeked
+        let mut peeked = [0; 16];
+        assert!(matches!(reader.peek(&mut peeked), Ok(2)));
+        assert_eq!(&peeked[..2], b"ca");
+        assert!(matches!(reader.read_array::<2>(), Ok(tag) if tag == *b"ca"));
+        assert!(matches!(reader.peek(&mut peeked), Ok(0)));
+    }
+}
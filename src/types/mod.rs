@@ -6,7 +6,7 @@ mod slotmap;
 pub use io::*;
 pub use slotmap::{
     Key as SlotmapKey,
-    //SecondaryMap,
+    SecondaryMap,
     Slotmap,
 };
 
@@ -38,7 +38,7 @@ pub enum ValidType {
     // Packed version - 16 major, 16 minor
     PVer(u32),
 
-    _USize(usize),
+    USize(usize),
 }
 
 impl core::fmt::Display for ValidType {
@@ -68,7 +68,7 @@ impl core::fmt::Display for ValidType {
                 Ok(())
             },
             Self::PVer(_) => unimplemented!(),
-            Self::_USize(v) => write!(f, "{v}"),
+            Self::USize(v) => write!(f, "{v}"),
         }
     }
 }
@@ -99,6 +99,18 @@ pub enum ParseError<IoError: core::fmt::Debug> {
         version:  u32,
     },
 
+    /// Table version this parser can't handle, with versions packed as
+    /// `(major << 16) | minor`
+    #[error(
+        "Unsupported {} version {version:#010x} (supported {supported:08x?})",
+        ValidType::Tag(*.tag)
+    )]
+    UnsupportedVersion {
+        tag:       [u8; 4],
+        version:   u32,
+        supported: &'static [u32],
+    },
+
     /// Allocator failed
     #[error("Allocating {location} failed (expected {expected}, got {allocated}")]
     Allocation {
@@ -116,6 +128,10 @@ pub enum ParseError<IoError: core::fmt::Debug> {
         needed:   usize,
     },
 
+    /// A limit from [`crate::FontOptions`] was exceeded
+    #[error("Exceeded {budget} budget of {limit}")]
+    BudgetExceeded { budget: &'static str, limit: usize },
+
     #[error("Missing required table {missing} to parse {parsing}")]
     MissingTable {
         missing: &'static str,
@@ -10,7 +10,7 @@ union SlotContent<T> {
     next_free: u16,
 }
 
-/// ```
+/// ```text
 /// |       u32        |
 /// |  u16   |   u16   |
 /// | index  | version |
@@ -56,6 +56,42 @@ impl<T, A: core::alloc::Allocator> Slotmap<T, A> {
         }
     }
 
+    pub fn allocator(&self) -> &A {
+        self.slots.allocator()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        usize::from(self.num_elems)
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.num_elems == 0
+    }
+
+    /// Drops every value, invalidating all outstanding keys. Slots are kept
+    /// for reuse
+    pub fn clear(&mut self) {
+        // Rebuilt in ascending order, with the last slot pointing at itself so
+        // the list reads as empty once it's reused
+        let mut next_free = u16::try_from(self.slots.len() - 1).expect("Unexpected truncation");
+        for (index, (content, version)) in self.slots.iter_mut().enumerate().rev() {
+            if *version % 2 == 1 {
+                unsafe {
+                    core::mem::ManuallyDrop::drop(&mut content.value);
+                }
+                *version = version.wrapping_add(1);
+            }
+
+            *content = SlotContent { next_free };
+            next_free = u16::try_from(index).expect("Unexpected truncation");
+        }
+
+        self.next_free = 0;
+        self.num_elems = 0;
+    }
+
     pub fn push(
         &mut self,
         value: T,
@@ -64,19 +100,20 @@ impl<T, A: core::alloc::Allocator> Slotmap<T, A> {
 
         let (_, mut avail_ver) = self.slots[self.next_free as usize];
         if avail_ver % 2 == 1 {
-            // Push new slot
+            // Free list is empty, push new slot
+            let index = u16::try_from(self.slots.len()).expect("Unexpected truncation");
             self.slots.push((
                 SlotContent {
                     value: core::mem::ManuallyDrop::new(value),
                 },
                 1,
             ));
-            let key = u32::from(self.num_elems) << u16::BITS | 1;
 
-            self.next_free = self.num_elems;
+            // Pointing at an occupied slot marks the free list as empty
+            self.next_free = index;
             self.num_elems += 1;
 
-            key
+            u32::from(index) << u16::BITS | 1
         } else {
             avail_ver += 1;
             let index = usize::from(self.next_free);
@@ -102,7 +139,8 @@ impl<T, A: core::alloc::Allocator> Slotmap<T, A> {
         let version = u16::try_from(key & 0x0000_ffff_u32).expect("Unexpected truncation");
         let index = usize::try_from(key >> u16::BITS).expect("Failed to cast u16 (u32) to usize");
 
-        !(self.slots.len() < index || self.slots[index].1 != version)
+        // Free slots have even versions, which no valid key carries
+        version % 2 == 1 && self.slots.get(index).is_some_and(|(_, v)| *v == version)
     }
 
     pub fn get(
@@ -151,6 +189,8 @@ impl<T, A: core::alloc::Allocator> Slotmap<T, A> {
                 version + 1,
             ),
         );
+        self.next_free = u16::try_from(index).expect("Unexpected truncation");
+        self.num_elems -= 1;
 
         Some(unsafe { core::mem::ManuallyDrop::into_inner(value.value) })
     }
@@ -185,8 +225,9 @@ impl<T, A: core::alloc::Allocator> Slotmap<T, A> {
 
 impl<T, A: core::alloc::Allocator> Drop for Slotmap<T, A> {
     fn drop(&mut self) {
+        // Occupied slots have odd versions
         for (value, version) in &mut self.slots {
-            if *version % 2 == 0 {
+            if *version % 2 == 1 {
                 unsafe {
                     core::mem::ManuallyDrop::drop(&mut value.value);
                 }
@@ -239,11 +280,34 @@ impl<T, A: core::alloc::Allocator> SecondaryMap<T, A> {
         let version = u16::try_from(key & 0x0000_ffff_u32).expect("Unexpected truncation");
         let index = usize::try_from(key >> u16::BITS).expect("Failed to cast u16 (u32) to usize");
 
-        self.items
-            .resize_with(usize::from(self.num_elems).max(index + 1), || None);
+        if self.items.len() <= index {
+            self.items.resize_with(index + 1, || None);
+        }
+        if self.items[index].is_none() {
+            self.num_elems += 1;
+        }
         self.items[index] = Some((value, unsafe { NonZeroU16::new_unchecked(version | 1) }));
     }
 
+    pub fn remove(
+        &mut self,
+        key: Key,
+    ) -> Option<T> {
+        let index = usize::try_from(key >> u16::BITS).expect("Failed to cast u16 (u32) to usize");
+
+        if !self.contains(key) {
+            return None;
+        }
+
+        self.num_elems -= 1;
+        self.items[index].take().map(|(v, _)| v)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.num_elems = 0;
+    }
+
     pub fn get(
         &self,
         key: Key,
@@ -270,3 +334,92 @@ impl<T, A: core::alloc::Allocator> SecondaryMap<T, A> {
         self.items[index].as_mut().map(|(v, _)| v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn index(key: Key) -> u32 {
+        key >> u16::BITS
+    }
+
+    #[test]
+    fn keys_index_their_slot_past_recycled_ones() {
+        let mut map = Slotmap::new(alloc::alloc::Global);
+        let a = map.push('a');
+        let b = map.push('b');
+        let c = map.push('c');
+        assert_eq!([a, b, c].map(index), [0, 1, 2]);
+
+        assert_eq!(map.try_pop(b), Some('b'));
+        let d = map.push('d');
+        assert_eq!(index(d), index(b));
+        assert_ne!(d, b);
+
+        // Free list is empty again, so new slots go on the end
+        let e = map.push('e');
+        let f = map.push('f');
+        assert_eq!([e, f].map(index), [3, 4]);
+
+        assert_eq!(map.get(b), None);
+        for (key, value) in [(a, 'a'), (c, 'c'), (d, 'd'), (e, 'e'), (f, 'f')] {
+            assert_eq!(map.get(key), Some(&value));
+        }
+    }
+
+    #[test]
+    fn clear_drops_values_and_invalidates_keys() {
+        let value = alloc::rc::Rc::new(());
+        let mut map = Slotmap::new(alloc::alloc::Global);
+        assert!(map.is_empty());
+
+        let keys: Vec<_> = (0..3).map(|_| map.push(value.clone())).collect();
+        assert!(map.try_pop(keys[1]).is_some());
+        assert_eq!(map.len(), 2);
+        assert!(!map.is_empty());
+        assert_eq!(alloc::rc::Rc::strong_count(&value), 3);
+
+        map.clear();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+        assert_eq!(alloc::rc::Rc::strong_count(&value), 1);
+        for key in &keys {
+            assert!(!map.contains(*key));
+            assert!(map.get(*key).is_none());
+        }
+
+        // Slots are reused, under new versions
+        let reused: Vec<_> = (0..4).map(|_| map.push(value.clone())).collect();
+        assert_eq!(reused.iter().map(|key| index(*key)).collect::<Vec<_>>(), [
+            0, 1, 2, 3
+        ]);
+        assert_eq!(map.len(), 4);
+        for key in &keys {
+            assert!(!map.contains(*key));
+        }
+        drop(map);
+        assert_eq!(alloc::rc::Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn keys_past_the_end_are_missing() {
+        let mut map = Slotmap::new(alloc::alloc::Global);
+        let key = map.push('a');
+        map.push('b');
+
+        // With the odd versions that only occupied slots carry
+        for index in [2u32, 3, u32::from(u16::MAX)] {
+            let past_end = index << u16::BITS | 1;
+            assert!(!map.contains(past_end));
+            assert_eq!(map.get(past_end), None);
+            assert_eq!(map.try_pop(past_end), None);
+        }
+
+        let mut secondary = SecondaryMap::new(alloc::alloc::Global);
+        secondary.insert(key, 'c');
+        let past_end = 1 << u16::BITS | 1;
+        assert!(!secondary.contains(past_end));
+        assert_eq!(secondary.get(past_end), None);
+        assert_eq!(secondary.remove(past_end), None);
+    }
+}